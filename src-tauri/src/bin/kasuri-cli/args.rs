@@ -0,0 +1,304 @@
+//! Command line argument parsing for the KASURI CLI.
+
+use kasuri::core::archive::PathMapping;
+use kasuri::core::launcher_import::Launcher;
+use std::path::PathBuf;
+
+/// Usage text printed by `help` and on invalid arguments
+pub const USAGE: &str = "\
+Usage: kasuri-cli <COMMAND>
+
+Commands:
+  search <QUERY> [--json]   Search applications the same way the window does
+  list [--json]             List all applications in the catalog
+  launch <APP_ID>           Launch an application
+  rescan                    Rescan the application search paths
+  stats [--json]            Show statistics about the catalog and its usage
+  settings validate         Check the settings file
+  export <FILE>             Export settings, pins and usage statistics to FILE
+  import <FILE> [--dry-run] [--map FROM=TO]... [--json]
+                            Merge an exported FILE into the current data
+  import-launcher <LAUNCHER> [DIR] [--apply] [--json]
+                            Preview importing from powertoys, flow, wox or keypirinha, --apply to import
+  help                      Show this help";
+
+/// Subcommands of the KASURI CLI.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    /// Search applications and print the results in ranking order
+    Search { query: String, json: bool },
+    /// List all applications in the catalog
+    List { json: bool },
+    /// Launch the application with the given ID
+    Launch { app_id: String },
+    /// Rescan the application search paths
+    Rescan,
+    /// Print statistics about the catalog
+    Stats { json: bool },
+    /// Validate the settings file
+    SettingsValidate,
+    /// Export the settings and the usage statistics to an archive
+    Export { file: PathBuf },
+    /// Merge an archive into the current settings and usage statistics
+    Import {
+        file: PathBuf,
+        mappings: Vec<PathMapping>,
+        dry_run: bool,
+        json: bool,
+    },
+    /// Import aliases and usage history from another launcher
+    ImportLauncher {
+        launcher: Launcher,
+        dir: Option<PathBuf>,
+        apply: bool,
+        json: bool,
+    },
+    /// Print the usage text
+    Help,
+}
+
+/// Parses the command line arguments, without the program name.
+///
+/// # Arguments
+///
+/// * `args` - The arguments passed to the CLI
+///
+/// # Returns
+///
+/// The parsed command, or a message describing why the arguments are invalid
+pub fn parse_args<I>(args: I) -> Result<CliCommand, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(CliCommand::Help);
+    };
+    let mut json = false;
+    let mut dry_run = false;
+    let mut apply = false;
+    let mut mappings = vec![];
+    let mut positional: Vec<String> = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--dry-run" => dry_run = true,
+            "--apply" => apply = true,
+            "--map" => {
+                let value = args.next().ok_or("Missing value for --map")?;
+                mappings.push(value.parse::<PathMapping>()?);
+            }
+            _ if arg.starts_with("--map=") => {
+                mappings.push(arg["--map=".len()..].parse::<PathMapping>()?);
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let command = match command.as_str() {
+        "search" => {
+            if positional.is_empty() {
+                return Err("Missing search query".to_string());
+            }
+            let query = positional
+                .iter()
+                .map(|arg| arg.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            CliCommand::Search { query, json }
+        }
+        "list" => CliCommand::List { json },
+        "stats" => CliCommand::Stats { json },
+        "launch" => match &positional[..] {
+            [app_id] => CliCommand::Launch {
+                app_id: app_id.clone(),
+            },
+            [] => return Err("Missing application ID".to_string()),
+            _ => return Err("Too many arguments for launch".to_string()),
+        },
+        "rescan" => CliCommand::Rescan,
+        "export" => match &positional[..] {
+            [file] => CliCommand::Export {
+                file: PathBuf::from(file),
+            },
+            [] => return Err("Missing archive file".to_string()),
+            _ => return Err("Too many arguments for export".to_string()),
+        },
+        "import" => match &positional[..] {
+            [file] => CliCommand::Import {
+                file: PathBuf::from(file),
+                mappings: std::mem::take(&mut mappings),
+                dry_run,
+                json,
+            },
+            [] => return Err("Missing archive file".to_string()),
+            _ => return Err("Too many arguments for import".to_string()),
+        },
+        "import-launcher" => match &positional[..] {
+            [launcher, rest @ ..] if rest.len() <= 1 => CliCommand::ImportLauncher {
+                launcher: launcher.parse()?,
+                dir: rest.first().map(PathBuf::from),
+                apply,
+                json,
+            },
+            [] => return Err("Missing launcher".to_string()),
+            _ => return Err("Too many arguments for import-launcher".to_string()),
+        },
+        "settings" => match &positional[..] {
+            [subcommand] if subcommand == "validate" => CliCommand::SettingsValidate,
+            _ => return Err("Unknown settings command, expected: settings validate".to_string()),
+        },
+        "help" | "--help" | "-h" => CliCommand::Help,
+        _ => return Err(format!("Unknown command: {}", command)),
+    };
+
+    let takes_positional = matches!(
+        command,
+        CliCommand::Search { .. }
+            | CliCommand::Launch { .. }
+            | CliCommand::SettingsValidate
+            | CliCommand::Export { .. }
+            | CliCommand::Import { .. }
+            | CliCommand::ImportLauncher { .. }
+    );
+    if !takes_positional && !positional.is_empty() {
+        return Err(format!("Unexpected argument: {}", positional[0]));
+    }
+    let takes_json = matches!(
+        command,
+        CliCommand::Search { .. }
+            | CliCommand::List { .. }
+            | CliCommand::Stats { .. }
+            | CliCommand::Import { .. }
+            | CliCommand::ImportLauncher { .. }
+    );
+    if json && !takes_json {
+        return Err("Option --json is not supported by this command".to_string());
+    }
+    let is_import = matches!(command, CliCommand::Import { .. });
+    if (dry_run || !mappings.is_empty()) && !is_import {
+        return Err("Options --dry-run and --map are only supported by import".to_string());
+    }
+    if apply && !matches!(command, CliCommand::ImportLauncher { .. }) {
+        return Err("Option --apply is only supported by import-launcher".to_string());
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_search() {
+        assert_eq!(
+            parse(&["search", "visual", "studio", "--json"]),
+            Ok(CliCommand::Search {
+                query: "visual studio".to_string(),
+                json: true
+            })
+        );
+        assert!(parse(&["search"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_commands_without_arguments() {
+        assert_eq!(parse(&[]), Ok(CliCommand::Help));
+        assert_eq!(parse(&["list"]), Ok(CliCommand::List { json: false }));
+        assert_eq!(
+            parse(&["stats", "--json"]),
+            Ok(CliCommand::Stats { json: true })
+        );
+        assert_eq!(parse(&["rescan"]), Ok(CliCommand::Rescan));
+        assert_eq!(
+            parse(&["settings", "validate"]),
+            Ok(CliCommand::SettingsValidate)
+        );
+    }
+
+    #[test]
+    fn test_parse_launch() {
+        assert_eq!(
+            parse(&["launch", "C:\\Apps\\App.exe"]),
+            Ok(CliCommand::Launch {
+                app_id: "C:\\Apps\\App.exe".to_string()
+            })
+        );
+        assert!(parse(&["launch"]).is_err());
+        assert!(parse(&["launch", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_export_and_import() {
+        assert_eq!(
+            parse(&["export", "kasuri.json"]),
+            Ok(CliCommand::Export {
+                file: PathBuf::from("kasuri.json")
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "import",
+                "kasuri.json",
+                "--dry-run",
+                "--map",
+                "D:\\Tools=C:\\Tools",
+                "--map=/opt=/usr/local",
+            ]),
+            Ok(CliCommand::Import {
+                file: PathBuf::from("kasuri.json"),
+                mappings: vec![
+                    "D:\\Tools=C:\\Tools".parse().unwrap(),
+                    "/opt=/usr/local".parse().unwrap(),
+                ],
+                dry_run: true,
+                json: false,
+            })
+        );
+        assert!(parse(&["import"]).is_err());
+        assert!(parse(&["import", "kasuri.json", "--map"]).is_err());
+        assert!(parse(&["import", "kasuri.json", "--map", "D:\\Tools"]).is_err());
+        assert!(parse(&["export", "kasuri.json", "--dry-run"]).is_err());
+        assert!(parse(&["list", "--map", "a=b"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_import_launcher() {
+        assert_eq!(
+            parse(&["import-launcher", "flow", "--apply"]),
+            Ok(CliCommand::ImportLauncher {
+                launcher: Launcher::FlowLauncher,
+                dir: None,
+                apply: true,
+                json: false,
+            })
+        );
+        assert_eq!(
+            parse(&["import-launcher", "wox", "D:\\Wox\\Settings", "--json"]),
+            Ok(CliCommand::ImportLauncher {
+                launcher: Launcher::Wox,
+                dir: Some(PathBuf::from("D:\\Wox\\Settings")),
+                apply: false,
+                json: true,
+            })
+        );
+        assert!(parse(&["import-launcher"]).is_err());
+        assert!(parse(&["import-launcher", "unknown"]).is_err());
+        assert!(parse(&["import-launcher", "wox", "a", "b"]).is_err());
+        assert!(parse(&["import", "kasuri.json", "--apply"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_arguments() {
+        assert!(parse(&["unknown"]).is_err());
+        assert!(parse(&["list", "--verbose"]).is_err());
+        assert!(parse(&["list", "extra"]).is_err());
+        assert!(parse(&["rescan", "--json"]).is_err());
+        assert!(parse(&["settings", "edit"]).is_err());
+    }
+}
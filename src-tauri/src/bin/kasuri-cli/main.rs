@@ -0,0 +1,278 @@
+//! Headless command line interface of KASURI.
+//!
+//! Uses the same core, settings and database as the window, so that KASURI
+//! can be scripted and the ranking can be checked from a terminal.
+
+mod args;
+
+use crate::args::{CliCommand, USAGE, parse_args};
+use kasuri::core::archive::{ImportReport, KasuriArchive};
+use kasuri::core::paths::KasuriPaths;
+use kasuri::core::policy::Policy;
+use kasuri::core::settings::Settings;
+use kasuri::{Kasuri, KasuriError, KasuriResult};
+use std::process::ExitCode;
+
+/// Exit code for invalid command line arguments
+const EXIT_CODE_USAGE: u8 = 2;
+
+/// Runs the given command.
+///
+/// # Arguments
+///
+/// * `command` - The command to run
+/// * `paths` - The locations of the settings file, the database and the caches
+///
+/// # Returns
+///
+/// A `KasuriResult<()>` indicating success or failure of the command
+fn run(command: CliCommand, paths: KasuriPaths) -> KasuriResult<()> {
+    match command {
+        CliCommand::Help => println!("{}", USAGE),
+        CliCommand::SettingsValidate => validate_settings(&paths)?,
+        CliCommand::Search { query, json } => {
+            let kasuri = start(paths)?;
+            let results = kasuri.handle_search_application(&query);
+            if json {
+                print_json(&results)?;
+            } else {
+                results
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, app)| println!("{}. {}\t{}", i + 1, app.name, app.app_id));
+            }
+        }
+        CliCommand::List { json } => {
+            let kasuri = start(paths)?;
+            let mut applications: Vec<_> = kasuri.get_applications().iter().collect();
+            applications.sort_by_key(|app| app.name.to_lowercase());
+            if json {
+                let applications: Vec<_> = applications
+                    .iter()
+                    .map(|app| {
+                        serde_json::json!({
+                            "name": app.name,
+                            "app_id": app.app_id,
+                            "path": app.path,
+                            "pinned": app.pinned,
+                        })
+                    })
+                    .collect();
+                print_json(&applications)?;
+            } else {
+                applications
+                    .iter()
+                    .for_each(|app| println!("{}\t{}\t{}", app.name, app.app_id, app.path));
+            }
+        }
+        CliCommand::Launch { app_id } => {
+            let mut kasuri = start(paths)?;
+            kasuri.handle_launch_application(&app_id)?;
+        }
+        CliCommand::Rescan => {
+            let mut kasuri = start(paths)?;
+            kasuri.load_applications_to_cache()?;
+            println!("Found {} applications", kasuri.get_applications().len());
+        }
+        CliCommand::Export { file } => {
+            let kasuri = start(paths)?;
+            let archive = kasuri.handle_export_archive()?;
+            archive.write(&file)?;
+            println!(
+                "Exported settings and {} applications to {}",
+                archive.applications.len(),
+                file.display()
+            );
+        }
+        CliCommand::Import {
+            file,
+            mappings,
+            dry_run,
+            json,
+        } => {
+            let archive = KasuriArchive::read(&file)?;
+            let mut kasuri = start(paths)?;
+            let report = kasuri.handle_import_archive(&archive, &mappings, dry_run)?;
+            if json {
+                print_json(&report)?;
+            } else {
+                print_import_report(&report);
+            }
+        }
+        CliCommand::ImportLauncher {
+            launcher,
+            dir,
+            apply,
+            json,
+        } => {
+            let dir = dir.unwrap_or_else(|| launcher.get_default_dir());
+            let data = launcher.read(&dir)?;
+            let mut kasuri = start(paths)?;
+            let report = kasuri.handle_import_launcher(&data, !apply)?;
+            if json {
+                print_json(&report)?;
+            } else {
+                print_import_report(&report);
+                if !apply {
+                    println!("Run again with --apply to import.");
+                }
+            }
+        }
+        CliCommand::Stats { json } => {
+            let kasuri = start(paths)?;
+            let stats = kasuri.handle_get_stats()?;
+            if json {
+                print_json(&stats)?;
+            } else {
+                println!("Applications: {}", stats.application_count);
+                println!("Used applications: {}", stats.used_application_count);
+                println!("Failing applications: {}", stats.failing_application_count);
+                println!(
+                    "Last scan: {}",
+                    format_timestamp(stats.last_application_search_time)
+                );
+                println!("Most used applications:");
+                stats.most_used_applications.iter().for_each(|usage| {
+                    println!(
+                        "  {:.3}\t{}\t{}",
+                        usage.usage_recency_score, usage.name, usage.app_id
+                    )
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Loads the settings and creates an initialized Kasuri instance.
+///
+/// # Arguments
+///
+/// * `paths` - The locations of the settings file, the database and the caches
+///
+/// # Returns
+///
+/// A `KasuriResult<Kasuri>` containing the initialized Kasuri instance or an error
+fn start(paths: KasuriPaths) -> KasuriResult<Kasuri> {
+    paths.create_directories()?;
+    let policy = Policy::load_or_empty(paths.get_policy_file());
+    let settings = Settings::load_or_recover(paths.get_settings_file(), &policy)?;
+    let mut kasuri = Kasuri::with_settings(settings, paths)?.with_policy(policy);
+    kasuri.init()?;
+    Ok(kasuri)
+}
+
+/// Checks that the settings file exists and can be loaded.
+///
+/// Unlike starting KASURI, a missing settings file is reported
+/// instead of being created with the default settings,
+/// and an invalid policy file is reported instead of being ignored.
+///
+/// # Arguments
+///
+/// * `paths` - The locations of the settings file, the database and the caches
+///
+/// # Returns
+///
+/// A `KasuriResult<()>` indicating whether the settings are valid
+fn validate_settings(paths: &KasuriPaths) -> KasuriResult<()> {
+    let settings_file = paths.get_settings_file();
+    if !settings_file.is_file() {
+        return Err(KasuriError::Settings(format!(
+            "Settings file not found: {}",
+            settings_file.display()
+        )));
+    }
+    let policy = Policy::load(paths.get_policy_file())?;
+    let (_, errors) = Settings::load_and_validate_with_policy(settings_file, &policy)?;
+    if !errors.is_empty() {
+        errors.iter().for_each(|e| eprintln!("{}", e));
+        return Err(KasuriError::InvalidSettings(errors));
+    }
+    println!("Settings are valid: {}", settings_file.display());
+    Ok(())
+}
+
+/// Prints what was imported, or would be imported in a dry run.
+fn print_import_report(report: &ImportReport) {
+    if report.dry_run {
+        println!("Dry run, nothing was changed.");
+    }
+    println!("Settings added: {}", report.settings_added.len());
+    report
+        .settings_added
+        .iter()
+        .for_each(|entry| println!("  {}", entry));
+    println!("Applications merged: {}", report.applications.len());
+    report.applications.iter().for_each(|app| {
+        println!(
+            "  {}\t{}{}",
+            app.name,
+            app.app_id,
+            if app.remapped {
+                format!("\t(from {})", app.archived_app_id)
+            } else {
+                String::new()
+            }
+        )
+    });
+    println!(
+        "Applications not found: {}",
+        report.applications_not_found.len()
+    );
+    report
+        .applications_not_found
+        .iter()
+        .for_each(|app_id| println!("  {}", app_id));
+    if !report.aliases_not_found.is_empty() {
+        println!("Aliases not found: {}", report.aliases_not_found.len());
+        report
+            .aliases_not_found
+            .iter()
+            .for_each(|alias| println!("  {}", alias));
+    }
+}
+
+/// Prints the given value as pretty JSON.
+fn print_json<T: serde::Serialize>(value: &T) -> KasuriResult<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| KasuriError::Platform(format!("Failed to serialize output: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Formats seconds since the Unix epoch as local time.
+fn format_timestamp(seconds: u64) -> String {
+    if seconds == 0 {
+        return "never".to_string();
+    }
+    chrono::DateTime::from_timestamp(seconds as i64, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| seconds.to_string())
+}
+
+/// Main function of the KASURI CLI.
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(EXIT_CODE_USAGE);
+        }
+    };
+    let paths = KasuriPaths::default();
+    if let Err(e) = paths.migrate_legacy_files() {
+        eprintln!("Failed to migrate files from previous version: {}", e);
+    }
+    match run(command, paths) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Export archive that takes the settings and the usage statistics to another machine.
+//!
+//! The archive is a single JSON file with the settings as in `settings.toml`
+//! and the usage count, last use and pinned state of each application that
+//! has been used or pinned. Paths are stored in a portable form, see
+//! `contract_path`, and can be remapped on import with `PathMapping`s:
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "exported_at": 1760000000,
+//!   "settings": { "settings_version": 1, "width": 800, ... },
+//!   "applications": [
+//!     { "app_id": "%APPDATA%\\Microsoft\\Windows\\Start Menu\\Programs\\App.lnk",
+//!       "name": "App", "usage_count": 12, "last_used": 1760000000, "pinned": true }
+//!   ]
+//! }
+//! ```
+
+use crate::core::path_expansion::starts_with_directory;
+use crate::{KasuriError, KasuriResult};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current format version of the archive
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Settings and usage statistics exported from KASURI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KasuriArchive {
+    /// Format version of the archive, see `ARCHIVE_FORMAT_VERSION`
+    pub format_version: u32,
+    /// Time of the export in seconds since the Unix epoch
+    pub exported_at: u64,
+    /// The settings as in the settings file, without the values enforced by the policy
+    pub settings: toml::Table,
+    /// Usage statistics of the applications that have been used or pinned
+    pub applications: Vec<ArchivedApplication>,
+}
+
+/// Usage statistics of a single application in an archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedApplication {
+    /// Unique identifier of the application, with the start of paths replaced by variables
+    pub app_id: String,
+    /// Display name of the application
+    pub name: String,
+    /// Number of times the application has been used
+    pub usage_count: i64,
+    /// Time of the last use in seconds since the Unix epoch, 0 if never used
+    pub last_used: i64,
+    /// Whether the application is pinned to the top of the search results
+    pub pinned: bool,
+}
+
+/// Replaces the start of paths when importing an archive from another machine,
+/// written as `FROM=TO` on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMapping {
+    /// Directory on the machine the archive was exported from
+    pub from: String,
+    /// Directory on this machine
+    pub to: String,
+}
+
+/// Application of this machine that an archived application was matched to.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApplicationMatch<'a> {
+    /// The application has the same ID on this machine
+    Same(&'a str),
+    /// The only application on this machine with the same file name
+    Remapped(&'a str),
+    /// No application of this machine matches
+    NotFound,
+}
+
+/// Result of importing an archive.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    /// Whether the import was only simulated and nothing was changed
+    pub dry_run: bool,
+    /// Description of each entry added to the settings
+    pub settings_added: Vec<String>,
+    /// Applications whose usage statistics were merged
+    pub applications: Vec<ImportedApplication>,
+    /// IDs of the archived applications that were not found on this machine
+    pub applications_not_found: Vec<String>,
+    /// Aliases whose application was not found on this machine, as `alias: target`
+    pub aliases_not_found: Vec<String>,
+}
+
+/// Usage statistics merged into an application of this machine.
+#[derive(Debug, Serialize)]
+pub struct ImportedApplication {
+    /// Display name of the application
+    pub name: String,
+    /// Unique identifier of the application on this machine
+    pub app_id: String,
+    /// Unique identifier of the application in the archive, or the key in another launcher
+    pub archived_app_id: String,
+    /// Whether the application was matched by its file name or name instead of its ID
+    pub remapped: bool,
+    /// Number of uses added to the application
+    pub usage_count: i64,
+    /// Whether the application is pinned in the archive
+    pub pinned: bool,
+}
+
+impl KasuriArchive {
+    /// Creates an archive with the current time as the time of the export.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings as in the settings file
+    /// * `applications` - Usage statistics of the applications
+    ///
+    /// # Returns
+    ///
+    /// A new archive in the current format version
+    pub fn new(settings: toml::Table, applications: Vec<ArchivedApplication>) -> Self {
+        Self {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            exported_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            settings,
+            applications,
+        }
+    }
+
+    /// Reads an archive file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the archive file
+    ///
+    /// # Returns
+    ///
+    /// The archive read from the file
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Io` if the file cannot be read,
+    /// or `KasuriError::Archive` if it is not a valid archive, see `parse`
+    pub fn read(path: &Path) -> KasuriResult<Self> {
+        log::info!("Reading archive: {}", path.display());
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses an archive from JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The contents of an archive file
+    ///
+    /// # Returns
+    ///
+    /// The parsed archive
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Archive` if the source is not a valid archive
+    /// or was written by a newer version of KASURI
+    pub fn parse(source: &str) -> KasuriResult<Self> {
+        let archive: Self = serde_json::from_str(source)
+            .map_err(|e| KasuriError::Archive(format!("Invalid archive: {}", e)))?;
+        if archive.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(KasuriError::Archive(format!(
+                "Archive was written by a newer version (format version {}, supported {})",
+                archive.format_version, ARCHIVE_FORMAT_VERSION
+            )));
+        }
+        log::debug!(
+            "Archive parsed: format version {}, {} applications",
+            archive.format_version,
+            archive.applications.len()
+        );
+        Ok(archive)
+    }
+
+    /// Writes the archive to a file as pretty JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the archive file, replaced if it exists
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the write
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Archive` if the archive cannot be serialized,
+    /// or `KasuriError::Io` if the file cannot be written
+    pub fn write(&self, path: &Path) -> KasuriResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| KasuriError::Archive(format!("Failed to serialize archive: {}", e)))?;
+        std::fs::write(path, json)?;
+        log::info!(
+            "Archive written with {} applications: {}",
+            self.applications.len(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+impl FromStr for PathMapping {
+    type Err = String;
+
+    /// Parses a mapping written as `FROM=TO`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => Ok(Self {
+                from: from.trim_end_matches(['/', '\\']).to_string(),
+                to: to.trim_end_matches(['/', '\\']).to_string(),
+            }),
+            _ => Err(format!("Invalid path mapping '{}', expected FROM=TO", s)),
+        }
+    }
+}
+
+/// Replaces the start of a path with the first mapping that matches it.
+///
+/// # Arguments
+///
+/// * `mappings` - The mappings to try in order
+/// * `path` - The path as written in the archive
+///
+/// # Returns
+///
+/// The mapped path, or the path as it is if no mapping matches
+pub fn map_path(mappings: &[PathMapping], path: &str) -> String {
+    mappings
+        .iter()
+        .find(|mapping| starts_with_directory(path, &mapping.from))
+        .map(|mapping| format!("{}{}", mapping.to, &path[mapping.from.len()..]))
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Finds the application of this machine that an archived application refers to.
+///
+/// IDs are compared without regard to ASCII case. An application that is not found
+/// by its ID is matched by its file name, if exactly one application has that file name,
+/// so that applications found in other directories keep their usage statistics.
+///
+/// # Arguments
+///
+/// * `app_id` - ID of the archived application, mapped and expanded
+/// * `local_app_ids` - IDs of the applications of this machine
+///
+/// # Returns
+///
+/// The matching application of this machine
+pub fn match_application<'a>(app_id: &str, local_app_ids: &[&'a str]) -> ApplicationMatch<'a> {
+    if let Some(local_app_id) = local_app_ids
+        .iter()
+        .find(|local_app_id| local_app_id.eq_ignore_ascii_case(app_id))
+    {
+        return ApplicationMatch::Same(local_app_id);
+    }
+    let Some(name) = file_name(app_id) else {
+        return ApplicationMatch::NotFound;
+    };
+    let mut candidates = local_app_ids.iter().filter(|local_app_id| {
+        file_name(local_app_id).is_some_and(|local_name| local_name.eq_ignore_ascii_case(name))
+    });
+    match (candidates.next(), candidates.next()) {
+        (Some(local_app_id), None) => ApplicationMatch::Remapped(local_app_id),
+        _ => ApplicationMatch::NotFound,
+    }
+}
+
+/// Returns the file name of an application ID that is a path.
+///
+/// # Returns
+///
+/// The last component of the path, or `None` if the ID is not a path,
+/// e.g. the ID of a Windows Store application
+fn file_name(app_id: &str) -> Option<&str> {
+    app_id
+        .rsplit_once(['/', '\\'])
+        .map(|(_, name)| name)
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rejects_newer_versions() {
+        let archive = KasuriArchive::new(toml::Table::new(), vec![]);
+        let json = serde_json::to_string(&archive).unwrap();
+        assert_eq!(KasuriArchive::parse(&json).unwrap(), archive);
+
+        let json = json.replace(
+            &format!("\"format_version\":{}", ARCHIVE_FORMAT_VERSION),
+            "\"format_version\":99",
+        );
+        assert!(matches!(
+            KasuriArchive::parse(&json),
+            Err(KasuriError::Archive(_))
+        ));
+        assert!(KasuriArchive::parse("{}").is_err());
+    }
+
+    #[test]
+    fn test_map_path() {
+        let mappings = vec![
+            "D:\\Tools\\=C:\\Tools".parse::<PathMapping>().unwrap(),
+            "/opt=/usr/local".parse::<PathMapping>().unwrap(),
+        ];
+        assert_eq!(
+            map_path(&mappings, "d:\\tools\\App.exe"),
+            "C:\\Tools\\App.exe"
+        );
+        assert_eq!(map_path(&mappings, "/opt/app"), "/usr/local/app");
+        assert_eq!(map_path(&mappings, "/optional/app"), "/optional/app");
+        assert!("no-separator".parse::<PathMapping>().is_err());
+        assert!("=C:\\Tools".parse::<PathMapping>().is_err());
+    }
+
+    #[test]
+    fn test_match_application() {
+        let local_app_ids = [
+            "C:\\Apps\\Editor.lnk",
+            "C:\\Apps\\one\\Tool.exe",
+            "C:\\Apps\\two\\Tool.exe",
+            "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App",
+        ];
+        assert_eq!(
+            match_application("c:\\apps\\editor.lnk", &local_app_ids),
+            ApplicationMatch::Same("C:\\Apps\\Editor.lnk")
+        );
+        assert_eq!(
+            match_application("D:\\Old\\Editor.lnk", &local_app_ids),
+            ApplicationMatch::Remapped("C:\\Apps\\Editor.lnk")
+        );
+        assert_eq!(
+            match_application("D:\\Old\\Tool.exe", &local_app_ids),
+            ApplicationMatch::NotFound
+        );
+        assert_eq!(
+            match_application(
+                "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App",
+                &local_app_ids
+            ),
+            ApplicationMatch::Same("Microsoft.WindowsCalculator_8wekyb3d8bbwe!App")
+        );
+        assert_eq!(
+            match_application("Other_8wekyb3d8bbwe!App", &local_app_ids),
+            ApplicationMatch::NotFound
+        );
+    }
+}
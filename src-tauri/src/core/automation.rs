@@ -0,0 +1,869 @@
+//! Local automation API.
+//!
+//! A JSON-RPC 2.0 endpoint that lets other programs of the same user control KASURI.
+//! It listens on a named pipe on Windows and on a Unix domain socket elsewhere,
+//! so it is never reachable from the network. Every message is a single line of JSON.
+//!
+//! Methods:
+//!
+//! - `search` `{"query": "<text>"}` - Search applications the same way the window does
+//! - `launch` `{"app_id": "<id>"}` - Launch an application
+//! - `list` - List all applications in the catalog
+//! - `pin` / `unpin` `{"app_id": "<id>"}` - Rank an application above other matches, or stop doing so
+//! - `reload` - Rescan the application search paths
+//! - `subscribe` `{"events": ["app_launched", "catalog_updated"]}` - Receive events as
+//!   notifications whose method is the event name. All events are sent if `events` is omitted.
+
+use crate::core::paths::KasuriPaths;
+use crate::model::event::{EVENT_NAME_APP_LAUNCHED, EVENT_NAME_CATALOG_UPDATED, KasuriEvent};
+use crate::{Kasuri, KasuriError, KasuriResult};
+use interprocess::local_socket::prelude::*;
+use interprocess::local_socket::{ListenerNonblockingMode, ListenerOptions, Stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Version of the JSON-RPC protocol
+const JSONRPC_VERSION: &str = "2.0";
+/// Error code for messages that are not valid JSON
+const ERROR_CODE_PARSE_ERROR: i64 = -32700;
+/// Error code for messages that are not valid JSON-RPC requests
+const ERROR_CODE_INVALID_REQUEST: i64 = -32600;
+/// Error code for unknown methods
+const ERROR_CODE_METHOD_NOT_FOUND: i64 = -32601;
+/// Error code for missing or invalid parameters
+const ERROR_CODE_INVALID_PARAMS: i64 = -32602;
+/// Error code for failures reported by Kasuri, the `KasuriError` is passed as error data
+const ERROR_CODE_KASURI_ERROR: i64 = -32000;
+/// Events that can be subscribed to
+const SUBSCRIBABLE_EVENTS: [&str; 2] = [EVENT_NAME_APP_LAUNCHED, EVENT_NAME_CATALOG_UPDATED];
+/// How often the event forwarder checks whether its session has ended
+const FORWARDER_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the listener and idle connections check whether the server has been stopped
+const SERVER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Prefix of the named pipe, the user name is appended
+#[cfg(windows)]
+const PIPE_NAME_PREFIX: &str = "kasuri-automation";
+/// File name of the Unix domain socket in the data directory
+#[cfg(not(windows))]
+const SOCKET_FILE_NAME: &str = "kasuri.sock";
+
+/// Gives the automation API access to the shared Kasuri instance.
+///
+/// Requests from several connections are served concurrently,
+/// so the implementation has to lock the instance for the duration of `f`.
+pub trait KasuriAccess: Clone + Send + 'static {
+    /// Calls `f` with exclusive access to the Kasuri instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to call
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`
+    fn with_kasuri<R>(&self, f: impl FnOnce(&mut Kasuri) -> R) -> R;
+}
+
+impl KasuriAccess for Arc<Mutex<Kasuri>> {
+    fn with_kasuri<R>(&self, f: impl FnOnce(&mut Kasuri) -> R) -> R {
+        f(&mut self.lock().unwrap())
+    }
+}
+
+/// Methods of the automation API with their parameters.
+#[derive(Debug, PartialEq)]
+enum AutomationMethod {
+    Search { query: String },
+    Launch { app_id: String },
+    List,
+    Pin { app_id: String },
+    Unpin { app_id: String },
+    Reload,
+    Subscribe { events: Vec<String> },
+}
+
+/// A parsed JSON-RPC request.
+#[derive(Debug, PartialEq)]
+struct AutomationRequest {
+    /// ID of the request, `None` for notifications which are not answered
+    id: Option<Value>,
+    /// The requested method
+    method: AutomationMethod,
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug, PartialEq)]
+struct AutomationError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+/// JSON-RPC request as received from the client.
+#[derive(Deserialize)]
+struct RawRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Parameters of the `search` method
+#[derive(Deserialize)]
+struct SearchParams {
+    query: String,
+}
+
+/// Parameters of the methods that act on a single application
+#[derive(Deserialize)]
+struct AppIdParams {
+    app_id: String,
+}
+
+/// Parameters of the `subscribe` method
+#[derive(Deserialize, Default)]
+struct SubscribeParams {
+    events: Option<Vec<String>>,
+}
+
+impl AutomationError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<KasuriError> for AutomationError {
+    fn from(error: KasuriError) -> Self {
+        Self {
+            code: ERROR_CODE_KASURI_ERROR,
+            message: error.to_string(),
+            data: serde_json::to_value(&error).ok(),
+        }
+    }
+}
+
+/// Returns the endpoint the automation API listens on.
+///
+/// On Windows this is the name of a named pipe, which is made unique per user.
+/// Elsewhere it is the path of a Unix domain socket in the data directory.
+///
+/// # Arguments
+///
+/// * `paths` - The locations of the settings file, the database and the caches
+///
+/// # Returns
+///
+/// The endpoint as a string, to be passed to `start_server`
+pub fn default_endpoint(paths: &KasuriPaths) -> String {
+    #[cfg(windows)]
+    {
+        let _ = paths;
+        let user_name = std::env::var("USERNAME").unwrap_or_default();
+        format!("{}-{}", PIPE_NAME_PREFIX, user_name)
+    }
+    #[cfg(not(windows))]
+    {
+        paths
+            .get_data_dir()
+            .join(SOCKET_FILE_NAME)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Handle of a running automation API server, see `start_server`.
+pub struct AutomationServer {
+    /// Set when the server is stopped, checked by the listener and every connection
+    stopped: Arc<AtomicBool>,
+    /// The thread accepting connections
+    listener_thread: JoinHandle<()>,
+}
+
+impl AutomationServer {
+    /// Stops the server.
+    ///
+    /// Waits until the endpoint is closed, so that the server can be started again right away.
+    /// Connected clients are disconnected within `SERVER_POLL_INTERVAL`.
+    pub fn stop(self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if self.listener_thread.join().is_err() {
+            log::error!("Automation API listener thread panicked");
+        }
+        log::info!("Automation API stopped");
+    }
+}
+
+/// Starts serving the automation API on a background thread.
+///
+/// Every connection is served on its own thread until the client disconnects
+/// or the server is stopped.
+///
+/// # Arguments
+///
+/// * `endpoint` - The named pipe or socket path to listen on, see `default_endpoint`
+/// * `access` - Access to the Kasuri instance the requests are executed on
+///
+/// # Returns
+///
+/// A `KasuriResult<AutomationServer>` with the handle to stop the server
+///
+/// # Errors
+///
+/// Returns `KasuriError::Io` if the endpoint cannot be created, e.g. because it is in use
+pub fn start_server<A: KasuriAccess>(endpoint: &str, access: A) -> KasuriResult<AutomationServer> {
+    let listener = create_listener(endpoint)?;
+    log::info!("Automation API listening on {}", endpoint);
+    let stopped = Arc::new(AtomicBool::new(false));
+    let listener_stopped = stopped.clone();
+    let listener_thread = thread::Builder::new()
+        .name("automation-api".to_string())
+        .spawn(move || {
+            while !listener_stopped.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok(stream) => {
+                        let access = access.clone();
+                        let stopped = listener_stopped.clone();
+                        thread::spawn(move || serve_connection(stream, access, stopped));
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(SERVER_POLL_INTERVAL)
+                    }
+                    Err(e) => log::warn!("Failed to accept automation API connection: {}", e),
+                }
+            }
+        })?;
+    Ok(AutomationServer {
+        stopped,
+        listener_thread,
+    })
+}
+
+/// Creates the named pipe listener.
+#[cfg(windows)]
+fn create_listener(endpoint: &str) -> io::Result<LocalSocketListener> {
+    use interprocess::local_socket::GenericNamespaced;
+    let name = endpoint.to_ns_name::<GenericNamespaced>()?;
+    ListenerOptions::new()
+        .name(name)
+        .nonblocking(ListenerNonblockingMode::Both)
+        .create_sync()
+}
+
+/// Creates the Unix domain socket listener, replacing a socket left behind by a crash.
+#[cfg(not(windows))]
+fn create_listener(endpoint: &str) -> io::Result<LocalSocketListener> {
+    use interprocess::local_socket::GenericFilePath;
+    let name = endpoint.to_fs_name::<GenericFilePath>()?;
+    ListenerOptions::new()
+        .name(name)
+        .try_overwrite(true)
+        .nonblocking(ListenerNonblockingMode::Both)
+        .create_sync()
+}
+
+/// Serves a single client until it disconnects or the server is stopped.
+///
+/// The stream is nonblocking, so that a client that sends nothing is disconnected
+/// within `SERVER_POLL_INTERVAL` after the server is stopped.
+///
+/// # Arguments
+///
+/// * `stream` - The nonblocking connection to the client
+/// * `access` - Access to the Kasuri instance the requests are executed on
+/// * `stopped` - Set when the server is stopped
+fn serve_connection<A: KasuriAccess>(stream: Stream, access: A, stopped: Arc<AtomicBool>) {
+    log::info!("Automation API client connected");
+    let (receiver, sender) = stream.split();
+    let mut session = Session::new(access, RetryingWriter(sender));
+    let mut reader = BufReader::new(receiver);
+    // Bytes read before `WouldBlock` stay in the buffer until the rest of the line arrives.
+    let mut line = vec![];
+    while !stopped.load(Ordering::SeqCst) {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(SERVER_POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                log::debug!("Failed to read from automation API client: {}", e);
+                break;
+            }
+        }
+        let message = String::from_utf8_lossy(&line).trim().to_string();
+        line.clear();
+        if message.is_empty() {
+            continue;
+        }
+        if let Err(e) = session.handle_message(&message) {
+            log::debug!("Failed to write to automation API client: {}", e);
+            break;
+        }
+    }
+    log::info!("Automation API client disconnected");
+}
+
+/// Writes to a nonblocking stream, waiting while it cannot take more data.
+struct RetryingWriter<W>(W);
+
+impl<W: Write> Write for RetryingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            match self.0.write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(SERVER_POLL_INTERVAL)
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        loop {
+            match self.0.flush() {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(SERVER_POLL_INTERVAL)
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// State of a single client connection.
+struct Session<A, W> {
+    /// Access to the Kasuri instance the requests are executed on
+    access: A,
+    /// Where responses and notifications are written, shared with the event forwarder
+    output: Arc<Mutex<W>>,
+    /// Names of the subscribed events, `None` until the client subscribes.
+    /// The event forwarder stops when the session and with it this list is dropped.
+    subscribed_events: Option<Arc<Mutex<Vec<String>>>>,
+}
+
+impl<A: KasuriAccess, W: Write + Send + 'static> Session<A, W> {
+    fn new(access: A, output: W) -> Self {
+        Self {
+            access,
+            output: Arc::new(Mutex::new(output)),
+            subscribed_events: None,
+        }
+    }
+
+    /// Handles a single message and writes the response, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A JSON-RPC request
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result<()>` indicating whether the response could be written
+    fn handle_message(&mut self, message: &str) -> io::Result<()> {
+        log::debug!("Automation API request: {}", message);
+        let (id, result) = match parse_request(message) {
+            Ok(request) => {
+                let result = match request.method {
+                    AutomationMethod::Subscribe { events } => {
+                        self.subscribe(events);
+                        Ok(Value::Bool(true))
+                    }
+                    method => self.access.with_kasuri(|kasuri| execute(kasuri, method)),
+                };
+                match request.id {
+                    Some(id) => (id, result),
+                    None => return Ok(()),
+                }
+            }
+            Err((id, error)) => (id, Err(error)),
+        };
+        let response = match result {
+            Ok(result) => json!({"jsonrpc": JSONRPC_VERSION, "id": id, "result": result}),
+            Err(error) => {
+                log::warn!("Automation API request failed: {}", error.message);
+                let mut error_object = json!({"code": error.code, "message": error.message});
+                if let Some(data) = error.data {
+                    error_object["data"] = data;
+                }
+                json!({"jsonrpc": JSONRPC_VERSION, "id": id, "error": error_object})
+            }
+        };
+        write_message(&self.output, &response)
+    }
+
+    /// Subscribes the client to the given events.
+    ///
+    /// Subscribing again replaces the subscribed events instead of sending events twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - Names of the events to forward to the client
+    fn subscribe(&mut self, events: Vec<String>) {
+        log::debug!("Automation API client subscribed to {:?}", events);
+        if let Some(subscribed_events) = &self.subscribed_events {
+            *subscribed_events.lock().unwrap() = events;
+            return;
+        }
+        let subscribed_events = Arc::new(Mutex::new(events));
+        let weak_subscribed_events = Arc::downgrade(&subscribed_events);
+        self.subscribed_events = Some(subscribed_events);
+        let receiver = self.access.with_kasuri(|kasuri| kasuri.subscribe());
+        let output = self.output.clone();
+        thread::spawn(move || forward_events(receiver, weak_subscribed_events, output));
+    }
+}
+
+/// Forwards subscribed events to the client as JSON-RPC notifications.
+///
+/// Returns when the session has ended, at the latest after `FORWARDER_CHECK_INTERVAL`,
+/// or when an event cannot be written. The receiver is dropped on return, so that
+/// Kasuri removes the subscription.
+///
+/// # Arguments
+///
+/// * `receiver` - The subscription to the Kasuri events
+/// * `subscribed_events` - Names of the events to forward, owned by the session
+/// * `output` - Where the notifications are written
+fn forward_events<W: Write>(
+    receiver: Receiver<KasuriEvent>,
+    subscribed_events: Weak<Mutex<Vec<String>>>,
+    output: Arc<Mutex<W>>,
+) {
+    loop {
+        let event = receiver.recv_timeout(FORWARDER_CHECK_INTERVAL);
+        let Some(events) = subscribed_events.upgrade() else {
+            log::debug!("Stopped forwarding events, the automation API client disconnected");
+            break;
+        };
+        let event = match event {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let name = event.get_name();
+        if !events.lock().unwrap().iter().any(|e| e == name) {
+            continue;
+        }
+        let notification = json!({"jsonrpc": JSONRPC_VERSION, "method": name, "params": event});
+        if let Err(e) = write_message(&output, &notification) {
+            log::debug!("Stopped forwarding events to automation API client: {}", e);
+            break;
+        }
+    }
+}
+
+/// Writes a message followed by a newline.
+fn write_message<W: Write>(output: &Mutex<W>, message: &Value) -> io::Result<()> {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", message)?;
+    output.flush()
+}
+
+/// Parses a JSON-RPC request.
+///
+/// # Arguments
+///
+/// * `message` - The received message
+///
+/// # Returns
+///
+/// The parsed request, or the ID of the request and the error to respond with
+fn parse_request(message: &str) -> Result<AutomationRequest, (Value, AutomationError)> {
+    let value: Value = serde_json::from_str(message).map_err(|e| {
+        (
+            Value::Null,
+            AutomationError::new(ERROR_CODE_PARSE_ERROR, format!("Parse error: {}", e)),
+        )
+    })?;
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request: RawRequest = serde_json::from_value(value).map_err(|e| {
+        (
+            id.clone(),
+            AutomationError::new(
+                ERROR_CODE_INVALID_REQUEST,
+                format!("Invalid request: {}", e),
+            ),
+        )
+    })?;
+    if request.jsonrpc != JSONRPC_VERSION {
+        return Err((
+            id,
+            AutomationError::new(
+                ERROR_CODE_INVALID_REQUEST,
+                format!("Unsupported JSON-RPC version: {}", request.jsonrpc),
+            ),
+        ));
+    }
+    let method = parse_method(&request.method, request.params).map_err(|e| (id, e))?;
+    Ok(AutomationRequest {
+        id: request.id,
+        method,
+    })
+}
+
+/// Parses the method name and its parameters.
+fn parse_method(method: &str, params: Value) -> Result<AutomationMethod, AutomationError> {
+    let method = match method {
+        "search" => AutomationMethod::Search {
+            query: parse_params::<SearchParams>(params)?.query,
+        },
+        "launch" => AutomationMethod::Launch {
+            app_id: parse_params::<AppIdParams>(params)?.app_id,
+        },
+        "list" => AutomationMethod::List,
+        "pin" => AutomationMethod::Pin {
+            app_id: parse_params::<AppIdParams>(params)?.app_id,
+        },
+        "unpin" => AutomationMethod::Unpin {
+            app_id: parse_params::<AppIdParams>(params)?.app_id,
+        },
+        "reload" => AutomationMethod::Reload,
+        "subscribe" => {
+            let params = if params.is_null() {
+                SubscribeParams::default()
+            } else {
+                parse_params::<SubscribeParams>(params)?
+            };
+            let events = match params.events {
+                Some(events) => events,
+                None => SUBSCRIBABLE_EVENTS.iter().map(|e| e.to_string()).collect(),
+            };
+            if let Some(event) = events
+                .iter()
+                .find(|e| !SUBSCRIBABLE_EVENTS.contains(&e.as_str()))
+            {
+                return Err(AutomationError::new(
+                    ERROR_CODE_INVALID_PARAMS,
+                    format!("Unknown event: {}", event),
+                ));
+            }
+            AutomationMethod::Subscribe { events }
+        }
+        _ => {
+            return Err(AutomationError::new(
+                ERROR_CODE_METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            ));
+        }
+    };
+    Ok(method)
+}
+
+/// Deserializes the parameters of a method.
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, AutomationError> {
+    serde_json::from_value(params).map_err(|e| {
+        AutomationError::new(ERROR_CODE_INVALID_PARAMS, format!("Invalid params: {}", e))
+    })
+}
+
+/// Executes a method on the Kasuri instance.
+///
+/// # Arguments
+///
+/// * `kasuri` - The Kasuri instance
+/// * `method` - The method to execute, `subscribe` is handled by the session
+///
+/// # Returns
+///
+/// The result of the method as JSON, or the error to respond with
+fn execute(kasuri: &mut Kasuri, method: AutomationMethod) -> Result<Value, AutomationError> {
+    let result = match method {
+        AutomationMethod::Search { query } => json!(kasuri.handle_search_application(&query)),
+        AutomationMethod::Launch { app_id } => {
+            kasuri.handle_launch_application(&app_id)?;
+            Value::Null
+        }
+        AutomationMethod::List => {
+            let mut applications: Vec<_> = kasuri.get_applications().iter().collect();
+            applications.sort_by_key(|app| app.name.to_lowercase());
+            applications
+                .iter()
+                .map(|app| {
+                    json!({
+                        "name": app.name,
+                        "app_id": app.app_id,
+                        "path": app.path,
+                        "pinned": app.pinned,
+                    })
+                })
+                .collect()
+        }
+        AutomationMethod::Pin { app_id } => {
+            kasuri.handle_set_pinned(&app_id, true)?;
+            Value::Null
+        }
+        AutomationMethod::Unpin { app_id } => {
+            kasuri.handle_set_pinned(&app_id, false)?;
+            Value::Null
+        }
+        AutomationMethod::Reload => {
+            kasuri.load_applications_to_cache()?;
+            json!({"application_count": kasuri.get_applications().len()})
+        }
+        AutomationMethod::Subscribe { .. } => {
+            return Err(AutomationError::new(
+                ERROR_CODE_INVALID_REQUEST,
+                "Subscriptions are handled by the connection",
+            ));
+        }
+    };
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::Settings;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    /// Creates a Kasuri instance that finds `Notepad.exe` and `Paint.exe` in a temporary directory.
+    fn start_kasuri(root: &std::path::Path) -> Arc<Mutex<Kasuri>> {
+        let apps_dir = root.join("apps");
+        fs::create_dir_all(&apps_dir).unwrap();
+        fs::write(apps_dir.join("Notepad.exe"), b"").unwrap();
+        fs::write(apps_dir.join("Paint.exe"), b"").unwrap();
+        let paths = KasuriPaths::builder()
+            .data_dir(root)
+            .cache_dir(root.join("cache"))
+            .build();
+        fs::write(
+            paths.get_settings_file(),
+            format!(
+                "application_search_path_list = [{}]\n",
+                toml::Value::String(apps_dir.to_string_lossy().to_string())
+            ),
+        )
+        .unwrap();
+        let settings = Settings::load(paths.get_settings_file()).unwrap();
+        let mut kasuri = Kasuri::with_settings(settings, paths).unwrap();
+        kasuri.init().unwrap();
+        Arc::new(Mutex::new(kasuri))
+    }
+
+    /// Returns the messages written to the session output.
+    fn messages(output: &Arc<Mutex<Vec<u8>>>) -> Vec<Value> {
+        String::from_utf8(output.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> String {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string()
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(
+            parse_request(
+                r#"{"jsonrpc":"2.0","id":1,"method":"search","params":{"query":"code"}}"#
+            ),
+            Ok(AutomationRequest {
+                id: Some(json!(1)),
+                method: AutomationMethod::Search {
+                    query: "code".to_string()
+                }
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"jsonrpc":"2.0","method":"reload"}"#),
+            Ok(AutomationRequest {
+                id: None,
+                method: AutomationMethod::Reload
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"jsonrpc":"2.0","id":"a","method":"subscribe"}"#),
+            Ok(AutomationRequest {
+                id: Some(json!("a")),
+                method: AutomationMethod::Subscribe {
+                    events: vec!["app_launched".to_string(), "catalog_updated".to_string()]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_request_errors() {
+        let code = |message: &str| parse_request(message).unwrap_err().1.code;
+        assert_eq!(code("{"), ERROR_CODE_PARSE_ERROR);
+        assert_eq!(
+            code(r#"{"id":1,"method":"list"}"#),
+            ERROR_CODE_INVALID_REQUEST
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"1.0","id":1,"method":"list"}"#),
+            ERROR_CODE_INVALID_REQUEST
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#),
+            ERROR_CODE_METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"launch","params":{}}"#),
+            ERROR_CODE_INVALID_PARAMS
+        );
+        assert_eq!(
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"subscribe","params":{"events":["x"]}}"#),
+            ERROR_CODE_INVALID_PARAMS
+        );
+        assert_eq!(
+            parse_request(r#"{"jsonrpc":"2.0","id":7}"#).unwrap_err().0,
+            json!(7)
+        );
+    }
+
+    #[test]
+    fn test_session_search_list_and_pin() {
+        let root = tempfile::tempdir().unwrap();
+        let kasuri = start_kasuri(root.path());
+        let output = Arc::new(Mutex::new(vec![]));
+        let mut session = Session {
+            access: kasuri.clone(),
+            output: output.clone(),
+            subscribed_events: None,
+        };
+
+        session
+            .handle_message(&request(1, "search", json!({"query": "notepad"})))
+            .unwrap();
+        let app_id = messages(&output)[0]["result"][0]["app_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        session
+            .handle_message(&request(2, "pin", json!({"app_id": app_id})))
+            .unwrap();
+        session
+            .handle_message(&request(3, "list", Value::Null))
+            .unwrap();
+        session
+            .handle_message(&request(4, "unpin", json!({"app_id": "unknown"})))
+            .unwrap();
+        // Notifications are executed but not answered.
+        session
+            .handle_message(r#"{"jsonrpc":"2.0","method":"reload"}"#)
+            .unwrap();
+
+        let messages = messages(&output);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[1],
+            json!({"jsonrpc": "2.0", "id": 2, "result": null})
+        );
+        let list = messages[2]["result"].as_array().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0]["name"], "Notepad");
+        assert_eq!(list[0]["pinned"], true);
+        assert_eq!(list[1]["pinned"], false);
+        assert_eq!(messages[3]["error"]["code"], ERROR_CODE_KASURI_ERROR);
+        assert_eq!(
+            messages[3]["error"]["data"]["code"],
+            "application_not_found"
+        );
+    }
+
+    #[test]
+    fn test_session_forwards_subscribed_events() {
+        let root = tempfile::tempdir().unwrap();
+        let kasuri = start_kasuri(root.path());
+        let output = Arc::new(Mutex::new(vec![]));
+        let mut session = Session {
+            access: kasuri.clone(),
+            output: output.clone(),
+            subscribed_events: None,
+        };
+
+        session
+            .handle_message(&request(
+                1,
+                "subscribe",
+                json!({"events": ["catalog_updated"]}),
+            ))
+            .unwrap();
+        kasuri.lock().unwrap().load_applications_to_cache().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while messages(&output).len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let messages = messages(&output);
+        assert_eq!(messages[0]["result"], true);
+        assert_eq!(
+            messages[1],
+            json!({
+                "jsonrpc": "2.0",
+                "method": "catalog_updated",
+                "params": {"application_count": 2}
+            })
+        );
+    }
+
+    #[test]
+    fn test_forwarder_stops_when_session_ends() {
+        let root = tempfile::tempdir().unwrap();
+        let kasuri = start_kasuri(root.path());
+        let output = Arc::new(Mutex::new(vec![]));
+        let mut session = Session {
+            access: kasuri.clone(),
+            output: output.clone(),
+            subscribed_events: None,
+        };
+        session
+            .handle_message(&request(1, "subscribe", Value::Null))
+            .unwrap();
+        assert_eq!(
+            kasuri
+                .lock()
+                .unwrap()
+                .event_subscribers
+                .lock()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        drop(session);
+        // The forwarder drops its receiver, so that publishing removes the subscription.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !kasuri
+            .lock()
+            .unwrap()
+            .event_subscribers
+            .lock()
+            .unwrap()
+            .is_empty()
+            && Instant::now() < deadline
+        {
+            thread::sleep(Duration::from_millis(50));
+            kasuri.lock().unwrap().notify_catalog_updated();
+        }
+        assert!(
+            kasuri
+                .lock()
+                .unwrap()
+                .event_subscribers
+                .lock()
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(messages(&output).len(), 1);
+    }
+}
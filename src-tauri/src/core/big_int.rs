@@ -0,0 +1,592 @@
+//! Arbitrary-precision signed integers used by the calculator.
+//!
+//! Only the operations that the calculator needs are implemented: addition, subtraction,
+//! multiplication, truncated division, powers, and conversion from and to text in
+//! the radixes 2 to 36. The numbers handled by the calculator are limited to a few
+//! thousand bits, so simple schoolbook algorithms are used throughout.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Number of bits of a digit of the magnitude
+const DIGIT_BITS: u32 = 32;
+
+/// A signed integer of any size.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigInt {
+    /// Whether the number is below zero, never set for zero
+    negative: bool,
+    /// Magnitude of the number, least significant digit first, without leading zero digits
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    /// Creates a number from its sign and magnitude, removing leading zero digits.
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        trim(&mut digits);
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    /// Parses the digits of a non-negative number.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The digits, without a sign or a radix prefix such as `0x`
+    /// * `radix` - The radix of the digits, from 2 to 36
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if the text is empty or contains a character that is not a digit
+    pub fn parse(text: &str, radix: u32) -> Option<Self> {
+        if text.is_empty() {
+            return None;
+        }
+        let mut digits = vec![];
+        for c in text.chars() {
+            let digit = c.to_digit(radix)?;
+            mul_add_small(&mut digits, radix, digit);
+        }
+        Some(Self::from_parts(false, digits))
+    }
+
+    /// Converts a float with an integral value to an integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The float to convert
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if the float is not finite or has a fractional part
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        let mut magnitude = value.abs();
+        let mut digits = vec![];
+        while magnitude >= 1.0 {
+            let digit = magnitude % 4294967296.0;
+            digits.push(digit as u32);
+            magnitude = (magnitude - digit) / 4294967296.0;
+        }
+        Some(Self::from_parts(value < 0.0, digits))
+    }
+
+    /// Returns whether the number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Returns whether the number is below zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the number of bits of the magnitude, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(top) => {
+                (self.digits.len() as u64 - 1) * DIGIT_BITS as u64
+                    + (DIGIT_BITS - top.leading_zeros()) as u64
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the absolute value of the number.
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.digits.clone())
+    }
+
+    /// Converts the number to the nearest float, infinite if it is too large.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &digit| acc * 4294967296.0 + digit as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    /// Converts the number to a `u32`.
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if it is negative or too large
+    pub fn to_u32(&self) -> Option<u32> {
+        match self.digits[..] {
+            _ if self.negative => None,
+            [] => Some(0),
+            [digit] => Some(digit),
+            _ => None,
+        }
+    }
+
+    /// Divides the number, truncating the quotient toward zero like the `/` and `%`
+    /// operators of Rust integers.
+    ///
+    /// # Arguments
+    ///
+    /// * `divisor` - The number to divide by
+    ///
+    /// # Returns
+    ///
+    /// The quotient and the remainder, which has the sign of this number,
+    /// or `None` if the divisor is zero
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.digits, &divisor.digits);
+        Some((
+            Self::from_parts(self.negative != divisor.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// Raises the number to a power.
+    ///
+    /// # Arguments
+    ///
+    /// * `exponent` - The power to raise the number to
+    ///
+    /// # Returns
+    ///
+    /// The number raised to the power, 1 if the exponent is 0
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = Self::from(1u64);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Formats the number in the given radix.
+    ///
+    /// # Arguments
+    ///
+    /// * `radix` - The radix of the digits, from 2 to 36
+    ///
+    /// # Returns
+    ///
+    /// The digits of the number in upper case, preceded by `-` if it is negative,
+    /// without a radix prefix
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut magnitude = self.digits.clone();
+        let mut text = vec![];
+        while !magnitude.is_empty() {
+            let remainder = div_rem_small(&mut magnitude, radix);
+            text.push(
+                std::char::from_digit(remainder, radix)
+                    .unwrap()
+                    .to_ascii_uppercase(),
+            );
+        }
+        if self.negative {
+            text.push('-');
+        }
+        text.iter().rev().collect()
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> Self {
+        Self::from_parts(false, vec![value as u32, (value >> DIGIT_BITS) as u32])
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = BigInt::from(value.unsigned_abs());
+        Self::from_parts(value < 0, magnitude.digits)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_radix(10))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        let negative = !self.negative;
+        BigInt::from_parts(negative, self.digits)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.digits, &other.digits));
+        }
+        match cmp_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitude(&other.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.digits.iter().enumerate() {
+                let value = digits[i + j] as u64 + a as u64 * b as u64 + carry;
+                digits[i + j] = value as u32;
+                carry = value >> DIGIT_BITS;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, digits)
+    }
+}
+
+/// Removes the leading zero digits of a magnitude.
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+/// Compares two magnitudes without leading zero digits.
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+/// Adds two magnitudes.
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut digits = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in long.iter().enumerate() {
+        let value = digit as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        digits.push(value as u32);
+        carry = value >> DIGIT_BITS;
+    }
+    digits.push(carry as u32);
+    digits
+}
+
+/// Subtracts a magnitude from a magnitude that is not smaller.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut value = digit as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = 0;
+        if value < 0 {
+            value += 1 << DIGIT_BITS;
+            borrow = 1;
+        }
+        digits.push(value as u32);
+    }
+    trim(&mut digits);
+    digits
+}
+
+/// Multiplies a magnitude by a small number and adds a small number to it, in place.
+fn mul_add_small(digits: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for digit in digits.iter_mut() {
+        let value = *digit as u64 * factor as u64 + carry;
+        *digit = value as u32;
+        carry = value >> DIGIT_BITS;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+    trim(digits);
+}
+
+/// Divides a magnitude by a small number in place and returns the remainder.
+fn div_rem_small(digits: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for digit in digits.iter_mut().rev() {
+        let value = (remainder << DIGIT_BITS) | *digit as u64;
+        *digit = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+    trim(digits);
+    remainder as u32
+}
+
+/// Divides a magnitude by a non-zero magnitude with binary long division.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let remainder = div_rem_small(&mut quotient, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..a.len() * DIGIT_BITS as usize).rev() {
+        // remainder = remainder * 2 + the next bit of the dividend
+        let mut carry = (a[bit / DIGIT_BITS as usize] >> (bit % DIGIT_BITS as usize)) & 1;
+        for digit in remainder.iter_mut() {
+            let next = *digit >> (DIGIT_BITS - 1);
+            *digit = (*digit << 1) | carry;
+            carry = next;
+        }
+        if carry > 0 {
+            remainder.push(carry);
+        }
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[bit / DIGIT_BITS as usize] |= 1 << (bit % DIGIT_BITS as usize);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        match text.strip_prefix('-') {
+            Some(digits) => -BigInt::parse(digits, 10).unwrap(),
+            None => BigInt::parse(text, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let cases = [
+            ("0", 10, "0"),
+            ("000123", 10, "123"),
+            ("ff", 16, "255"),
+            ("1F", 16, "31"),
+            ("101", 2, "5"),
+            ("777", 8, "511"),
+            (
+                "340282366920938463463374607431768211456",
+                10,
+                "340282366920938463463374607431768211456",
+            ),
+        ];
+        for (text, radix, expected) in cases {
+            assert_eq!(
+                BigInt::parse(text, radix).unwrap().to_string(),
+                expected,
+                "{}",
+                text
+            );
+        }
+        assert!(BigInt::parse("", 10).is_none());
+        assert!(BigInt::parse("12a", 10).is_none());
+        assert!(BigInt::parse("102", 2).is_none());
+        assert_eq!(big("-255").to_string_radix(16), "-FF");
+        assert_eq!(big("5").to_string_radix(2), "101");
+        assert_eq!(BigInt::from(-42i64).to_string(), "-42");
+        assert_eq!(BigInt::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(
+            BigInt::from_f64(-1e20).unwrap().to_string(),
+            "-100000000000000000000"
+        );
+        assert!(BigInt::from_f64(0.5).is_none());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!((&a - &a), BigInt::default());
+        assert_eq!(
+            big("2").pow(128).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(big("-3").pow(3).to_string(), "-27");
+        assert_eq!(big("7").pow(0).to_string(), "1");
+        assert!(big("-1") < big("0") && big("-5") < big("-4") && big("10") > big("9"));
+        assert_eq!(big("2").pow(100).bits(), 101);
+        assert_eq!(big("2").pow(64).to_f64(), 18446744073709551616.0);
+        assert_eq!(big("-7").to_u32(), None);
+        assert_eq!(big("4294967295").to_u32(), Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let cases = [
+            ("7", "2", "3", "1"),
+            ("-7", "2", "-3", "-1"),
+            ("7", "-2", "-3", "1"),
+            ("1", "10", "0", "1"),
+            (
+                "121932631137021795226185032733622923332237463801111263526900",
+                "987654321098765432109876543210",
+                "123456789012345678901234567890",
+                "0",
+            ),
+            (
+                "340282366920938463463374607431768211457",
+                "18446744073709551616",
+                "18446744073709551616",
+                "1",
+            ),
+            // Multi-limb divisors with multi-limb remainders
+            (
+                "10000000000000000012345678901234567890123",
+                "18446744073709551619",
+                "542101086242752217584",
+                "15217058600000421627",
+            ),
+            (
+                "-10000000000000000012345678901234567890123",
+                "18446744073709551619",
+                "-542101086242752217584",
+                "-15217058600000421627",
+            ),
+            (
+                "10000000000000000012345678901234567890123",
+                "-79228162514264337593543950335",
+                "-126217744835",
+                "28671802223450634493625120398",
+            ),
+            (
+                "-340282366920938463463374607431768211455",
+                "-18446744073709551617",
+                "18446744073709551615",
+                "0",
+            ),
+            (
+                "79228162514264337593543950341",
+                "79228162514264337593543950343",
+                "0",
+                "79228162514264337593543950341",
+            ),
+            (
+                "340282366920938463463374607431768211456",
+                "18446744073709551615",
+                "18446744073709551617",
+                "1",
+            ),
+            ("0", "18446744073709551616", "0", "0"),
+        ];
+        for (a, b, quotient, remainder) in cases {
+            let (q, r) = big(a).div_rem(&big(b)).unwrap();
+            assert_eq!(
+                (q.to_string(), r.to_string()),
+                (quotient.to_string(), remainder.to_string())
+            );
+        }
+        assert!(big("1").div_rem(&big("0")).is_none());
+        assert!(
+            big("-340282366920938463463374607431768211456")
+                .div_rem(&BigInt::default())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_carry_and_borrow() {
+        let sums = [
+            ("4294967295", "1", "4294967296"),
+            ("18446744073709551615", "1", "18446744073709551616"),
+            (
+                "79228162514264337593543950335",
+                "4294967297",
+                "79228162514264337597838917632",
+            ),
+        ];
+        for (a, b, expected) in sums {
+            assert_eq!((&big(a) + &big(b)).to_string(), expected, "{} + {}", a, b);
+            assert_eq!((&big(b) + &big(a)).to_string(), expected, "{} + {}", b, a);
+        }
+        let differences = [
+            ("4294967296", "1", "4294967295"),
+            ("18446744073709551616", "1", "18446744073709551615"),
+            (
+                "79228162514264337593543950336",
+                "4294967297",
+                "79228162514264337589248983039",
+            ),
+            ("1", "18446744073709551616", "-18446744073709551615"),
+        ];
+        for (a, b, expected) in differences {
+            assert_eq!((&big(a) - &big(b)).to_string(), expected, "{} - {}", a, b);
+        }
+        let products = [
+            ("4294967295", "4294967295", "18446744065119617025"),
+            (
+                "18446744073709551615",
+                "18446744073709551615",
+                "340282366920938463426481119284349108225",
+            ),
+            ("4294967297", "-4294967295", "-18446744073709551615"),
+        ];
+        for (a, b, expected) in products {
+            assert_eq!((&big(a) * &big(b)).to_string(), expected, "{} * {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_to_string_of_powers() {
+        let cases = [
+            (2u64, 128, "340282366920938463463374607431768211456"),
+            (10, 40, "10000000000000000000000000000000000000000"),
+        ];
+        for (base, exponent, expected) in cases {
+            assert_eq!(BigInt::from(base).pow(exponent).to_string(), expected);
+        }
+    }
+}
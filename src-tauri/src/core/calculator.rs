@@ -0,0 +1,745 @@
+//! Calculator that answers arithmetic typed into the search box.
+//!
+//! A query such as `1920*1080/4` or `0x1F + 12` is evaluated by a small recursive
+//! descent parser, so no code is ever executed. Integers are exact and of any size
+//! (up to `MAX_INTEGER_BITS`), other numbers are floats. The usual precedence applies:
+//!
+//! | Precedence | Operators |
+//! |------------|-----------|
+//! | highest    | `!` (factorial) |
+//! |            | `^` or `**` (right associative) |
+//! |            | unary `-` and `+` |
+//! |            | `*`, `/`, `%` |
+//! | lowest     | `+`, `-` |
+//!
+//! Numbers can be written in hex (`0xFF`), binary (`0b1010`) and octal (`0o17`), and
+//! the result is shown in another radix with a suffix such as `in hex`, `to bin` or `as oct`.
+//!
+//! The calculator row is shown at the top of the search results. Its ID is `calc:`
+//! followed by the query, so that the value is calculated again when it is copied.
+//! Unit conversions, see `unit_conversion`, are shown in the same row.
+
+use crate::core::big_int::BigInt;
+use std::f64::consts::{E, PI, TAU};
+
+/// Prefix of the IDs of calculator result rows
+pub const APP_ID_PREFIX: &str = "calc:";
+
+/// Largest integer result in bits, larger results are calculated as floats
+const MAX_INTEGER_BITS: u64 = 16384;
+
+/// Deepest nesting of parentheses and unary operators, to bound the recursion
+const MAX_DEPTH: usize = 64;
+
+/// Significant digits of float results
+const REAL_PRECISION: usize = 12;
+
+/// Words after the expression that select the radix of the result
+const RADIX_SUFFIXES: [(&str, u32); 8] = [
+    ("hex", 16),
+    ("hexadecimal", 16),
+    ("bin", 2),
+    ("binary", 2),
+    ("oct", 8),
+    ("octal", 8),
+    ("dec", 10),
+    ("decimal", 10),
+];
+
+/// Words that join the expression and the radix of the result, e.g. `255 in hex`
+const RADIX_JOINERS: [&str; 3] = ["in", "to", "as"];
+
+/// A calculated query, shown as a result row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calculation {
+    /// The query as typed, without surrounding spaces
+    pub expression: String,
+    /// The result, e.g. `518400`, `0x2B` or `3.14159265359`, copied when the row is selected
+    pub value: String,
+    /// Unit of the result, e.g. `km`, empty for plain numbers
+    pub unit: String,
+}
+
+impl Calculation {
+    /// Returns the text shown in the result row, e.g. `1920*1080/4 = 518400`
+    /// or `5 mi to km = 8.04672 km`.
+    pub fn get_result_name(&self) -> String {
+        if self.unit.is_empty() {
+            format!("{} = {}", self.expression, self.value)
+        } else {
+            format!("{} = {} {}", self.expression, self.value, self.unit)
+        }
+    }
+
+    /// Returns the ID of the result row, which `parse_app_id` reads back.
+    pub fn get_app_id(&self) -> String {
+        format!("{}{}", APP_ID_PREFIX, self.expression)
+    }
+}
+
+/// Calculates a search query.
+///
+/// Only queries that contain an operator or a function call, or that convert a number
+/// to another radix, are calculated, so that searching for `7zip` or `2048` does not
+/// show a calculator row.
+///
+/// # Arguments
+///
+/// * `query` - The search query typed by the user, optionally starting with `=`
+///
+/// # Returns
+///
+/// The calculation, or `None` if the query is not arithmetic or cannot be calculated
+pub fn evaluate(query: &str) -> Option<Calculation> {
+    let expression = query.trim();
+    let expression = expression
+        .strip_prefix('=')
+        .unwrap_or(expression)
+        .trim_start();
+    let (text, radix) = split_radix_suffix(expression);
+    let tokens = tokenize(text).ok()?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        depth: 0,
+        operations: 0,
+    };
+    let value = parser.parse().ok()?;
+    let has_radix_literal = tokens
+        .iter()
+        .any(|token| matches!(token, Token::Number(_, radix) if *radix != 10));
+    if parser.operations == 0 && radix.is_none() && !has_radix_literal {
+        return None;
+    }
+    match format_value(&value, radix.unwrap_or(10)) {
+        Ok(value) => {
+            log::debug!("Calculated '{}' = {}", expression, value);
+            Some(Calculation {
+                expression: expression.to_string(),
+                value,
+                unit: String::new(),
+            })
+        }
+        Err(e) => {
+            log::debug!("Cannot show the result of '{}': {}", expression, e);
+            None
+        }
+    }
+}
+
+/// Reads the query back from the ID of a result row.
+///
+/// # Arguments
+///
+/// * `app_id` - The ID of the result row, see `Calculation::get_app_id`
+///
+/// # Returns
+///
+/// The query to calculate again, or `None` if the ID is not a calculator row
+pub fn parse_app_id(app_id: &str) -> Option<&str> {
+    app_id.strip_prefix(APP_ID_PREFIX)
+}
+
+/// Splits a radix suffix such as `in hex` from the end of an expression.
+///
+/// # Returns
+///
+/// The expression without the suffix and the radix of the suffix, if any
+fn split_radix_suffix(expression: &str) -> (&str, Option<u32>) {
+    let lower = expression.to_ascii_lowercase();
+    for (suffix, radix) in RADIX_SUFFIXES {
+        for joiner in RADIX_JOINERS {
+            let pattern = format!(" {} {}", joiner, suffix);
+            if lower.ends_with(&pattern) {
+                return (&expression[..expression.len() - pattern.len()], Some(radix));
+            }
+        }
+    }
+    (expression, None)
+}
+
+/// A value of the calculator.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// An exact integer
+    Integer(BigInt),
+    /// A float, used for fractions and for functions such as `sqrt`
+    Real(f64),
+}
+
+impl Value {
+    /// Returns the value as a float.
+    fn to_f64(&self) -> f64 {
+        match self {
+            Value::Integer(v) => v.to_f64(),
+            Value::Real(v) => *v,
+        }
+    }
+
+    /// Keeps an integer result unless it is larger than `MAX_INTEGER_BITS`.
+    fn integer(value: BigInt) -> Value {
+        if value.bits() > MAX_INTEGER_BITS {
+            Value::Real(value.to_f64())
+        } else {
+            Value::Integer(value)
+        }
+    }
+}
+
+/// A token of an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A number and the radix it was written in
+    Number(Value, u32),
+    /// A function or constant name
+    Name(String),
+    /// An operator, a parenthesis or a comma
+    Symbol(char),
+}
+
+/// Splits an expression into tokens.
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+            {
+                // The sign of an exponent such as 1.5e-3 belongs to the number
+                if matches!(chars[i], 'e' | 'E')
+                    && matches!(chars.get(i + 1), Some('+' | '-'))
+                    && !chars[start..i].iter().any(|c| c.is_ascii_alphabetic())
+                {
+                    i += 1;
+                }
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+            tokens.push(parse_number(&literal)?);
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            tokens.push(Token::Name(
+                chars[start..i].iter().collect::<String>().to_lowercase(),
+            ));
+        } else {
+            let symbol = match c {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    i += 1;
+                    '^'
+                }
+                '×' => '*',
+                '÷' => '/',
+                '+' | '-' | '*' | '/' | '%' | '^' | '!' | '(' | ')' | ',' => c,
+                _ => return Err(format!("Unexpected character: {}", c)),
+            };
+            tokens.push(Token::Symbol(symbol));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses a number literal such as `42`, `1.5e3`, `0xFF`, `0b1010` or `0o17`.
+fn parse_number(literal: &str) -> Result<Token, String> {
+    let invalid = || format!("Invalid number: {}", literal);
+    let lower = literal.to_ascii_lowercase();
+    for (prefix, radix) in [("0x", 16), ("0b", 2), ("0o", 8)] {
+        if let Some(digits) = lower.strip_prefix(prefix) {
+            let value = BigInt::parse(digits, radix).ok_or_else(invalid)?;
+            return Ok(Token::Number(Value::integer(value), radix));
+        }
+    }
+    if lower.contains(['.', 'e']) {
+        let value = lower.parse::<f64>().map_err(|_| invalid())?;
+        return Ok(Token::Number(Value::Real(value), 10));
+    }
+    let value = BigInt::parse(&lower, 10).ok_or_else(invalid)?;
+    Ok(Token::Number(Value::integer(value), 10))
+}
+
+/// Recursive descent parser that evaluates the tokens while parsing them.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    /// Current nesting of parentheses and unary operators
+    depth: usize,
+    /// Number of operators and function calls evaluated
+    operations: usize,
+}
+
+impl Parser<'_> {
+    /// Evaluates the whole expression.
+    fn parse(&mut self) -> Result<Value, String> {
+        let value = self.parse_sum()?;
+        match self.tokens.get(self.position) {
+            None => Ok(value),
+            Some(token) => Err(format!("Unexpected token: {:?}", token)),
+        }
+    }
+
+    /// Returns the next token if it is the given symbol.
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.tokens.get(self.position) == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn parse_sum(&mut self) -> Result<Value, String> {
+        let mut value = self.parse_product()?;
+        loop {
+            let operator = if self.eat('+') {
+                add
+            } else if self.eat('-') {
+                subtract
+            } else {
+                return Ok(value);
+            };
+            let right = self.parse_product()?;
+            self.operations += 1;
+            value = operator(value, right)?;
+        }
+    }
+
+    /// product := unary (('*' | '/' | '%') unary)*
+    fn parse_product(&mut self) -> Result<Value, String> {
+        let mut value = self.parse_unary()?;
+        loop {
+            let operator = if self.eat('*') {
+                multiply
+            } else if self.eat('/') {
+                divide
+            } else if self.eat('%') {
+                remainder
+            } else {
+                return Ok(value);
+            };
+            let right = self.parse_unary()?;
+            self.operations += 1;
+            value = operator(value, right)?;
+        }
+    }
+
+    /// unary := ('-' | '+') unary | power
+    fn parse_unary(&mut self) -> Result<Value, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        let value = if self.eat('-') {
+            negate(self.parse_unary()?)
+        } else if self.eat('+') {
+            self.parse_unary()
+        } else {
+            self.parse_power()
+        };
+        self.depth -= 1;
+        value
+    }
+
+    /// power := factorial ('^' unary)?
+    fn parse_power(&mut self) -> Result<Value, String> {
+        let base = self.parse_factorial()?;
+        if !self.eat('^') {
+            return Ok(base);
+        }
+        let exponent = self.parse_unary()?;
+        self.operations += 1;
+        power(base, exponent)
+    }
+
+    /// factorial := primary '!'*
+    fn parse_factorial(&mut self) -> Result<Value, String> {
+        let mut value = self.parse_primary()?;
+        while self.eat('!') {
+            self.operations += 1;
+            value = factorial(value)?;
+        }
+        Ok(value)
+    }
+
+    /// primary := number | name | name '(' arguments ')' | '(' sum ')'
+    fn parse_primary(&mut self) -> Result<Value, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or("Unexpected end of expression")?
+            .clone();
+        self.position += 1;
+        match token {
+            Token::Number(value, _) => Ok(value),
+            Token::Name(name) if self.eat('(') => {
+                let mut arguments = vec![];
+                if !self.eat(')') {
+                    loop {
+                        arguments.push(self.parse_nested()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(format!("Missing ) after the arguments of {}", name));
+                        }
+                    }
+                }
+                self.operations += 1;
+                call_function(&name, arguments)
+            }
+            Token::Name(name) => match name.as_str() {
+                "pi" | "π" => Ok(Value::Real(PI)),
+                "e" => Ok(Value::Real(E)),
+                "tau" | "τ" => Ok(Value::Real(TAU)),
+                _ => Err(format!("Unknown constant: {}", name)),
+            },
+            Token::Symbol('(') => {
+                let value = self.parse_nested()?;
+                if !self.eat(')') {
+                    return Err("Missing )".to_string());
+                }
+                Ok(value)
+            }
+            Token::Symbol(symbol) => Err(format!("Unexpected symbol: {}", symbol)),
+        }
+    }
+
+    /// Evaluates an expression in parentheses.
+    fn parse_nested(&mut self) -> Result<Value, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        let value = self.parse_sum();
+        self.depth -= 1;
+        value
+    }
+}
+
+fn add(left: Value, right: Value) -> Result<Value, String> {
+    Ok(match (left, right) {
+        (Value::Integer(a), Value::Integer(b)) => Value::integer(&a + &b),
+        (a, b) => Value::Real(a.to_f64() + b.to_f64()),
+    })
+}
+
+fn subtract(left: Value, right: Value) -> Result<Value, String> {
+    add(left, negate(right)?)
+}
+
+fn multiply(left: Value, right: Value) -> Result<Value, String> {
+    Ok(match (left, right) {
+        (Value::Integer(a), Value::Integer(b)) => Value::integer(&a * &b),
+        (a, b) => Value::Real(a.to_f64() * b.to_f64()),
+    })
+}
+
+/// Divides exactly if the integers divide evenly, otherwise as floats.
+fn divide(left: Value, right: Value) -> Result<Value, String> {
+    if right.to_f64() == 0.0 {
+        return Err("Division by zero".to_string());
+    }
+    if let (Value::Integer(a), Value::Integer(b)) = (&left, &right) {
+        let (quotient, remainder) = a.div_rem(b).ok_or("Division by zero")?;
+        if remainder.is_zero() {
+            return Ok(Value::Integer(quotient));
+        }
+    }
+    Ok(Value::Real(left.to_f64() / right.to_f64()))
+}
+
+fn remainder(left: Value, right: Value) -> Result<Value, String> {
+    if right.to_f64() == 0.0 {
+        return Err("Division by zero".to_string());
+    }
+    Ok(match (left, right) {
+        (Value::Integer(a), Value::Integer(b)) => {
+            Value::Integer(a.div_rem(&b).ok_or("Division by zero")?.1)
+        }
+        (a, b) => Value::Real(a.to_f64() % b.to_f64()),
+    })
+}
+
+fn negate(value: Value) -> Result<Value, String> {
+    Ok(match value {
+        Value::Integer(v) => Value::Integer(-v),
+        Value::Real(v) => Value::Real(-v),
+    })
+}
+
+/// Raises exactly if the result is an integer of at most `MAX_INTEGER_BITS`.
+fn power(base: Value, exponent: Value) -> Result<Value, String> {
+    if let (Value::Integer(b), Value::Integer(e)) = (&base, &exponent)
+        && let Some(e) = e.to_u32()
+        && (b.bits() <= 1 || b.bits().saturating_mul(e as u64) <= MAX_INTEGER_BITS + 64)
+    {
+        return Ok(Value::integer(b.pow(e)));
+    }
+    Ok(Value::Real(base.to_f64().powf(exponent.to_f64())))
+}
+
+fn factorial(value: Value) -> Result<Value, String> {
+    let n = match &value {
+        Value::Integer(n) => n.to_u32(),
+        Value::Real(_) => None,
+    }
+    .ok_or("Factorial needs a non-negative integer")?;
+    let mut result = BigInt::from(1u64);
+    for i in 2..=n as u64 {
+        result = &result * &BigInt::from(i);
+        if result.bits() > MAX_INTEGER_BITS {
+            return Err("Factorial is too large".to_string());
+        }
+    }
+    Ok(Value::Integer(result))
+}
+
+/// Calls a function by name.
+fn call_function(name: &str, arguments: Vec<Value>) -> Result<Value, String> {
+    let real = |f: fn(f64) -> f64| match &arguments[..] {
+        [x] => Ok(Value::Real(f(x.to_f64()))),
+        _ => Err(format!("{} takes 1 argument", name)),
+    };
+    let rounded = |f: fn(f64) -> f64| match &arguments[..] {
+        [Value::Integer(x)] => Ok(Value::Integer(x.clone())),
+        [Value::Real(x)] => Ok(BigInt::from_f64(f(*x))
+            .map(Value::integer)
+            .unwrap_or(Value::Real(f(*x)))),
+        _ => Err(format!("{} takes 1 argument", name)),
+    };
+    match name {
+        "sqrt" => real(f64::sqrt),
+        "cbrt" => real(f64::cbrt),
+        "exp" => real(f64::exp),
+        "ln" => real(f64::ln),
+        "log" => match &arguments[..] {
+            [x] => Ok(Value::Real(x.to_f64().log10())),
+            [x, base] => Ok(Value::Real(x.to_f64().log(base.to_f64()))),
+            _ => Err("log takes 1 or 2 arguments".to_string()),
+        },
+        "log10" => real(f64::log10),
+        "log2" => real(f64::log2),
+        "sin" => real(f64::sin),
+        "cos" => real(f64::cos),
+        "tan" => real(f64::tan),
+        "asin" => real(f64::asin),
+        "acos" => real(f64::acos),
+        "atan" => real(f64::atan),
+        "sinh" => real(f64::sinh),
+        "cosh" => real(f64::cosh),
+        "tanh" => real(f64::tanh),
+        "floor" => rounded(f64::floor),
+        "ceil" => rounded(f64::ceil),
+        "round" => rounded(f64::round),
+        "trunc" => rounded(f64::trunc),
+        "abs" => match &arguments[..] {
+            [Value::Integer(x)] => Ok(Value::Integer(x.abs())),
+            [Value::Real(x)] => Ok(Value::Real(x.abs())),
+            _ => Err("abs takes 1 argument".to_string()),
+        },
+        "min" | "max" => {
+            let mut arguments = arguments.into_iter();
+            let first = arguments
+                .next()
+                .ok_or(format!("{} takes 1 or more arguments", name))?;
+            Ok(arguments.fold(first, |a, b| {
+                let b_is_larger = b.to_f64() > a.to_f64();
+                if b_is_larger == (name == "max") { b } else { a }
+            }))
+        }
+        _ => Err(format!("Unknown function: {}", name)),
+    }
+}
+
+/// Formats a result in the given radix.
+///
+/// Floats are rounded to `REAL_PRECISION` significant digits, and very large or
+/// small floats are written with an exponent, e.g. `1.5e-9`.
+fn format_value(value: &Value, radix: u32) -> Result<String, String> {
+    let integer = match value {
+        Value::Integer(v) => v.clone(),
+        Value::Real(v) if !v.is_finite() => return Err("Result is not a finite number".to_string()),
+        Value::Real(v) if radix == 10 => return Ok(format_real(*v)),
+        Value::Real(v) => BigInt::from_f64(*v).ok_or("Only integers can be shown in this radix")?,
+    };
+    let prefix = match radix {
+        16 => "0x",
+        2 => "0b",
+        8 => "0o",
+        _ => "",
+    };
+    let sign = if integer.is_negative() { "-" } else { "" };
+    Ok(format!(
+        "{}{}{}",
+        sign,
+        prefix,
+        integer.abs().to_string_radix(radix)
+    ))
+}
+
+/// Formats a finite float with `REAL_PRECISION` significant digits.
+///
+/// # Arguments
+///
+/// * `value` - The float to format
+///
+/// # Returns
+///
+/// The float without trailing zeros, e.g. `0.3` or `8.04672`, with an exponent
+/// if it is very large or small, e.g. `1.5e-9`
+pub fn format_real(value: f64) -> String {
+    let rounded: f64 = format!("{:.*e}", REAL_PRECISION - 1, value)
+        .parse()
+        .unwrap_or(value);
+    if rounded == 0.0 {
+        "0".to_string()
+    } else if rounded.abs() >= 1e21 || rounded.abs() < 1e-7 {
+        format!("{:e}", rounded)
+    } else {
+        format!("{}", rounded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value_of(query: &str) -> Option<String> {
+        evaluate(query).map(|calculation| calculation.value)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cases = [
+            ("1920*1080/4", "518400"),
+            ("0x1F + 12", "43"),
+            ("1 + 2 * 3", "7"),
+            ("(1 + 2) * 3", "9"),
+            ("2 ^ 3 ^ 2", "512"),
+            ("2 ** 10", "1024"),
+            ("-2 ^ 2", "-4"),
+            ("(-2) ^ 2", "4"),
+            ("2 * -3", "-6"),
+            ("7 / 2", "3.5"),
+            ("-7 % 3", "-1"),
+            ("7.5 % 2", "1.5"),
+            ("0.1 + 0.2", "0.3"),
+            ("1 / 3", "0.333333333333"),
+            ("2 ^ -1", "0.5"),
+            ("1.5e3 * 2", "3000"),
+            ("1e-9 * 1.5", "1.5e-9"),
+            ("10 ^ 25 / 3", "3.33333333333e24"),
+            ("5!", "120"),
+            ("3! !", "720"),
+            ("2 ^ 100", "1267650600228229401496703205376"),
+            ("99999999999999999999 + 1", "100000000000000000000"),
+            ("= 6 × 7", "42"),
+            ("84 ÷ 2", "42"),
+            ("1_000_000 * 3", "3000000"),
+            ("sqrt(16)", "4"),
+            ("sqrt(2)", "1.41421356237"),
+            ("sin(pi / 2)", "1"),
+            ("cos(0) + ln(e)", "2"),
+            ("log(1000)", "3"),
+            ("log(8, 2)", "3"),
+            ("log2(1024)", "10"),
+            ("2 * PI", "6.28318530718"),
+            ("tau / 2", "3.14159265359"),
+            ("abs(-5) + floor(2.7) + ceil(2.1)", "10"),
+            ("round(2.5) * 10", "30"),
+            ("max(1, 7, 3) - min(4, 2)", "5"),
+            ("0b1010 + 0o17", "25"),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(value_of(query).as_deref(), Some(expected), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_radix_output() {
+        let cases = [
+            ("255 in hex", "0xFF"),
+            ("0xFF to dec", "255"),
+            ("10 as bin", "0b1010"),
+            ("8 * 8 in octal", "0o100"),
+            ("-255 in hex", "-0xFF"),
+            ("0x1F", "31"),
+            ("6.0 / 2 in hex", "0x3"),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(value_of(query).as_deref(), Some(expected), "{}", query);
+        }
+        assert_eq!(value_of("1 / 3 in hex"), None);
+    }
+
+    #[test]
+    fn test_not_calculated() {
+        let queries = [
+            "",
+            "notepad",
+            "7zip",
+            "2048",
+            "-5",
+            "pi",
+            "visual studio 2022",
+            "1 +",
+            "(1 + 2",
+            "1 / 0",
+            "5 % 0",
+            "foo(2)",
+            "sqrt(1, 2)",
+            "(-1)!",
+            "2.5!",
+            "10.0 ^ 400",
+            "sqrt(-1)",
+            "1 & 2",
+            "0x",
+            "0b102",
+            "1.2.3 + 1",
+        ];
+        for query in queries {
+            assert_eq!(value_of(query), None, "{}", query);
+        }
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(value_of(&format!("{} + 1", nested)), None);
+        assert_eq!(value_of(&format!("{}1", "-".repeat(100))), None);
+    }
+
+    #[test]
+    fn test_big_integers() {
+        let factorial = value_of("30!").unwrap();
+        assert_eq!(factorial, "265252859812191058636308480000000");
+        let power = value_of("2 ^ 4096").unwrap();
+        assert_eq!(power.len(), 1234);
+        assert!(power.starts_with("1044388881413152506691752710716624382579964249047383780384233483283953907971557456848826811934997558"));
+        assert!(value_of("2 ^ 4096 % 1000").is_some());
+        assert_eq!(value_of("(2 ^ 4096 + 1) - 2 ^ 4096").as_deref(), Some("1"));
+        assert_eq!(value_of("10000!"), None);
+        assert_eq!(value_of("2 ^ 20000").as_deref(), None);
+    }
+
+    #[test]
+    fn test_result_row() {
+        let calculation = evaluate("  1920*1080/4 ").unwrap();
+        assert_eq!(calculation.get_result_name(), "1920*1080/4 = 518400");
+        assert_eq!(calculation.get_app_id(), "calc:1920*1080/4");
+        assert_eq!(parse_app_id(&calculation.get_app_id()), Some("1920*1080/4"));
+        assert!(parse_app_id("web:g 1+1").is_none());
+        let conversion = Calculation {
+            unit: "km".to_string(),
+            ..evaluate("8 / 2").unwrap()
+        };
+        assert_eq!(conversion.get_result_name(), "8 / 2 = 4 km");
+    }
+}
@@ -0,0 +1,149 @@
+//! Parser for `kasuri://` deep links.
+//!
+//! Deep links let other tools (web pages, Stream Deck buttons, scripts) trigger KASURI.
+//! Supported routes:
+//!
+//! - `kasuri://show`
+//! - `kasuri://search?q=<query>`
+//! - `kasuri://launch/<app_id>` (the app ID is percent-encoded)
+//! - `kasuri://reload`
+//! - `kasuri://settings`
+
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+/// URI scheme of KASURI deep links
+pub const DEEP_LINK_SCHEME: &str = "kasuri";
+
+/// A parsed `kasuri://` deep link.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeepLink {
+    /// Show the main window
+    Show,
+    /// Show the main window and search for the query
+    Search(String),
+    /// Launch the application with the given app ID
+    Launch(String),
+    /// Rescan the applications
+    Reload,
+    /// Open the settings window
+    Settings,
+}
+
+impl DeepLink {
+    /// Returns whether the given text looks like a KASURI deep link.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to check, e.g. a command line argument
+    ///
+    /// # Returns
+    ///
+    /// `true` if the text starts with the `kasuri:` scheme
+    pub fn is_deep_link(text: &str) -> bool {
+        text.len() > DEEP_LINK_SCHEME.len()
+            && text.is_char_boundary(DEEP_LINK_SCHEME.len())
+            && text[..DEEP_LINK_SCHEME.len()].eq_ignore_ascii_case(DEEP_LINK_SCHEME)
+            && text[DEEP_LINK_SCHEME.len()..].starts_with(':')
+    }
+}
+
+/// Parses a deep link from its URI
+impl std::str::FromStr for DeepLink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|e| format!("Invalid deep link '{}': {}", s, e))?;
+        if url.scheme() != DEEP_LINK_SCHEME {
+            return Err(format!("Unsupported scheme: {}", url.scheme()));
+        }
+        let route = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let path = percent_decode_str(url.path().trim_matches('/'))
+            .decode_utf8()
+            .map_err(|_| format!("Invalid UTF-8 in deep link '{}'", s))?
+            .into_owned();
+
+        match route.as_str() {
+            "show" | "" if path.is_empty() => Ok(DeepLink::Show),
+            "search" if path.is_empty() => {
+                let query = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "q")
+                    .map(|(_, value)| value.into_owned())
+                    .unwrap_or_default();
+                Ok(DeepLink::Search(query))
+            }
+            "launch" if !path.is_empty() => Ok(DeepLink::Launch(path)),
+            "launch" => Err("Missing application ID in launch deep link".to_string()),
+            "reload" if path.is_empty() => Ok(DeepLink::Reload),
+            "settings" if path.is_empty() => Ok(DeepLink::Settings),
+            _ => Err(format!("Unknown deep link route: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(uri: &str) -> Result<DeepLink, String> {
+        uri.parse::<DeepLink>()
+    }
+
+    #[test]
+    fn test_parse_search() {
+        assert_eq!(
+            parse("kasuri://search?q=visual%20studio"),
+            Ok(DeepLink::Search("visual studio".to_string()))
+        );
+        assert_eq!(
+            parse("kasuri://search/?q=code+insiders&x=1"),
+            Ok(DeepLink::Search("code insiders".to_string()))
+        );
+        assert_eq!(
+            parse("kasuri://search"),
+            Ok(DeepLink::Search(String::new()))
+        );
+    }
+
+    #[test]
+    fn test_parse_launch() {
+        assert_eq!(
+            parse("kasuri://launch/C%3A%5CWindows%5Cnotepad.exe"),
+            Ok(DeepLink::Launch("C:\\Windows\\notepad.exe".to_string()))
+        );
+        assert_eq!(
+            parse("kasuri://launch/Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"),
+            Ok(DeepLink::Launch(
+                "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App".to_string()
+            ))
+        );
+        assert!(parse("kasuri://launch").is_err());
+        assert!(parse("kasuri://launch/").is_err());
+    }
+
+    #[test]
+    fn test_parse_simple_routes() {
+        assert_eq!(parse("kasuri://show"), Ok(DeepLink::Show));
+        assert_eq!(parse("kasuri://reload"), Ok(DeepLink::Reload));
+        assert_eq!(parse("kasuri://reload/"), Ok(DeepLink::Reload));
+        assert_eq!(parse("KASURI://Settings"), Ok(DeepLink::Settings));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_links() {
+        assert!(parse("https://search?q=code").is_err());
+        assert!(parse("kasuri://unknown").is_err());
+        assert!(parse("kasuri://reload/now").is_err());
+        assert!(parse("kasuri://launch/%E0%A4").is_err());
+        assert!(parse("not a uri").is_err());
+    }
+
+    #[test]
+    fn test_is_deep_link() {
+        assert!(DeepLink::is_deep_link("kasuri://reload"));
+        assert!(DeepLink::is_deep_link("Kasuri://reload"));
+        assert!(!DeepLink::is_deep_link("--reload"));
+        assert!(!DeepLink::is_deep_link("kasuri"));
+    }
+}
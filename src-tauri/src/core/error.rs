@@ -0,0 +1,203 @@
+//! Error type of the KASURI application.
+//!
+//! All fallible operations return a `KasuriError`, which tells the kind of
+//! failure apart and can be serialized to the frontend with a stable error code.
+
+use crate::core::settings::SettingsFieldError;
+use crate::model::launch_failure::LaunchFailure;
+use serde::ser::SerializeStruct;
+
+/// Errors that can occur in KASURI.
+///
+/// Each variant has a stable error code (see `KasuriError::code`), which is
+/// sent to the frontend together with a human readable message.
+#[derive(Debug)]
+pub enum KasuriError {
+    /// Settings could not be loaded, parsed, validated or saved
+    Settings(String),
+    /// Settings failed validation, see `Settings::validate`
+    InvalidSettings(Vec<SettingsFieldError>),
+    /// A database operation failed
+    Database(String),
+    /// Scanning for applications failed or has not been done yet
+    Scan(String),
+    /// Extracting application icons failed
+    Icon(String),
+    /// The requested application is not in the application cache
+    ApplicationNotFound(String),
+    /// Launching an application failed
+    Launch(LaunchFailure),
+    /// The requested action is unknown or not available for the application
+    Action(String),
+    /// A platform specific operation (PowerShell, shell, clipboard, window) failed
+    Platform(String),
+    /// An export archive or the data of another launcher could not be read or written,
+    /// see `core::archive` and `core::launcher_import`
+    Archive(String),
+    /// A file system or other I/O operation failed
+    Io(std::io::Error),
+}
+
+impl KasuriError {
+    /// Returns the stable error code of the error.
+    ///
+    /// The error codes are part of the interface to the frontend and must not change.
+    ///
+    /// # Returns
+    ///
+    /// The error code as a static string
+    pub fn code(&self) -> &'static str {
+        match self {
+            KasuriError::Settings(_) => "settings",
+            KasuriError::InvalidSettings(_) => "invalid_settings",
+            KasuriError::Database(_) => "database",
+            KasuriError::Scan(_) => "scan",
+            KasuriError::Icon(_) => "icon",
+            KasuriError::ApplicationNotFound(_) => "application_not_found",
+            KasuriError::Launch(_) => "launch",
+            KasuriError::Action(_) => "action",
+            KasuriError::Platform(_) => "platform",
+            KasuriError::Archive(_) => "archive",
+            KasuriError::Io(_) => "io",
+        }
+    }
+}
+
+impl std::fmt::Display for KasuriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KasuriError::Settings(message) => write!(f, "Settings error: {}", message),
+            KasuriError::InvalidSettings(errors) => write!(
+                f,
+                "Invalid settings: {}",
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            KasuriError::Database(message) => write!(f, "Database error: {}", message),
+            KasuriError::Scan(message) => write!(f, "Scan error: {}", message),
+            KasuriError::Icon(message) => write!(f, "Icon error: {}", message),
+            KasuriError::ApplicationNotFound(app_id) => {
+                write!(f, "Application with ID {} not found", app_id)
+            }
+            KasuriError::Launch(failure) => write!(f, "{}", failure),
+            KasuriError::Action(message) => write!(f, "Action error: {}", message),
+            KasuriError::Platform(message) => write!(f, "Platform error: {}", message),
+            KasuriError::Archive(message) => write!(f, "Archive error: {}", message),
+            KasuriError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for KasuriError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KasuriError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes the error for the frontend as `{ code, message, details }`.
+///
+/// `details` carries the launch failure for `launch` errors, the app ID for
+/// `application_not_found` errors and the field errors for `invalid_settings`
+/// errors, and is `null` otherwise.
+impl serde::Serialize for KasuriError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("KasuriError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        match self {
+            KasuriError::Launch(failure) => state.serialize_field("details", failure)?,
+            KasuriError::InvalidSettings(errors) => state.serialize_field("details", errors)?,
+            KasuriError::ApplicationNotFound(app_id) => {
+                state.serialize_field("details", &serde_json::json!({ "app_id": app_id }))?
+            }
+            _ => state.serialize_field("details", &None::<()>)?,
+        }
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for KasuriError {
+    fn from(e: std::io::Error) -> Self {
+        KasuriError::Io(e)
+    }
+}
+
+impl From<sqlite::Error> for KasuriError {
+    fn from(e: sqlite::Error) -> Self {
+        KasuriError::Database(e.to_string())
+    }
+}
+
+impl From<toml::de::Error> for KasuriError {
+    fn from(e: toml::de::Error) -> Self {
+        KasuriError::Settings(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for KasuriError {
+    fn from(e: toml::ser::Error) -> Self {
+        KasuriError::Settings(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_with_code() {
+        let error = KasuriError::Database("disk I/O error".to_string());
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "database");
+        assert_eq!(value["message"], "Database error: disk I/O error");
+        assert!(value["details"].is_null());
+    }
+
+    #[test]
+    fn test_serialize_launch_failure_details() {
+        let error = KasuriError::Launch(LaunchFailure {
+            app_id: "app".to_string(),
+            name: Some("App".to_string()),
+            message: "File not found".to_string(),
+            failure_count: 2,
+        });
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "launch");
+        assert_eq!(value["details"]["app_id"], "app");
+        assert_eq!(value["details"]["failure_count"], 2);
+    }
+
+    #[test]
+    fn test_serialize_application_not_found_details() {
+        let error = KasuriError::ApplicationNotFound("missing".to_string());
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "application_not_found");
+        assert_eq!(value["details"]["app_id"], "missing");
+    }
+
+    #[test]
+    fn test_serialize_invalid_settings_details() {
+        let error = KasuriError::InvalidSettings(vec![SettingsFieldError::new(
+            "application_name_aliases",
+            Some(1),
+            "Alias is empty",
+        )]);
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "invalid_settings");
+        assert_eq!(
+            value["message"],
+            "Invalid settings: application_name_aliases[1]: Alias is empty"
+        );
+        assert_eq!(value["details"][0]["field"], "application_name_aliases");
+        assert_eq!(value["details"][0]["index"], 1);
+    }
+}
@@ -0,0 +1,629 @@
+//! Import of aliases and usage history from other launchers.
+//!
+//! The following launchers are supported. PowerToys Run, Flow Launcher and Wox
+//! keep their data in the `Settings` directory of their user data, Keypirinha
+//! in its profile directory:
+//!
+//! | Launcher | Default directory | Imported data |
+//! | --- | --- | --- |
+//! | PowerToys Run | `%LOCALAPPDATA%\Microsoft\PowerToys\PowerToys Run\Settings` | Usage counts and last use |
+//! | Flow Launcher | `%APPDATA%\FlowLauncher\Settings` | Usage counts, custom query shortcuts as aliases |
+//! | Wox | `%APPDATA%\Wox\Settings` | Usage counts |
+//! | Keypirinha | `%APPDATA%\Keypirinha` | Usage counts and last use, aliases |
+//!
+//! The launchers identify results by their title and subtitle, which for
+//! applications are the name and usually the path. The results are matched to
+//! the applications of KASURI by path first and by name otherwise, see `match_entry`.
+
+use crate::core::path_expansion::expand_path;
+use crate::{KasuriError, KasuriResult};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File with the usage counts of PowerToys Run, Flow Launcher and Wox
+const USER_SELECTED_RECORD_FILE: &str = "UserSelectedRecord.json";
+/// File with the settings of Flow Launcher
+const FLOW_LAUNCHER_SETTINGS_FILE: &str = "Settings.json";
+/// Directory of Keypirinha with the usage history, in its profile directory
+const KEYPIRINHA_LOCAL_DIR: &str = "Local";
+/// File with the usage history of Keypirinha
+const KEYPIRINHA_HISTORY_FILE: &str = "History.ini";
+/// Directory of Keypirinha with the user configuration, in its profile directory
+const KEYPIRINHA_USER_DIR: &str = "User";
+/// File with the user configuration of Keypirinha
+const KEYPIRINHA_CONFIG_FILE: &str = "Keypirinha.ini";
+/// Section of the Keypirinha configuration with the aliases
+const KEYPIRINHA_ALIASES_SECTION: &str = "aliases";
+
+/// Launchers that data can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    /// PowerToys Run
+    PowerToysRun,
+    /// Flow Launcher
+    FlowLauncher,
+    /// Wox
+    Wox,
+    /// Keypirinha
+    Keypirinha,
+}
+
+/// Data read from another launcher.
+#[derive(Debug, Default, PartialEq)]
+pub struct LauncherData {
+    /// Keywords that open an application
+    pub aliases: Vec<LauncherAlias>,
+    /// How often results were selected
+    pub usage: Vec<LauncherUsage>,
+}
+
+/// Keyword that opens an application in another launcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LauncherAlias {
+    /// The keyword
+    pub alias: String,
+    /// The query the keyword stands for, usually the name of the application
+    pub target: String,
+}
+
+/// How often a result was selected in another launcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LauncherUsage {
+    /// The key of the result as stored by the launcher
+    pub key: String,
+    /// Title of the result, the application name for applications
+    pub name: String,
+    /// Path found in the key, `None` if the key has no path
+    pub path: Option<String>,
+    /// Number of times the result was selected
+    pub usage_count: i64,
+    /// Time of the last selection in seconds since the Unix epoch, 0 if unknown
+    pub last_used: i64,
+}
+
+/// Application of KASURI that imported data is matched against.
+#[derive(Debug, Clone, Copy)]
+pub struct LauncherImportTarget<'a> {
+    /// Unique identifier of the application
+    pub app_id: &'a str,
+    /// Display name of the application
+    pub name: &'a str,
+    /// Path of the application
+    pub path: &'a str,
+}
+
+impl Launcher {
+    /// All supported launchers
+    pub const ALL: [Launcher; 4] = [
+        Launcher::PowerToysRun,
+        Launcher::FlowLauncher,
+        Launcher::Wox,
+        Launcher::Keypirinha,
+    ];
+
+    /// Returns the name of the launcher as used on the command line.
+    ///
+    /// # Returns
+    ///
+    /// The name of the launcher
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Launcher::PowerToysRun => "powertoys",
+            Launcher::FlowLauncher => "flow",
+            Launcher::Wox => "wox",
+            Launcher::Keypirinha => "keypirinha",
+        }
+    }
+
+    /// Returns the directory where the launcher keeps its settings by default,
+    /// the profile directory for Keypirinha.
+    ///
+    /// # Returns
+    ///
+    /// The expanded path of the settings directory
+    pub fn get_default_dir(&self) -> PathBuf {
+        let dir = match self {
+            Launcher::PowerToysRun => r"%LOCALAPPDATA%\Microsoft\PowerToys\PowerToys Run\Settings",
+            Launcher::FlowLauncher => r"%APPDATA%\FlowLauncher\Settings",
+            Launcher::Wox => r"%APPDATA%\Wox\Settings",
+            Launcher::Keypirinha => r"%APPDATA%\Keypirinha",
+        };
+        PathBuf::from(expand_path(dir))
+    }
+
+    /// Reads the aliases and the usage history of the launcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The settings directory of the launcher, see `get_default_dir`
+    ///
+    /// # Returns
+    ///
+    /// The data read from the launcher
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Io` if the usage history cannot be read,
+    /// or `KasuriError::Archive` if a file has an unknown format
+    pub fn read(&self, dir: &Path) -> KasuriResult<LauncherData> {
+        log::info!("Reading data of {} from {}", self.get_name(), dir.display());
+        let records_file = match self {
+            Launcher::Keypirinha => dir.join(KEYPIRINHA_LOCAL_DIR).join(KEYPIRINHA_HISTORY_FILE),
+            _ => dir.join(USER_SELECTED_RECORD_FILE),
+        };
+        let records = std::fs::read_to_string(records_file)?;
+        let usage = match self {
+            Launcher::PowerToysRun => parse_powertoys_records(&records)?,
+            Launcher::FlowLauncher | Launcher::Wox => parse_wox_records(&records)?,
+            Launcher::Keypirinha => parse_keypirinha_history(&records)?,
+        };
+        let aliases = match self {
+            Launcher::FlowLauncher => {
+                match std::fs::read_to_string(dir.join(FLOW_LAUNCHER_SETTINGS_FILE)) {
+                    Ok(settings) => parse_flow_launcher_shortcuts(&settings)?,
+                    Err(e) => {
+                        log::warn!("Failed to read the settings of Flow Launcher: {}", e);
+                        vec![]
+                    }
+                }
+            }
+            Launcher::Keypirinha => {
+                match std::fs::read_to_string(
+                    dir.join(KEYPIRINHA_USER_DIR).join(KEYPIRINHA_CONFIG_FILE),
+                ) {
+                    Ok(config) => parse_keypirinha_aliases(&config)?,
+                    Err(e) => {
+                        log::warn!("Failed to read the configuration of Keypirinha: {}", e);
+                        vec![]
+                    }
+                }
+            }
+            Launcher::PowerToysRun | Launcher::Wox => vec![],
+        };
+        log::info!(
+            "Read {} usage records and {} aliases from {}",
+            usage.len(),
+            aliases.len(),
+            self.get_name()
+        );
+        Ok(LauncherData { aliases, usage })
+    }
+}
+
+impl FromStr for Launcher {
+    type Err = String;
+
+    /// Parses the name of a launcher, see `get_name`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|launcher| launcher.get_name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown launcher '{}', expected one of: {}",
+                    s,
+                    Self::ALL.map(|launcher| launcher.get_name()).join(", ")
+                )
+            })
+    }
+}
+
+/// Parses `UserSelectedRecord.json` of PowerToys Run.
+///
+/// The records are stored by `"<title>|<subtitle>"` with the number of selections
+/// and the time of the last selection:
+///
+/// ```json
+/// { "recordsDict": { "Notepad|C:\\...\\Notepad.lnk": { "SelectedCount": 3, "LastSelected": "2024-05-01T10:00:00+09:00" } } }
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The contents of the file
+///
+/// # Returns
+///
+/// The usage of each record
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if the source is not a PowerToys Run record file
+pub fn parse_powertoys_records(source: &str) -> KasuriResult<Vec<LauncherUsage>> {
+    let records = find_records(source, "recordsdict")?;
+    Ok(records
+        .iter()
+        .filter_map(|(key, value)| {
+            let usage_count = get_ignore_case(value, "selectedcount")?.as_i64()?;
+            let last_used = get_ignore_case(value, "lastselected")
+                .and_then(Value::as_str)
+                .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+                .map(|time| time.timestamp())
+                .unwrap_or(0);
+            let (name, path) = match key.split_once('|') {
+                Some((title, subtitle)) => (title.to_string(), find_path(subtitle)),
+                None => (key.clone(), None),
+            };
+            Some(LauncherUsage {
+                key: key.clone(),
+                name,
+                path: path.map(str::to_string),
+                usage_count,
+                last_used,
+            })
+        })
+        .collect())
+}
+
+/// Parses `UserSelectedRecord.json` of Wox and Flow Launcher.
+///
+/// The records are stored by the title and the subtitle written together,
+/// with the number of selections:
+///
+/// ```json
+/// { "records": { "NotepadC:\\...\\Notepad.lnk": 3 } }
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The contents of the file
+///
+/// # Returns
+///
+/// The usage of each record
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if the source is not a Wox record file
+pub fn parse_wox_records(source: &str) -> KasuriResult<Vec<LauncherUsage>> {
+    let records = find_records(source, "records")?;
+    Ok(records
+        .iter()
+        .filter_map(|(key, value)| {
+            let usage_count = value.as_i64()?;
+            let path = find_path(key);
+            let name = match path {
+                Some(path) => key[..key.len() - path.len()].to_string(),
+                None => key.clone(),
+            };
+            Some(LauncherUsage {
+                key: key.clone(),
+                name,
+                path: path.map(str::to_string),
+                usage_count,
+                last_used: 0,
+            })
+        })
+        .collect())
+}
+
+/// Parses the custom query shortcuts in `Settings.json` of Flow Launcher.
+///
+/// ```json
+/// { "CustomShortcuts": [ { "Key": "np", "Value": "notepad" } ] }
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The contents of the file
+///
+/// # Returns
+///
+/// The shortcuts as aliases, empty if there are none
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if the source is not valid JSON
+pub fn parse_flow_launcher_shortcuts(source: &str) -> KasuriResult<Vec<LauncherAlias>> {
+    let settings: Value = serde_json::from_str(source)
+        .map_err(|e| KasuriError::Archive(format!("Invalid Flow Launcher settings: {}", e)))?;
+    let Some(shortcuts) = get_ignore_case(&settings, "customshortcuts").and_then(Value::as_array)
+    else {
+        return Ok(vec![]);
+    };
+    Ok(shortcuts
+        .iter()
+        .filter_map(|shortcut| {
+            let alias = get_ignore_case(shortcut, "key")?.as_str()?.trim();
+            let target = get_ignore_case(shortcut, "value")?.as_str()?.trim();
+            (!alias.is_empty() && !target.is_empty()).then(|| LauncherAlias {
+                alias: alias.to_string(),
+                target: target.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Parses `Local\History.ini` of Keypirinha.
+///
+/// Each section is a selected item by its label, with the target of the item,
+/// the number of selections and the time of the last selection:
+///
+/// ```ini
+/// [Notepad]
+/// target = C:\...\Notepad.lnk
+/// count = 4
+/// last_used = 2024-05-01T10:00:00+09:00
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The contents of the file
+///
+/// # Returns
+///
+/// The usage of each item, without the items that have no count
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if the source is not an INI file
+pub fn parse_keypirinha_history(source: &str) -> KasuriResult<Vec<LauncherUsage>> {
+    let mut usage: Vec<LauncherUsage> = vec![];
+    for (section, key, value) in parse_ini(source)? {
+        if section.is_empty() {
+            continue;
+        }
+        if usage.last().is_none_or(|record| record.key != section) {
+            usage.push(LauncherUsage {
+                key: section.to_string(),
+                name: section.to_string(),
+                path: None,
+                usage_count: -1,
+                last_used: 0,
+            });
+        }
+        let record = usage.last_mut().unwrap();
+        match key.to_lowercase().as_str() {
+            "target" => record.path = find_path(value).map(str::to_string),
+            "count" => record.usage_count = value.parse().unwrap_or(-1),
+            "last_used" => {
+                record.last_used = chrono::DateTime::parse_from_rfc3339(value)
+                    .map(|time| time.timestamp())
+                    .unwrap_or(0)
+            }
+            _ => {}
+        }
+    }
+    usage.retain(|record| record.usage_count >= 0);
+    Ok(usage)
+}
+
+/// Parses the aliases in `User\Keypirinha.ini` of Keypirinha.
+///
+/// ```ini
+/// [aliases]
+/// np = Notepad
+/// ```
+///
+/// # Arguments
+///
+/// * `source` - The contents of the file
+///
+/// # Returns
+///
+/// The aliases, empty if there are none
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if the source is not an INI file
+pub fn parse_keypirinha_aliases(source: &str) -> KasuriResult<Vec<LauncherAlias>> {
+    Ok(parse_ini(source)?
+        .into_iter()
+        .filter(|(section, key, value)| {
+            section.eq_ignore_ascii_case(KEYPIRINHA_ALIASES_SECTION)
+                && !key.is_empty()
+                && !value.is_empty()
+        })
+        .map(|(_, alias, target)| LauncherAlias {
+            alias: alias.to_string(),
+            target: target.to_string(),
+        })
+        .collect())
+}
+
+/// Finds the application that imported data refers to.
+///
+/// An application is matched by its path or ID if `path` is given, otherwise
+/// by its name. If no name is equal, the application with the longest name
+/// that `name` starts with is used, because some launchers add text to the title.
+/// Names are compared without regard to case and must match a single application.
+///
+/// # Arguments
+///
+/// * `name` - The title or query in the other launcher
+/// * `path` - The path in the other launcher, if known
+/// * `targets` - The applications of KASURI
+///
+/// # Returns
+///
+/// The matching application, or `None` if there is no single match
+pub fn match_entry<'a>(
+    name: &str,
+    path: Option<&str>,
+    targets: &[LauncherImportTarget<'a>],
+) -> Option<LauncherImportTarget<'a>> {
+    if let Some(path) = path
+        && let Some(target) = targets.iter().find(|target| {
+            target.path.eq_ignore_ascii_case(path) || target.app_id.eq_ignore_ascii_case(path)
+        })
+    {
+        return Some(*target);
+    }
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    let equal: Vec<_> = targets
+        .iter()
+        .filter(|target| target.name.to_lowercase() == name)
+        .collect();
+    if let [target] = equal[..] {
+        return Some(*target);
+    }
+    if !equal.is_empty() {
+        return None;
+    }
+    let longest = targets
+        .iter()
+        .filter(|target| !target.name.is_empty() && name.starts_with(&target.name.to_lowercase()))
+        .map(|target| target.name.len())
+        .max()?;
+    let mut prefixes = targets.iter().filter(|target| {
+        target.name.len() == longest && name.starts_with(&target.name.to_lowercase())
+    });
+    match (prefixes.next(), prefixes.next()) {
+        (Some(target), None) => Some(*target),
+        _ => None,
+    }
+}
+
+/// Parses a record file and returns the object with the records.
+///
+/// # Arguments
+///
+/// * `source` - The contents of the record file
+/// * `field` - Name of the field with the records, compared without regard to case
+fn find_records(source: &str, field: &str) -> KasuriResult<serde_json::Map<String, Value>> {
+    let value: Value = serde_json::from_str(source)
+        .map_err(|e| KasuriError::Archive(format!("Invalid record file: {}", e)))?;
+    get_ignore_case(&value, field)
+        .and_then(Value::as_object)
+        .cloned()
+        .ok_or_else(|| KasuriError::Archive(format!("Record file has no {} object", field)))
+}
+
+/// Parses an INI file as used by Keypirinha.
+///
+/// Lines starting with `;` or `#` are comments, and keys before the first section
+/// have an empty section name.
+///
+/// # Returns
+///
+/// The section, the key and the value of each entry, trimmed and in file order
+///
+/// # Errors
+///
+/// Returns `KasuriError::Archive` if a line is neither a section, an entry nor a comment
+fn parse_ini(source: &str) -> KasuriResult<Vec<(&str, &str, &str)>> {
+    let mut section = "";
+    let mut entries = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            section = name.trim();
+        } else if let Some((key, value)) = line.split_once('=') {
+            entries.push((section, key.trim(), value.trim()));
+        } else {
+            return Err(KasuriError::Archive(format!(
+                "Invalid INI file at line {}: {}",
+                i + 1,
+                line
+            )));
+        }
+    }
+    Ok(entries)
+}
+
+/// Returns a field of a JSON object, comparing the name without regard to case.
+///
+/// The launchers are written in C# and their field names are PascalCase or camelCase
+/// depending on the version.
+fn get_ignore_case<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Finds the start of an absolute Windows path in a text.
+///
+/// # Returns
+///
+/// The text from the drive letter or the UNC prefix to the end, or `None` if there is none
+fn find_path(text: &str) -> Option<&str> {
+    let bytes = text.as_bytes();
+    (0..bytes.len())
+        .find(|&i| {
+            let rest = &bytes[i..];
+            rest.starts_with(br"\\")
+                || (rest.len() >= 3
+                    && rest[0].is_ascii_alphabetic()
+                    && rest[1] == b':'
+                    && (rest[2] == b'\\' || rest[2] == b'/'))
+        })
+        .map(|i| &text[i..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGETS: [LauncherImportTarget; 4] = [
+        LauncherImportTarget {
+            app_id: r"C:\Apps\Notepad.lnk",
+            name: "Notepad",
+            path: r"C:\Apps\Notepad.lnk",
+        },
+        LauncherImportTarget {
+            app_id: r"C:\Apps\Notepad++.lnk",
+            name: "Notepad++",
+            path: r"C:\Apps\Notepad++.lnk",
+        },
+        LauncherImportTarget {
+            app_id: "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App",
+            name: "Calculator",
+            path: "Microsoft.WindowsCalculator_8.0.0.0_x64__8wekyb3d8bbwe",
+        },
+        LauncherImportTarget {
+            app_id: r"C:\Other\Calculator.exe",
+            name: "Calculator",
+            path: r"C:\Other\Calculator.exe",
+        },
+    ];
+
+    #[test]
+    fn test_launcher_from_str() {
+        assert_eq!("Flow".parse(), Ok(Launcher::FlowLauncher));
+        assert_eq!("powertoys".parse(), Ok(Launcher::PowerToysRun));
+        assert_eq!("Keypirinha".parse(), Ok(Launcher::Keypirinha));
+        assert!("launchy".parse::<Launcher>().is_err());
+    }
+
+    #[test]
+    fn test_find_path() {
+        assert_eq!(
+            find_path(r"NotepadC:\Apps\Notepad.lnk"),
+            Some(r"C:\Apps\Notepad.lnk")
+        );
+        assert_eq!(
+            find_path(r"Share\\server\apps\a.exe"),
+            Some(r"\\server\apps\a.exe")
+        );
+        assert_eq!(find_path("Calculator"), None);
+    }
+
+    #[test]
+    fn test_match_entry() {
+        let app_id =
+            |target: Option<LauncherImportTarget<'static>>| target.map(|target| target.app_id);
+        assert_eq!(
+            app_id(match_entry("", Some(r"c:\apps\notepad.lnk"), &TARGETS)),
+            Some(r"C:\Apps\Notepad.lnk")
+        );
+        assert_eq!(
+            app_id(match_entry("notepad++", None, &TARGETS)),
+            Some(r"C:\Apps\Notepad++.lnk")
+        );
+        assert_eq!(
+            app_id(match_entry("Notepad++ Text editor", None, &TARGETS)),
+            Some(r"C:\Apps\Notepad++.lnk")
+        );
+        // The name is not unique.
+        assert_eq!(app_id(match_entry("Calculator", None, &TARGETS)), None);
+        assert_eq!(app_id(match_entry("Paint", None, &TARGETS)), None);
+    }
+}
@@ -1,6 +1,6 @@
 use rolling_file::{BasicRollingFileAppender, RollingConditionBasic};
 use std::{
-    path::PathBuf,
+    path::Path,
     sync::{LazyLock, Mutex},
 };
 
@@ -14,34 +14,23 @@ static INSTANCE: LazyLock<Mutex<Logger>> = LazyLock::new(|| {
     })
 });
 
-/// Returns the path to the log directory.
-/// This function constructs the path to the `logs` directory located next to the executable.
-/// # Returns
-/// A `PathBuf` representing the log directory.
-pub fn get_log_directory() -> PathBuf {
-    std::env::current_exe()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .join("logs")
-}
-
 /// Initializes the logger for the KASURI application.
 /// This function sets up a global logger that writes logs to both the console and a rolling file.
-/// The log files are stored in a `logs` directory next to the executable.
+/// The log files are stored in the given log directory (see `KasuriPaths::get_log_dir`).
 /// The logger supports log rotation, keeping up to 5 old log files, each with a maximum size of 10 MB.
 /// The log messages are formatted with a timestamp, log level, and message content.
 /// The log level can be dynamically changed at runtime.
+/// # Arguments
+/// * `log_dir`: The directory to write the log files to.
 /// # Panics
 /// Panics if the log directory cannot be created or the rolling file appender cannot be initialized.
 /// Panics if logger initialization fails.
-pub fn init_logger() -> () {
+pub fn init_logger(log_dir: &Path) -> () {
     let top_dispatch = fern::Dispatch::new();
     let console_dispatch = fern::Dispatch::new().chain(std::io::stdout());
 
-    let log_dir = get_log_directory();
     if !log_dir.exists() {
-        std::fs::create_dir_all(&log_dir).expect("Failed to create log directory");
+        std::fs::create_dir_all(log_dir).expect("Failed to create log directory");
     }
     let log_file = BasicRollingFileAppender::new(
         log_dir.join(LOG_FILE_NAME),
@@ -1,2 +1,17 @@
+pub mod archive;
+pub mod automation;
+pub mod big_int;
+pub mod calculator;
+pub mod deep_link;
+pub mod error;
+pub mod launcher_import;
 pub mod log;
+pub mod path_expansion;
+pub mod paths;
+pub mod policy;
 pub mod settings;
+pub mod settings_backup;
+pub mod settings_migration;
+pub mod settings_watcher;
+pub mod unit_conversion;
+pub mod web_search;
@@ -0,0 +1,292 @@
+//! Expansion of environment variables and `~` in paths from the settings.
+//!
+//! Paths in `settings.toml` may refer to `%VAR%`, `$VAR` or `${VAR}` and start
+//! with `~` for the home directory, so that one settings file can be shared
+//! across users and machines. The settings keep the unexpanded form, which is
+//! what is shown in the settings window and written back on save.
+//! `contract_path` does the reverse, for paths that are taken to another machine.
+
+use std::path::Path;
+
+/// Variables that `contract_path` writes in place of their values,
+/// more specific directories first
+const CONTRACTED_VARIABLES: [&str; 7] = [
+    "LOCALAPPDATA",
+    "APPDATA",
+    "ProgramData",
+    "ProgramFiles(x86)",
+    "ProgramFiles",
+    "SystemRoot",
+    "USERPROFILE",
+];
+
+/// Expands environment variables and a leading `~` in a path.
+///
+/// Variables that are not defined are left as they are.
+///
+/// # Arguments
+///
+/// * `path` - The path as written in the settings
+///
+/// # Returns
+///
+/// The expanded path
+pub fn expand_path(path: &str) -> String {
+    let home_dir = dirs::home_dir();
+    expand_path_with(path, |name| std::env::var(name).ok(), home_dir.as_deref())
+}
+
+/// Expands environment variables and a leading `~` in a path,
+/// looking up the variables and the home directory with the given sources.
+///
+/// # Arguments
+///
+/// * `path` - The path as written in the settings
+/// * `lookup` - Returns the value of an environment variable, `None` if it is not defined
+/// * `home_dir` - The home directory, `None` if it is unknown
+///
+/// # Returns
+///
+/// The expanded path
+pub fn expand_path_with<F>(path: &str, lookup: F, home_dir: Option<&Path>) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(path.len());
+    let mut rest = path;
+
+    if let (Some(after), Some(home_dir)) = (rest.strip_prefix('~'), home_dir)
+        && (after.is_empty() || after.starts_with(['/', '\\']))
+    {
+        result.push_str(&home_dir.to_string_lossy());
+        rest = after;
+    }
+
+    while let Some(start) = rest.find(['%', '$']) {
+        result.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        match expand_variable(candidate, &lookup) {
+            Some((value, length)) => {
+                result.push_str(&value);
+                rest = &candidate[length..];
+            }
+            None => {
+                result.push_str(&candidate[..1]);
+                rest = &candidate[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Replaces the start of a path with a variable that refers to it.
+///
+/// The well known directories in `CONTRACTED_VARIABLES` are written as `%VAR%`,
+/// and the home directory as `~` if none of them matches, so that the path
+/// can be expanded on another machine or for another user, see `expand_path`.
+///
+/// # Arguments
+///
+/// * `path` - An expanded path
+///
+/// # Returns
+///
+/// The path with a variable for its start, or the path as it is if no variable matches
+pub fn contract_path(path: &str) -> String {
+    let home_dir = dirs::home_dir();
+    contract_path_with(path, |name| std::env::var(name).ok(), home_dir.as_deref())
+}
+
+/// Replaces the start of a path with a variable that refers to it,
+/// looking up the variables and the home directory with the given sources.
+///
+/// The longest matching directory wins. Directories are compared without regard
+/// to ASCII case, because paths on Windows are case-insensitive.
+///
+/// # Arguments
+///
+/// * `path` - An expanded path
+/// * `lookup` - Returns the value of an environment variable, `None` if it is not defined
+/// * `home_dir` - The home directory, `None` if it is unknown
+///
+/// # Returns
+///
+/// The path with a variable for its start, or the path as it is if no variable matches
+pub fn contract_path_with<F>(path: &str, lookup: F, home_dir: Option<&Path>) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let candidates = CONTRACTED_VARIABLES
+        .iter()
+        .filter_map(|name| lookup(name).map(|value| (format!("%{}%", name), value)))
+        .chain(home_dir.map(|dir| ("~".to_string(), dir.to_string_lossy().to_string())));
+    let mut best: Option<(String, usize)> = None;
+    for (variable, value) in candidates {
+        let value = value.trim_end_matches(['/', '\\']);
+        if value.is_empty()
+            || best
+                .as_ref()
+                .is_some_and(|(_, length)| *length >= value.len())
+        {
+            continue;
+        }
+        if starts_with_directory(path, value) {
+            best = Some((variable, value.len()));
+        }
+    }
+    match best {
+        Some((variable, length)) => format!("{}{}", variable, &path[length..]),
+        None => path.to_string(),
+    }
+}
+
+/// Checks whether a path is a directory or inside it, ignoring ASCII case.
+///
+/// # Arguments
+///
+/// * `path` - The path to check
+/// * `directory` - The directory, without a trailing separator
+pub fn starts_with_directory(path: &str, directory: &str) -> bool {
+    path.get(..directory.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(directory))
+        && (path.len() == directory.len() || path[directory.len()..].starts_with(['/', '\\']))
+}
+
+/// Expands the variable at the start of `text`.
+///
+/// # Returns
+///
+/// The value of the variable and the length of the reference in `text`,
+/// or `None` if `text` does not start with a reference to a defined variable
+fn expand_variable<F>(text: &str, lookup: &F) -> Option<(String, usize)>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let (name, length) = if let Some(after) = text.strip_prefix('%') {
+        let end = after.find('%')?;
+        (&after[..end], end + 2)
+    } else if let Some(after) = text.strip_prefix("${") {
+        let end = after.find('}')?;
+        (&after[..end], end + 3)
+    } else {
+        let after = &text[1..];
+        let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        (&after[..end], end + 1)
+    };
+    if !is_variable_name(name) {
+        return None;
+    }
+    lookup(name).map(|value| (value, length))
+}
+
+/// Checks whether a name can be the name of an environment variable.
+///
+/// Parentheses are allowed for Windows variables such as `ProgramFiles(x86)`.
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '(' || c == ')')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(path: &str) -> String {
+        expand_path_with(
+            path,
+            |name| match name {
+                "APPDATA" => Some(r"C:\Users\kasuri\AppData\Roaming".to_string()),
+                "ProgramFiles(x86)" => Some(r"C:\Program Files (x86)".to_string()),
+                "TOOLS" => Some("/opt/tools".to_string()),
+                _ => None,
+            },
+            Some(Path::new("/home/kasuri")),
+        )
+    }
+
+    #[test]
+    fn test_expand_windows_variables() {
+        assert_eq!(
+            expand(r"%APPDATA%\Microsoft\Windows\Start Menu"),
+            r"C:\Users\kasuri\AppData\Roaming\Microsoft\Windows\Start Menu"
+        );
+        assert_eq!(
+            expand(r"%ProgramFiles(x86)%\Tools"),
+            r"C:\Program Files (x86)\Tools"
+        );
+    }
+
+    #[test]
+    fn test_expand_unix_variables() {
+        assert_eq!(expand("$TOOLS/bin"), "/opt/tools/bin");
+        assert_eq!(expand("${TOOLS}bin"), "/opt/toolsbin");
+    }
+
+    #[test]
+    fn test_expand_home_directory() {
+        assert_eq!(expand("~"), "/home/kasuri");
+        assert_eq!(expand("~/apps"), "/home/kasuri/apps");
+        assert_eq!(expand(r"~\apps"), r"/home/kasuri\apps");
+        assert_eq!(expand("~other/apps"), "~other/apps");
+        assert_eq!(expand("/apps/~"), "/apps/~");
+    }
+
+    fn contract(path: &str) -> String {
+        contract_path_with(
+            path,
+            |name| match name {
+                "APPDATA" => Some(r"C:\Users\kasuri\AppData\Roaming".to_string()),
+                "USERPROFILE" => Some(r"C:\Users\kasuri".to_string()),
+                "ProgramFiles" => Some(r"C:\Program Files\".to_string()),
+                _ => None,
+            },
+            Some(Path::new("/home/kasuri")),
+        )
+    }
+
+    #[test]
+    fn test_contract_path() {
+        assert_eq!(
+            contract(r"c:\users\kasuri\AppData\Roaming\Microsoft\Windows\Start Menu"),
+            r"%APPDATA%\Microsoft\Windows\Start Menu"
+        );
+        assert_eq!(
+            contract(r"C:\Users\kasuri\Desktop"),
+            r"%USERPROFILE%\Desktop"
+        );
+        assert_eq!(
+            contract(r"C:\Program Files\App\app.exe"),
+            r"%ProgramFiles%\App\app.exe"
+        );
+        assert_eq!(contract("/home/kasuri/apps"), "~/apps");
+        assert_eq!(
+            contract(r"C:\Users\kasuri2\Desktop"),
+            r"C:\Users\kasuri2\Desktop"
+        );
+        assert_eq!(contract("WindowsStoreApp"), "WindowsStoreApp");
+        assert_eq!(
+            expand(&contract(r"C:\Users\kasuri\AppData\Roaming\a")),
+            r"C:\Users\kasuri\AppData\Roaming\a"
+        );
+    }
+
+    #[test]
+    fn test_keep_undefined_and_incomplete_references() {
+        assert_eq!(expand(r"%UNDEFINED%\apps"), r"%UNDEFINED%\apps");
+        assert_eq!(expand("$UNDEFINED/apps"), "$UNDEFINED/apps");
+        assert_eq!(expand("100%"), "100%");
+        assert_eq!(
+            expand("50% of %APPDATA%"),
+            r"50% of C:\Users\kasuri\AppData\Roaming"
+        );
+        assert_eq!(expand("${TOOLS"), "${TOOLS");
+        assert_eq!(expand("$"), "$");
+        assert_eq!(expand("WindowsStoreApp"), "WindowsStoreApp");
+    }
+}
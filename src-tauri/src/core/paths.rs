@@ -0,0 +1,582 @@
+//! File system locations used by KASURI.
+//!
+//! The core never looks up its own directories. Instead a `KasuriPaths` is
+//! injected at construction, so that the core can be embedded in other hosts
+//! or driven from tests against a temporary directory.
+//!
+//! By default the data is stored per user. If a portable marker file exists
+//! next to the executable, everything is stored beside the executable instead.
+//! The policy file is machine-wide in both cases.
+
+use crate::KasuriResult;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identifier of the application, used as the name of the cache directory
+const APP_IDENTIFIER: &str = "jp.sabiz.kasuri";
+/// Name of the per-user data directory
+const DATA_DIR_NAME: &str = "KASURI";
+/// Name of the marker file next to the executable that enables portable mode
+pub const PORTABLE_MARKER_FILE_NAME: &str = "kasuri.portable";
+/// Name of the cache directory inside the data directory in portable mode
+const PORTABLE_CACHE_DIR_NAME: &str = "cache";
+/// Name of the settings file
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+/// Name of the SQLite database file
+const DATABASE_FILE_NAME: &str = "kasuri.db";
+/// Name of the log directory
+const LOG_DIR_NAME: &str = "logs";
+/// Name of the machine-wide policy file
+const POLICY_FILE_NAME: &str = "policy.toml";
+/// Name of the marker file in the data directory written once the legacy files were migrated
+const MIGRATION_MARKER_FILE_NAME: &str = "migrated";
+
+/// Locations of the files and directories used by KASURI.
+///
+/// Use `KasuriPaths::builder()` to override single locations,
+/// or `KasuriPaths::default()` for the locations of an installed application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KasuriPaths {
+    /// Whether the data is stored next to the executable
+    portable: bool,
+    /// Directory that holds the settings file, the database and the logs by default
+    data_dir: PathBuf,
+    /// Directory for cached data such as application icons
+    cache_dir: PathBuf,
+    /// Path of the settings file
+    settings_file: PathBuf,
+    /// Path of the SQLite database file
+    database_file: PathBuf,
+    /// Directory for the log files
+    log_dir: PathBuf,
+    /// Path of the machine-wide policy file
+    policy_file: PathBuf,
+}
+
+impl KasuriPaths {
+    /// Creates a builder for `KasuriPaths`.
+    ///
+    /// # Returns
+    ///
+    /// A new `KasuriPathsBuilder` without any overridden locations
+    pub fn builder() -> KasuriPathsBuilder {
+        KasuriPathsBuilder::default()
+    }
+
+    /// Returns the data directory.
+    ///
+    /// # Returns
+    ///
+    /// The directory that holds the settings file, the database and the logs by default
+    pub fn get_data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// Returns the cache directory.
+    ///
+    /// # Returns
+    ///
+    /// The directory for cached data such as application icons
+    pub fn get_cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Returns the path of the settings file.
+    ///
+    /// # Returns
+    ///
+    /// The path of the settings file
+    pub fn get_settings_file(&self) -> &Path {
+        &self.settings_file
+    }
+
+    /// Returns the path of the SQLite database file.
+    ///
+    /// # Returns
+    ///
+    /// The path of the database file
+    pub fn get_database_file(&self) -> &Path {
+        &self.database_file
+    }
+
+    /// Returns the path of the database file of a settings profile.
+    ///
+    /// Used for profiles that keep their usage statistics separately,
+    /// e.g. `kasuri.db` becomes `kasuri-work.db` for the profile `work`.
+    /// Characters that cannot be used in file names are replaced with `_`.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Name of the profile, `None` for the shared database
+    ///
+    /// # Returns
+    ///
+    /// The path of the database file next to the shared database file
+    pub fn get_profile_database_file(&self, profile: Option<&str>) -> PathBuf {
+        let Some(profile) = profile else {
+            return self.database_file.clone();
+        };
+        let profile: String = profile
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let stem = self
+            .database_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut database_file = self.database_file.clone();
+        database_file.set_file_name(format!("{}-{}", stem, profile));
+        if let Some(extension) = self.database_file.extension() {
+            database_file.set_extension(extension);
+        }
+        database_file
+    }
+
+    /// Returns the log directory.
+    ///
+    /// # Returns
+    ///
+    /// The directory for the log files
+    pub fn get_log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// Returns the path of the machine-wide policy file.
+    ///
+    /// # Returns
+    ///
+    /// The path of the policy file, which does not need to exist
+    pub fn get_policy_file(&self) -> &Path {
+        &self.policy_file
+    }
+
+    /// Returns whether KASURI runs in portable mode.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the data is stored next to the executable
+    pub fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    /// Creates the directories that hold the settings file, the database, the logs and the cache.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the operation
+    pub fn create_directories(&self) -> KasuriResult<()> {
+        let settings_dir = self.settings_file.parent();
+        let database_dir = self.database_file.parent();
+        [
+            Some(self.data_dir.as_path()),
+            Some(self.cache_dir.as_path()),
+            Some(self.log_dir.as_path()),
+            settings_dir,
+            database_dir,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .try_for_each(|dir| {
+            log::debug!("Creating directory: {}", dir.display());
+            fs::create_dir_all(dir)
+        })?;
+        Ok(())
+    }
+
+    /// Migrates the files of older versions, which were stored next to the executable.
+    ///
+    /// Nothing is migrated in portable mode, because the files are already in place.
+    ///
+    /// # Returns
+    ///
+    /// The paths of the migrated files and directories, see `migrate_from`
+    pub fn migrate_legacy_files(&self) -> KasuriResult<Vec<PathBuf>> {
+        if self.portable {
+            return Ok(vec![]);
+        }
+        match executable_dir() {
+            Some(exe_dir) => self.migrate_from(&exe_dir),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Copies the settings file, the database and the logs from the given directory.
+    ///
+    /// Each of them is copied only if it exists in `legacy_dir` and does not exist
+    /// at the current location yet. The original files are left in place, because
+    /// the directory of the executable may be shared by all users of the machine
+    /// and is often not writable. A marker file is then written to the data directory,
+    /// so that the migration happens on the first start only and files that the user
+    /// deleted afterwards are not copied again.
+    ///
+    /// # Arguments
+    ///
+    /// * `legacy_dir` - The directory that holds the files of an older version
+    ///
+    /// # Returns
+    ///
+    /// The paths of the migrated files and directories at their new location
+    pub fn migrate_from(&self, legacy_dir: &Path) -> KasuriResult<Vec<PathBuf>> {
+        let marker = self.data_dir.join(MIGRATION_MARKER_FILE_NAME);
+        if legacy_dir == self.data_dir || marker.exists() {
+            return Ok(vec![]);
+        }
+        let mut migrated = vec![];
+        for (source, target) in [
+            (legacy_dir.join(SETTINGS_FILE_NAME), &self.settings_file),
+            (legacy_dir.join(DATABASE_FILE_NAME), &self.database_file),
+        ] {
+            if source.is_file() && !target.exists() {
+                log::debug!("Migrating {} to {}", source.display(), target.display());
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(&source, target)?;
+                migrated.push(target.clone());
+            }
+        }
+
+        let source = legacy_dir.join(LOG_DIR_NAME);
+        if source.is_dir() && !self.log_dir.exists() {
+            log::debug!(
+                "Migrating {} to {}",
+                source.display(),
+                self.log_dir.display()
+            );
+            fs::create_dir_all(&self.log_dir)?;
+            for entry in fs::read_dir(&source)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    fs::copy(entry.path(), self.log_dir.join(entry.file_name()))?;
+                }
+            }
+            migrated.push(self.log_dir.clone());
+        }
+        fs::create_dir_all(&self.data_dir)?;
+        fs::write(&marker, "")?;
+        Ok(migrated)
+    }
+}
+
+impl Default for KasuriPaths {
+    /// Creates the locations of an installed application.
+    ///
+    /// The settings file, the database and the logs are placed in the per-user
+    /// data directory, and the icons are cached in the platform cache directory.
+    /// In portable mode everything is placed next to the executable.
+    fn default() -> Self {
+        KasuriPaths::builder().build()
+    }
+}
+
+/// Builder for `KasuriPaths`.
+///
+/// Locations that are not set are derived from the data directory,
+/// which defaults to the per-user data directory, or to the directory
+/// of the executable in portable mode.
+#[derive(Debug, Default)]
+pub struct KasuriPathsBuilder {
+    data_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    settings_file: Option<PathBuf>,
+    database_file: Option<PathBuf>,
+    log_dir: Option<PathBuf>,
+    policy_file: Option<PathBuf>,
+}
+
+impl KasuriPathsBuilder {
+    /// Sets the data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - Directory that holds the settings file, the database and the logs
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Sets the cache directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache_dir` - Directory for cached data such as application icons
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Sets the path of the settings file.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_file` - Path of the settings file
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn settings_file(mut self, settings_file: impl Into<PathBuf>) -> Self {
+        self.settings_file = Some(settings_file.into());
+        self
+    }
+
+    /// Sets the path of the SQLite database file.
+    ///
+    /// # Arguments
+    ///
+    /// * `database_file` - Path of the database file
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn database_file(mut self, database_file: impl Into<PathBuf>) -> Self {
+        self.database_file = Some(database_file.into());
+        self
+    }
+
+    /// Sets the log directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_dir` - Directory for the log files
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn log_dir(mut self, log_dir: impl Into<PathBuf>) -> Self {
+        self.log_dir = Some(log_dir.into());
+        self
+    }
+
+    /// Sets the path of the machine-wide policy file.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy_file` - Path of the policy file
+    ///
+    /// # Returns
+    ///
+    /// The builder for chaining
+    pub fn policy_file(mut self, policy_file: impl Into<PathBuf>) -> Self {
+        self.policy_file = Some(policy_file.into());
+        self
+    }
+
+    /// Builds the `KasuriPaths`, filling in the locations that were not set.
+    ///
+    /// # Returns
+    ///
+    /// The resulting `KasuriPaths`
+    pub fn build(self) -> KasuriPaths {
+        let portable_dir = match self.data_dir {
+            Some(_) => None,
+            None => portable_dir(),
+        };
+        let portable = portable_dir.is_some();
+        let data_dir = self
+            .data_dir
+            .or(portable_dir)
+            .unwrap_or_else(default_data_dir);
+        let cache_dir = self.cache_dir.unwrap_or_else(|| {
+            if portable {
+                data_dir.join(PORTABLE_CACHE_DIR_NAME)
+            } else {
+                default_cache_dir(&data_dir)
+            }
+        });
+        let paths = KasuriPaths {
+            portable,
+            settings_file: self
+                .settings_file
+                .unwrap_or_else(|| data_dir.join(SETTINGS_FILE_NAME)),
+            database_file: self
+                .database_file
+                .unwrap_or_else(|| data_dir.join(DATABASE_FILE_NAME)),
+            log_dir: self.log_dir.unwrap_or_else(|| data_dir.join(LOG_DIR_NAME)),
+            policy_file: self.policy_file.unwrap_or_else(default_policy_file),
+            cache_dir,
+            data_dir,
+        };
+        log::debug!("Using paths: {:?}", paths);
+        paths
+    }
+}
+
+/// Returns the directory of the executable.
+fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// Returns the directory of the executable if the portable marker file exists in it.
+fn portable_dir() -> Option<PathBuf> {
+    executable_dir().filter(|dir| dir.join(PORTABLE_MARKER_FILE_NAME).is_file())
+}
+
+/// Returns the per-user data directory of the application.
+///
+/// Falls back to the directory of the executable
+/// if the platform has no per-user data directory.
+fn default_data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .map(|dir| dir.join(DATA_DIR_NAME))
+        .or_else(executable_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Returns the machine-wide location of the policy file.
+///
+/// `%ProgramData%\KASURI\policy.toml` on Windows and `/etc/kasuri/policy.toml` elsewhere.
+fn default_policy_file() -> PathBuf {
+    if cfg!(windows) {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+            .join(DATA_DIR_NAME)
+            .join(POLICY_FILE_NAME)
+    } else {
+        PathBuf::from("/etc/kasuri").join(POLICY_FILE_NAME)
+    }
+}
+
+/// Returns the platform cache directory of the application.
+///
+/// Falls back to a `cache` directory inside the data directory
+/// if the platform has no cache directory.
+fn default_cache_dir(data_dir: &Path) -> PathBuf {
+    dirs::cache_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .unwrap_or_else(|| data_dir.join(PORTABLE_CACHE_DIR_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_derives_paths_from_data_dir() {
+        let paths = KasuriPaths::builder()
+            .data_dir("/data")
+            .cache_dir("/cache")
+            .build();
+        assert_eq!(paths.get_data_dir(), Path::new("/data"));
+        assert_eq!(paths.get_cache_dir(), Path::new("/cache"));
+        assert_eq!(paths.get_settings_file(), Path::new("/data/settings.toml"));
+        assert_eq!(paths.get_database_file(), Path::new("/data/kasuri.db"));
+        assert_eq!(paths.get_log_dir(), Path::new("/data/logs"));
+    }
+
+    #[test]
+    fn test_build_keeps_overridden_paths() {
+        let paths = KasuriPaths::builder()
+            .data_dir("/data")
+            .settings_file("/etc/kasuri.toml")
+            .database_file("/var/kasuri.db")
+            .log_dir("/var/log/kasuri")
+            .policy_file("/etc/kasuri/policy.toml")
+            .build();
+        assert_eq!(paths.get_settings_file(), Path::new("/etc/kasuri.toml"));
+        assert_eq!(
+            paths.get_policy_file(),
+            Path::new("/etc/kasuri/policy.toml")
+        );
+        assert_eq!(paths.get_database_file(), Path::new("/var/kasuri.db"));
+        assert_eq!(paths.get_log_dir(), Path::new("/var/log/kasuri"));
+        assert_eq!(
+            paths.get_profile_database_file(Some("demo/screen share")),
+            Path::new("/var/kasuri-demo_screen_share.db")
+        );
+        assert_eq!(
+            paths.get_profile_database_file(None),
+            Path::new("/var/kasuri.db")
+        );
+    }
+
+    #[test]
+    fn test_build_with_data_dir_is_not_portable() {
+        let paths = KasuriPaths::builder().data_dir("/data").build();
+        assert!(!paths.is_portable());
+    }
+
+    #[test]
+    fn test_migrate_from_copies_missing_files() {
+        let legacy_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        fs::write(legacy_dir.path().join(SETTINGS_FILE_NAME), "width = 600").unwrap();
+        fs::write(legacy_dir.path().join(DATABASE_FILE_NAME), "db").unwrap();
+        fs::create_dir(legacy_dir.path().join(LOG_DIR_NAME)).unwrap();
+        fs::write(
+            legacy_dir.path().join(LOG_DIR_NAME).join("KASURI.log"),
+            "log",
+        )
+        .unwrap();
+        let paths = KasuriPaths::builder()
+            .data_dir(data_dir.path().join("KASURI"))
+            .build();
+
+        let migrated = paths.migrate_from(legacy_dir.path()).unwrap();
+
+        assert_eq!(migrated.len(), 3);
+        assert_eq!(
+            fs::read_to_string(paths.get_settings_file()).unwrap(),
+            "width = 600"
+        );
+        assert_eq!(fs::read_to_string(paths.get_database_file()).unwrap(), "db");
+        assert_eq!(
+            fs::read_to_string(paths.get_log_dir().join("KASURI.log")).unwrap(),
+            "log"
+        );
+        assert!(legacy_dir.path().join(SETTINGS_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_migrate_from_keeps_existing_files() {
+        let legacy_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        fs::write(legacy_dir.path().join(SETTINGS_FILE_NAME), "width = 600").unwrap();
+        let paths = KasuriPaths::builder().data_dir(data_dir.path()).build();
+        fs::write(paths.get_settings_file(), "width = 800").unwrap();
+
+        let migrated = paths.migrate_from(legacy_dir.path()).unwrap();
+
+        assert!(migrated.is_empty());
+        assert_eq!(
+            fs::read_to_string(paths.get_settings_file()).unwrap(),
+            "width = 800"
+        );
+    }
+
+    #[test]
+    fn test_migrate_from_runs_once() {
+        let legacy_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        fs::write(legacy_dir.path().join(SETTINGS_FILE_NAME), "width = 600").unwrap();
+        fs::write(legacy_dir.path().join(DATABASE_FILE_NAME), "db").unwrap();
+        let paths = KasuriPaths::builder().data_dir(data_dir.path()).build();
+        assert_eq!(paths.migrate_from(legacy_dir.path()).unwrap().len(), 2);
+
+        // A file deleted after the migration is not copied again.
+        fs::remove_file(paths.get_database_file()).unwrap();
+        let migrated = paths.migrate_from(legacy_dir.path()).unwrap();
+
+        assert!(migrated.is_empty());
+        assert!(!paths.get_database_file().exists());
+        assert!(legacy_dir.path().join(DATABASE_FILE_NAME).exists());
+    }
+}
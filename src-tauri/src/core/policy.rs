@@ -0,0 +1,303 @@
+//! Machine-wide policy that overrides or locks user settings.
+//!
+//! The policy file is an optional TOML file in a machine-wide location, see
+//! `KasuriPaths::get_policy_file`. It has two tables with the same keys as
+//! `settings.toml`:
+//!
+//! ```toml
+//! # Used when the user has not set the key
+//! [defaults]
+//! shortcut_key = "ctrl+Space"
+//!
+//! # Always used, the user cannot change them
+//! [enforced]
+//! auto_startup = true
+//! application_hide_list = ['C:\ProgramData\Microsoft\Windows\Start Menu\Programs\Uninstall.lnk']
+//! ```
+
+use crate::core::settings::{Settings, SettingsFieldError};
+use crate::core::settings_migration::SETTINGS_VERSION_KEY;
+use crate::{KasuriError, KasuriResult};
+use std::fs;
+use std::path::Path;
+
+/// Name of the table with the default values
+const POLICY_TABLE_DEFAULTS: &str = "defaults";
+/// Name of the table with the enforced values
+const POLICY_TABLE_ENFORCED: &str = "enforced";
+
+/// Key of the profiles in the settings file, whose values cannot replace enforced values
+const PROFILES_KEY: &str = "profiles";
+
+/// Settings values set by the administrator of the machine.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Policy {
+    /// Values used for the settings that the user has not set
+    defaults: toml::Table,
+    /// Values that replace the settings of the user
+    enforced: toml::Table,
+}
+
+impl Policy {
+    /// Loads the policy file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the policy file
+    ///
+    /// # Returns
+    ///
+    /// The loaded policy, or an empty policy if the file does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy file cannot be read or is not valid
+    pub fn load(path: &Path) -> KasuriResult<Self> {
+        if !path.is_file() {
+            log::debug!("No policy file found at {}", path.display());
+            return Ok(Self::default());
+        }
+        log::info!("Loading policy from file: {}", path.display());
+        let policy = Self::parse(&fs::read_to_string(path)?)?;
+        log::debug!("Policy loaded: {:?}", policy);
+        Ok(policy)
+    }
+
+    /// Loads the policy file, falling back to an empty policy if it is invalid.
+    ///
+    /// Used at startup, so that a broken policy file does not keep KASURI from starting.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the policy file
+    ///
+    /// # Returns
+    ///
+    /// The loaded policy, or an empty policy if the file does not exist or is invalid
+    pub fn load_or_empty(path: &Path) -> Self {
+        Self::load(path).unwrap_or_else(|e| {
+            log::error!(
+                "Failed to load policy file {}, ignoring it: {}",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    /// Parses a policy from TOML.
+    ///
+    /// Keys that are not settings are ignored with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The contents of a policy file
+    ///
+    /// # Returns
+    ///
+    /// The parsed policy
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if the source is not valid TOML
+    /// or `defaults` or `enforced` is not a table
+    pub fn parse(source: &str) -> KasuriResult<Self> {
+        let mut table: toml::Table = toml::from_str(source)?;
+        let known_fields = toml::Table::try_from(Settings::default())?;
+        let mut read_table = |name: &str| -> KasuriResult<toml::Table> {
+            let mut values = match table.remove(name) {
+                None => toml::Table::new(),
+                Some(toml::Value::Table(values)) => values,
+                Some(_) => {
+                    return Err(KasuriError::Settings(format!(
+                        "Policy: {} must be a table",
+                        name
+                    )));
+                }
+            };
+            values.retain(|key, _| {
+                let known = key != SETTINGS_VERSION_KEY && known_fields.contains_key(key);
+                if !known {
+                    log::warn!("Policy: ignoring unknown setting {}.{}", name, key);
+                }
+                known
+            });
+            Ok(values)
+        };
+        let defaults = read_table(POLICY_TABLE_DEFAULTS)?;
+        let enforced = read_table(POLICY_TABLE_ENFORCED)?;
+        table
+            .keys()
+            .for_each(|key| log::warn!("Policy: ignoring unknown table {}", key));
+        Ok(Self { defaults, enforced })
+    }
+
+    /// Returns whether the policy sets nothing.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the policy has neither defaults nor enforced values
+    pub fn is_empty(&self) -> bool {
+        self.defaults.is_empty() && self.enforced.is_empty()
+    }
+
+    /// Returns the default values of the policy.
+    ///
+    /// # Returns
+    ///
+    /// The values used for the settings that the user has not set
+    pub fn get_defaults(&self) -> &toml::Table {
+        &self.defaults
+    }
+
+    /// Returns the names of the settings that the user cannot change.
+    ///
+    /// # Returns
+    ///
+    /// The names of the enforced settings as in the settings file
+    pub fn get_enforced_fields(&self) -> Vec<String> {
+        self.enforced.keys().cloned().collect()
+    }
+
+    /// Returns whether the user cannot change a setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Name of the setting as in the settings file
+    ///
+    /// # Returns
+    ///
+    /// `true` if the setting is enforced by the policy
+    pub fn is_enforced(&self, field: &str) -> bool {
+        self.enforced.contains_key(field)
+    }
+
+    /// Layers the policy over the values of the settings file.
+    ///
+    /// Default values are added for the keys that are missing,
+    /// and enforced values replace the values of the user.
+    /// Profiles cannot replace enforced values, see `remove_enforced_from_profiles`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values of the settings file
+    pub fn apply(&self, values: &mut toml::Table) {
+        for (key, value) in &self.defaults {
+            values.entry(key).or_insert_with(|| value.clone());
+        }
+        for (key, value) in &self.enforced {
+            values.insert(key.clone(), value.clone());
+        }
+        self.remove_enforced_from_profiles(values);
+    }
+
+    /// Removes the enforced settings from the profiles in the values of a settings file,
+    /// so that switching to a profile does not replace them.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values of the settings file
+    pub fn remove_enforced_from_profiles(&self, values: &mut toml::Table) {
+        let Some(toml::Value::Array(profiles)) = values.get_mut(PROFILES_KEY) else {
+            return;
+        };
+        for profile in profiles.iter_mut().filter_map(|v| v.as_table_mut()) {
+            for key in self.enforced.keys() {
+                if profile.remove(key).is_some() {
+                    log::warn!(
+                        "Ignoring '{}' of a profile, it is enforced by the policy",
+                        key
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that new settings do not change the enforced settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The settings in use
+    /// * `new` - The settings to be saved
+    ///
+    /// # Returns
+    ///
+    /// An error for each enforced setting that differs, empty if none differs
+    pub fn check(&self, current: &Settings, new: &Settings) -> Vec<SettingsFieldError> {
+        if self.enforced.is_empty() {
+            return vec![];
+        }
+        let (Ok(current), Ok(new)) = (toml::Table::try_from(current), toml::Table::try_from(new))
+        else {
+            return vec![];
+        };
+        let profile_errors = new
+            .get(PROFILES_KEY)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .flat_map(|(i, profile)| {
+                self.enforced
+                    .keys()
+                    .filter(|key| profile.get(key.as_str()).is_some())
+                    .map(move |key| {
+                        SettingsFieldError::new(
+                            PROFILES_KEY,
+                            Some(i),
+                            format!("{} is locked by the policy", key),
+                        )
+                    })
+            });
+        self.enforced
+            .keys()
+            .filter(|key| current.get(*key) != new.get(*key))
+            .map(|key| SettingsFieldError::new(key, None, "This setting is locked by the policy"))
+            .chain(profile_errors)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+[defaults]
+width = 1000
+unknown_key = 1
+
+[enforced]
+auto_startup = true
+application_hide_list = ["C:\\apps\\uninstall.exe"]
+"#;
+
+    #[test]
+    fn test_parse_ignores_unknown_keys() {
+        let policy = Policy::parse(POLICY).unwrap();
+        assert_eq!(policy.get_defaults().len(), 1);
+        assert_eq!(
+            policy.get_enforced_fields(),
+            vec!["application_hide_list", "auto_startup"]
+        );
+        assert!(policy.is_enforced("auto_startup"));
+        assert!(!policy.is_enforced("width"));
+        assert!(Policy::parse("enforced = 1").is_err());
+        assert!(Policy::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_layers_defaults_and_enforced_values() {
+        let policy = Policy::parse(POLICY).unwrap();
+        let mut values: toml::Table =
+            toml::from_str("auto_startup = false\nlog_level = \"debug\"\n").unwrap();
+        policy.apply(&mut values);
+        assert_eq!(values["width"].as_integer(), Some(1000));
+        assert_eq!(values["auto_startup"].as_bool(), Some(true));
+        assert_eq!(values["log_level"].as_str(), Some("debug"));
+
+        let mut values: toml::Table = toml::from_str("width = 600\n").unwrap();
+        policy.apply(&mut values);
+        assert_eq!(values["width"].as_integer(), Some(600));
+    }
+}
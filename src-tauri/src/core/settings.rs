@@ -1,25 +1,33 @@
-use crate::KasuriResult;
+use crate::core::path_expansion::expand_path;
+use crate::core::policy::Policy;
+use crate::core::settings_backup;
+use crate::core::settings_migration::{self, SETTINGS_VERSION_KEY};
+use crate::core::web_search::{QUERY_PLACEHOLDER, WebSearchKeyword};
+use crate::{KasuriError, KasuriResult};
 use dirs::data_dir;
+use global_hotkey::hotkey::HotKey;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{Read, Write},
-    path::PathBuf,
-};
+use std::{fs::File, io::Read, ops::RangeInclusive, path::Path, str::FromStr};
 
 /// Placeholder for data directory
 const DEFAULT_SETTINGS_MARKER_DATA_DIR: &str = "<DATA_DIR>";
-/// Settings file name
-const SETTINGS_FILE_NAME: &str = "settings.toml";
 /// Constant value indicating Windows Store App
 pub const SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP: &str = "WindowsStoreApp";
+/// Log levels that can be set in the settings
+pub const LOG_LEVELS: [&str; 4] = ["error", "warn", "info", "debug"];
+/// Allowed width of the main window in logical pixels
+pub const WIDTH_RANGE: RangeInclusive<u32> = 200..=7680;
 
 /// Structure that holds application settings.
 ///
 /// This structure contains all configurable parameters for the Kasuri application.
 /// Settings are loaded from a TOML file and can be saved back to the file when modified.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Settings {
+    /// Format version of the settings file, see `settings_migration`
+    settings_version: u32,
+
     /// List of paths to search for applications
     application_search_path_list: Vec<String>,
 
@@ -38,45 +46,372 @@ pub struct Settings {
     /// Global shortcut key combination to show/hide the application
     shortcut_key: String,
 
+    /// Flag indicating whether the local automation API is served
+    automation_api_enabled: bool,
+
+    /// Paths of applications that are hidden from the search results
+    application_hide_list: Vec<String>,
+
+    /// Name of the active profile, empty if no profile is active
+    active_profile: String,
+
     /// List of application name aliases
     application_name_aliases: Vec<ApplicationNameAlias>,
+
+    /// Display names and icons that replace those of single applications
+    application_overrides: Vec<ApplicationOverride>,
+
+    /// Entries that are not files on disk, such as commands, folders and URLs
+    custom_entries: Vec<CustomEntry>,
+
+    /// Keywords that open a web search with the rest of the query
+    web_search_keywords: Vec<WebSearchKeyword>,
+
+    /// Keyword of the web search offered for every query, empty for none
+    web_search_fallback: String,
+
+    /// Named profiles that replace some of the settings while they are active
+    profiles: Vec<SettingsProfile>,
 }
 
-/// Internal structure for partial settings deserializatión.
+/// Aliases for the applications that match an entry.
 ///
-/// This structure is used when loading settings from a file that may not contain
-/// all settings fields. Any missing fields will be filled with default values.
-#[derive(Debug, Clone, Deserialize)]
-struct PartialSettings {
-    /// Optional list of application search paths
-    application_search_path_list: Option<Vec<String>>,
+/// An application matches if it meets all criteria that are set, at least one must be set.
+/// `name` and `path` are patterns compared without regard to case,
+/// where `*` matches any characters and `?` matches a single character.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationNameAlias {
+    /// The ID of the application, see `kasuri-cli list`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub app_id: String,
+
+    /// Pattern of the display name of the application
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+
+    /// Pattern of the path to the application executable or shortcut,
+    /// or of the package full name of a Windows Store app
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+
+    /// The aliases for the application
+    pub aliases: Vec<String>,
+}
+
+impl ApplicationNameAlias {
+    /// Creates an entry that gives aliases to the application with the given ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the application
+    /// * `aliases` - The aliases for the application
+    ///
+    /// # Returns
+    ///
+    /// A new entry that matches only the application with the given ID
+    pub fn for_app_id(app_id: &str, aliases: Vec<String>) -> Self {
+        Self {
+            app_id: app_id.to_string(),
+            aliases,
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether any criterion of the entry is set.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the entry matches no application
+    pub fn has_target(&self) -> bool {
+        [&self.app_id, &self.name, &self.path]
+            .iter()
+            .any(|value| !value.trim().is_empty())
+    }
+
+    /// Returns whether an application matches the entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the application
+    /// * `name` - The display name of the application
+    /// * `path` - The path of the application
+    ///
+    /// # Returns
+    ///
+    /// `true` if the application meets all criteria that are set
+    pub fn matches(&self, app_id: &str, name: &str, path: &str) -> bool {
+        self.has_target()
+            && (self.app_id.is_empty() || self.app_id.eq_ignore_ascii_case(app_id))
+            && (self.name.is_empty() || matches_pattern(&self.name, name))
+            && (self.path.is_empty() || matches_pattern(&self.path, path))
+    }
 
-    /// Optional interval between application searches
-    application_search_interval_on_startup_minute: Option<u64>,
+    /// Returns whether two entries have the same criteria.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The entry to compare with
+    ///
+    /// # Returns
+    ///
+    /// `true` if both entries match the same applications
+    fn has_same_target(&self, other: &ApplicationNameAlias) -> bool {
+        self.app_id.eq_ignore_ascii_case(&other.app_id)
+            && self.name.to_lowercase() == other.name.to_lowercase()
+            && expand_path(&self.path).eq_ignore_ascii_case(&expand_path(&other.path))
+    }
 
-    /// Optional logging level
-    log_level: Option<String>,
+    /// Returns a description of the applications the entry matches.
+    ///
+    /// # Returns
+    ///
+    /// The criteria that are set, e.g. `name=Visual Studio Code*`
+    pub fn describe_target(&self) -> String {
+        [
+            ("app_id", &self.app_id),
+            ("name", &self.name),
+            ("path", &self.path),
+        ]
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
 
-    /// Optional window width
-    width: Option<u32>,
+/// Returns whether a text matches a pattern without regard to case.
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern, `*` matches any characters and `?` matches a single character
+/// * `text` - The text to match
+///
+/// # Returns
+///
+/// `true` if the whole text matches the pattern
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    // Position after the last `*` and the text position it was tried with, for backtracking.
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            star = Some((p, t));
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
 
-    /// Optional auto startup flag
-    auto_startup: Option<bool>,
+/// Display name and icon that replace those of a single application.
+///
+/// The override is kept in the settings, so it survives rescans of the applications.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationOverride {
+    /// The ID of the application, see `kasuri-cli list`
+    pub app_id: String,
 
-    /// Optional global shortcut key
-    shortcut_key: Option<String>,
+    /// Name shown instead of the name of the application, empty to keep the name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub display_name: String,
 
-    /// Optional list of application name aliases
-    application_name_aliases: Option<Vec<ApplicationNameAlias>>,
+    /// Path of a PNG file shown instead of the icon of the application, empty to keep the icon
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ApplicationNameAlias {
-    /// The path to the application executable
-    pub path: String,
+/// An entry of the search results that is defined in the settings instead of found on disk.
+///
+/// A command that is a URL, a folder or a file other than a program is opened with its
+/// default handler, any other command is started as a process, see `Application::launch`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomEntry {
+    /// Name shown in the search results, also identifies the entry
+    pub name: String,
+
+    /// Program, script, folder or URL to open
+    pub command: String,
+
+    /// Command line arguments passed to the program
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<String>,
+
+    /// Directory the program is started in, empty for the directory of KASURI
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub working_dir: String,
+
+    /// Environment variables set for the program, written as `NAME=value`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environment: Vec<String>,
+
+    /// Path of a PNG file shown as the icon of the entry
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub icon: String,
 
-    /// The alias for the application
-    pub alias: String,
+    /// Whether the program is started in a console window
+    #[serde(default)]
+    pub show_console: bool,
+}
+
+impl CustomEntry {
+    /// Prefix of the application IDs of custom entries
+    pub const APP_ID_PREFIX: &str = "custom:";
+
+    /// Returns the application ID of the entry.
+    ///
+    /// # Returns
+    ///
+    /// The name of the entry with the `custom:` prefix
+    pub fn get_app_id(&self) -> String {
+        format!("{}{}", Self::APP_ID_PREFIX, self.name.trim())
+    }
+
+    /// Returns the environment variables of the entry as names and values.
+    ///
+    /// # Returns
+    ///
+    /// The variables that are written as `NAME=value`, others are left out
+    pub fn get_environment(&self) -> Vec<(&str, &str)> {
+        self.environment
+            .iter()
+            .filter_map(|variable| variable.split_once('='))
+            .filter(|(name, _)| !name.trim().is_empty())
+            .map(|(name, value)| (name.trim(), value))
+            .collect()
+    }
+}
+
+/// A named set of settings that replace the general settings while the profile is active.
+///
+/// Settings that are not set in the profile are taken from the general settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsProfile {
+    /// Name of the profile, shown in the tray menu
+    pub name: String,
+
+    /// Whether the usage statistics are kept in a database of their own
+    #[serde(default)]
+    pub separate_usage: bool,
+
+    /// List of paths to search for applications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_search_path_list: Option<Vec<String>>,
+
+    /// Paths of applications that are hidden from the search results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_hide_list: Option<Vec<String>>,
+
+    /// List of application name aliases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_name_aliases: Option<Vec<ApplicationNameAlias>>,
+
+    /// Width of the main application window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Global shortcut key combination to show/hide the application
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut_key: Option<String>,
+}
+
+impl SettingsProfile {
+    /// Keys of the settings that a profile may leave out
+    const OPTIONAL_KEYS: [&str; 5] = [
+        "application_search_path_list",
+        "application_hide_list",
+        "application_name_aliases",
+        "width",
+        "shortcut_key",
+    ];
+}
+
+/// A problem with a single field of the settings.
+///
+/// Returned by `Settings::validate` and shown next to the field in the settings window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SettingsFieldError {
+    /// Name of the field as in the settings file, e.g. `shortcut_key`
+    pub field: String,
+
+    /// Index of the invalid entry for list fields
+    pub index: Option<usize>,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl SettingsFieldError {
+    /// Creates a new field error.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Name of the field as in the settings file
+    /// * `index` - Index of the invalid entry for list fields
+    /// * `message` - Description of the problem
+    pub fn new(field: &str, index: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            index,
+            message: message.into(),
+        }
+    }
+}
+
+/// Formats the error as `field: message` or `field[index]: message`
+impl std::fmt::Display for SettingsFieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}[{}]: {}", self.field, index, self.message),
+            None => write!(f, "{}: {}", self.field, self.message),
+        }
+    }
+}
+
+/// Settings that differ between two `Settings`.
+///
+/// Used to apply changed settings without restarting the application,
+/// each flag tells whether the corresponding setting has changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SettingsChanges {
+    pub application_search_path_list: bool,
+    pub application_search_interval_on_startup_minute: bool,
+    pub log_level: bool,
+    pub width: bool,
+    pub auto_startup: bool,
+    pub shortcut_key: bool,
+    pub automation_api_enabled: bool,
+    pub application_hide_list: bool,
+    pub application_name_aliases: bool,
+    pub application_overrides: bool,
+    pub custom_entries: bool,
+    /// The web search keywords or the fallback web search
+    pub web_search: bool,
+    /// The active profile or the list of profiles
+    pub profiles: bool,
+    /// The database that holds the usage statistics, see `Settings::get_usage_profile`
+    pub usage_profile: bool,
+}
+
+impl SettingsChanges {
+    /// Returns whether any setting has changed.
+    ///
+    /// # Returns
+    ///
+    /// `true` if at least one setting differs
+    pub fn is_any(&self) -> bool {
+        *self != Self::default()
+    }
 }
 
 impl Settings {
@@ -84,6 +419,38 @@ impl Settings {
     ///
     /// If the settings file does not exist, this method creates default settings
     /// and saves them to a new settings file before loading them.
+    /// Files in an older format are migrated and written back first,
+    /// see `settings_migration::migrate_file`.
+    /// Invalid fields are replaced with their default values and logged as warnings,
+    /// see `load_and_validate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the loaded `Settings` object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading or writing the settings file fails,
+    /// or if the file is not valid TOML.
+    pub fn load(path: &Path) -> KasuriResult<Self> {
+        Self::load_with_policy(path, &Policy::default())
+    }
+
+    /// Load settings from the settings file with a machine-wide policy layered over them.
+    ///
+    /// Works like `load`. The default values of the policy are used for the settings
+    /// missing in the file, and the enforced values of the policy replace the values
+    /// in the file. A new settings file does not contain the values set by the policy,
+    /// so that later changes to the policy take effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `policy` - The machine-wide policy
     ///
     /// # Returns
     ///
@@ -91,34 +458,418 @@ impl Settings {
     ///
     /// # Errors
     ///
-    /// Returns an error if reading or writing the settings file fails.
-    pub fn load() -> KasuriResult<Self> {
-        log::debug!("Loading settings from file: {}", SETTINGS_FILE_NAME);
+    /// Returns an error if reading or writing the settings file fails,
+    /// or if the file is not valid TOML.
+    pub fn load_with_policy(path: &Path, policy: &Policy) -> KasuriResult<Self> {
+        log::debug!("Loading settings from file: {}", path.display());
 
-        if !Self::is_existing_settings_file() {
+        if !Self::is_existing_settings_file(path) {
             log::info!("Settings file not found, creating default settings");
-            let settings = Self::default();
-            settings.save()?;
+            let mut values = toml::Table::try_from(Self::default())?;
+            values.extend(policy.get_defaults().clone());
+            policy.apply(&mut values);
+            let (settings, _) = Self::from_table(&values);
+            settings.save_with_policy(path, policy)?;
+        }
+
+        settings_migration::migrate_file(path)?;
+        let (settings, errors) = Self::load_and_validate_with_policy(path, policy)?;
+        errors
+            .iter()
+            .for_each(|e| log::warn!("Invalid setting, using the default value: {}", e));
+        Ok(settings)
+    }
+
+    /// Loads the settings file, recovering a corrupt file from the newest valid backup.
+    ///
+    /// Used at startup, so that a settings file truncated by a crash or a full disk
+    /// does not keep KASURI from starting. The corrupt file is kept next to the
+    /// settings file with the `.corrupt` extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `policy` - The machine-wide policy, see `load_with_policy`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the loaded or recovered `Settings` object.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of loading the settings file if it cannot be read,
+    /// or if it is corrupt and no backup can be loaded either.
+    pub fn load_or_recover(path: &Path, policy: &Policy) -> KasuriResult<Self> {
+        let error = match Self::load_with_policy(path, policy) {
+            Ok(settings) => return Ok(settings),
+            Err(KasuriError::Settings(message)) => message,
+            Err(e) => return Err(e),
+        };
+        log::error!(
+            "Settings file is corrupt, recovering from backup: {}",
+            error
+        );
+
+        let backups = settings_backup::list_backups(path)?;
+        for backup in backups {
+            let backup_path = settings_backup::get_backup_path(path, &backup.name)?;
+            if let Err(e) = Self::load_from_file(&backup_path, policy) {
+                log::warn!("Skipping corrupt settings backup {}: {}", backup.name, e);
+                continue;
+            }
+            let corrupt_file = settings_backup::set_aside_corrupt_file(path)?;
+            log::warn!("Corrupt settings file moved to: {}", corrupt_file.display());
+            let contents = std::fs::read_to_string(&backup_path)?;
+            settings_backup::write_atomically(path, &contents)?;
+            log::info!("Settings recovered from backup: {}", backup.name);
+            return Self::load_with_policy(path, policy);
+        }
+        Err(KasuriError::Settings(error))
+    }
+
+    /// Restores a backup of the settings file.
+    ///
+    /// The current settings file is backed up before it is replaced,
+    /// so that restoring can be undone by restoring that backup.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `name` - File name of the backup as returned by `settings_backup::list_backups`
+    /// * `policy` - The machine-wide policy, see `load_with_policy`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the restored `Settings` object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup does not exist or is corrupt,
+    /// or if the settings file cannot be written.
+    pub fn restore_backup(path: &Path, name: &str, policy: &Policy) -> KasuriResult<Self> {
+        log::info!("Restoring settings backup: {}", name);
+        let backup_path = settings_backup::get_backup_path(path, name)?;
+        Self::load_from_file(&backup_path, policy)?;
+        let contents = std::fs::read_to_string(&backup_path)?;
+        settings_backup::write_atomically(path, &contents)?;
+        Self::load_with_policy(path, policy)
+    }
+
+    /// Loads the settings file and reports the problems found in it.
+    ///
+    /// Fields that have the wrong type or fail `validate` are replaced with their
+    /// default values. Search paths that do not exist are only reported, because
+    /// they may be on a drive that is not connected at the moment.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    ///
+    /// # Returns
+    ///
+    /// The loaded settings and the problems found in the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file cannot be read or is not valid TOML.
+    pub fn load_and_validate(path: &Path) -> KasuriResult<(Self, Vec<SettingsFieldError>)> {
+        Self::load_and_validate_with_policy(path, &Policy::default())
+    }
+
+    /// Loads the settings file with a machine-wide policy layered over it
+    /// and reports the problems found in it.
+    ///
+    /// Works like `load_and_validate`, see `load_with_policy` for the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `policy` - The machine-wide policy
+    ///
+    /// # Returns
+    ///
+    /// The loaded settings and the problems found in the file or the policy
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file cannot be read or is not valid TOML.
+    pub fn load_and_validate_with_policy(
+        path: &Path,
+        policy: &Policy,
+    ) -> KasuriResult<(Self, Vec<SettingsFieldError>)> {
+        let (mut settings, mut errors) = Self::load_from_file(path, policy)?;
+        let validation_errors = settings.validate();
+        settings.fall_back_invalid_fields(&validation_errors);
+        errors.extend(validation_errors);
+        Ok((settings, errors))
+    }
+
+    /// Checks the settings for problems.
+    ///
+    /// The following problems are reported:
+    /// - a search path that is empty or does not exist
+    /// - an unknown log level
+    /// - a window width outside of `WIDTH_RANGE`
+    /// - a shortcut key that cannot be parsed
+    /// - a hidden application path that is empty
+    /// - an alias entry without an application ID, name or path,
+    ///   or with an alias that is empty or used more than once
+    /// - a profile name that is empty or used more than once,
+    ///   or a width or shortcut key of a profile as above
+    /// - an active profile that does not exist
+    ///
+    /// # Returns
+    ///
+    /// The problems found, empty if the settings are valid
+    pub fn validate(&self) -> Vec<SettingsFieldError> {
+        let mut errors = vec![];
+
+        for (i, path) in self.application_search_path_list.iter().enumerate() {
+            if path == SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP {
+                continue;
+            }
+            if path.trim().is_empty() {
+                errors.push(SettingsFieldError::new(
+                    "application_search_path_list",
+                    Some(i),
+                    "Search path is empty",
+                ));
+            } else {
+                let expanded = expand_path(path);
+                if !Path::new(&expanded).is_dir() {
+                    errors.push(SettingsFieldError::new(
+                        "application_search_path_list",
+                        Some(i),
+                        format!("Search path does not exist: {}", expanded),
+                    ));
+                }
+            }
+        }
+
+        if !LOG_LEVELS.contains(&self.log_level.to_lowercase().as_str()) {
+            errors.push(SettingsFieldError::new(
+                "log_level",
+                None,
+                format!(
+                    "Unknown log level '{}', expected one of: {}",
+                    self.log_level,
+                    LOG_LEVELS.join(", ")
+                ),
+            ));
+        }
+
+        if let Some(message) = Self::validate_width(self.width) {
+            errors.push(SettingsFieldError::new("width", None, message));
+        }
+
+        if let Some(message) = Self::validate_shortcut_key(&self.shortcut_key) {
+            errors.push(SettingsFieldError::new("shortcut_key", None, message));
         }
 
-        Self::load_from_file()
+        for (i, path) in self.application_hide_list.iter().enumerate() {
+            if path.trim().is_empty() {
+                errors.push(SettingsFieldError::new(
+                    "application_hide_list",
+                    Some(i),
+                    "Hidden application path is empty",
+                ));
+            }
+        }
+
+        let mut used_aliases: Vec<&str> = vec![];
+        for (i, entry) in self.application_name_aliases.iter().enumerate() {
+            let duplicate = entry.aliases.iter().enumerate().find(|(j, alias)| {
+                used_aliases
+                    .iter()
+                    .copied()
+                    .chain(entry.aliases[..*j].iter().map(String::as_str))
+                    .any(|v| v.trim().eq_ignore_ascii_case(alias.trim()))
+            });
+            let message = if !entry.has_target() {
+                "Alias has no application ID, name or path".to_string()
+            } else if entry.aliases.is_empty()
+                || entry.aliases.iter().any(|alias| alias.trim().is_empty())
+            {
+                "Alias is empty".to_string()
+            } else if let Some((_, alias)) = duplicate {
+                format!("Duplicate alias: {}", alias)
+            } else {
+                used_aliases.extend(entry.aliases.iter().map(String::as_str));
+                continue;
+            };
+            errors.push(SettingsFieldError::new(
+                "application_name_aliases",
+                Some(i),
+                message,
+            ));
+        }
+
+        for (i, entry) in self.application_overrides.iter().enumerate() {
+            let previous = &self.application_overrides[..i];
+            let message = if entry.app_id.trim().is_empty() {
+                "Application ID is empty".to_string()
+            } else if previous
+                .iter()
+                .any(|v| v.app_id.trim().eq_ignore_ascii_case(entry.app_id.trim()))
+            {
+                format!("Duplicate override: {}", entry.app_id)
+            } else if entry.display_name.trim().is_empty() && entry.icon.trim().is_empty() {
+                "Override has no display name or icon".to_string()
+            } else if !entry.icon.is_empty() && !entry.icon.to_lowercase().ends_with(".png") {
+                "Icon is not a PNG file".to_string()
+            } else {
+                continue;
+            };
+            errors.push(SettingsFieldError::new(
+                "application_overrides",
+                Some(i),
+                message,
+            ));
+        }
+
+        for (i, entry) in self.custom_entries.iter().enumerate() {
+            let previous = &self.custom_entries[..i];
+            let message = if entry.name.trim().is_empty() {
+                "Entry name is empty".to_string()
+            } else if previous
+                .iter()
+                .any(|v| v.name.trim().eq_ignore_ascii_case(entry.name.trim()))
+            {
+                format!("Duplicate entry: {}", entry.name)
+            } else if entry.command.trim().is_empty() {
+                "Command is empty".to_string()
+            } else if let Some(variable) = entry.environment.iter().find(|v| {
+                v.split_once('=')
+                    .is_none_or(|(name, _)| name.trim().is_empty())
+            }) {
+                format!("Environment variable is not NAME=value: {}", variable)
+            } else if !entry.icon.is_empty() && !entry.icon.to_lowercase().ends_with(".png") {
+                "Icon is not a PNG file".to_string()
+            } else {
+                continue;
+            };
+            errors.push(SettingsFieldError::new("custom_entries", Some(i), message));
+        }
+
+        for (i, entry) in self.web_search_keywords.iter().enumerate() {
+            let previous = &self.web_search_keywords[..i];
+            let message = if entry.keyword.trim().is_empty()
+                || entry.keyword.trim().contains(char::is_whitespace)
+            {
+                "Keyword is empty or contains spaces".to_string()
+            } else if previous
+                .iter()
+                .any(|v| v.keyword.trim().eq_ignore_ascii_case(entry.keyword.trim()))
+            {
+                format!("Duplicate keyword: {}", entry.keyword)
+            } else if !entry.url_template.contains(QUERY_PLACEHOLDER) {
+                format!("URL template has no {}", QUERY_PLACEHOLDER)
+            } else {
+                continue;
+            };
+            errors.push(SettingsFieldError::new(
+                "web_search_keywords",
+                Some(i),
+                message,
+            ));
+        }
+
+        if !self.web_search_fallback.is_empty() && self.get_web_search_fallback().is_none() {
+            errors.push(SettingsFieldError::new(
+                "web_search_fallback",
+                None,
+                format!("Unknown keyword: {}", self.web_search_fallback),
+            ));
+        }
+
+        for (i, profile) in self.profiles.iter().enumerate() {
+            let previous = &self.profiles[..i];
+            let message = if profile.name.trim().is_empty() {
+                "Profile name is empty".to_string()
+            } else if previous
+                .iter()
+                .any(|v| v.name.trim().eq_ignore_ascii_case(profile.name.trim()))
+            {
+                format!("Duplicate profile: {}", profile.name)
+            } else if let Some(message) = profile.width.and_then(Self::validate_width) {
+                message
+            } else if let Some(message) = profile
+                .shortcut_key
+                .as_deref()
+                .and_then(Self::validate_shortcut_key)
+            {
+                message
+            } else {
+                continue;
+            };
+            errors.push(SettingsFieldError::new("profiles", Some(i), message));
+        }
+
+        if !self.active_profile.is_empty()
+            && !self.profiles.iter().any(|v| v.name == self.active_profile)
+        {
+            errors.push(SettingsFieldError::new(
+                "active_profile",
+                None,
+                format!("Unknown profile: {}", self.active_profile),
+            ));
+        }
+
+        errors
+    }
+
+    /// Checks the width of the main window.
+    ///
+    /// # Returns
+    ///
+    /// The description of the problem, or `None` if the width is valid
+    fn validate_width(width: u32) -> Option<String> {
+        (!WIDTH_RANGE.contains(&width)).then(|| {
+            format!(
+                "Width must be between {} and {}",
+                WIDTH_RANGE.start(),
+                WIDTH_RANGE.end()
+            )
+        })
+    }
+
+    /// Checks the global shortcut key.
+    ///
+    /// # Returns
+    ///
+    /// The description of the problem, or `None` if the shortcut key can be parsed
+    fn validate_shortcut_key(shortcut_key: &str) -> Option<String> {
+        HotKey::from_str(shortcut_key)
+            .err()
+            .map(|e| format!("Invalid shortcut key '{}': {}", shortcut_key, e))
+    }
+
+    /// Returns the format version of the settings file.
+    ///
+    /// # Returns
+    ///
+    /// The format version, see `settings_migration::SETTINGS_VERSION`
+    pub fn get_settings_version(&self) -> u32 {
+        self.settings_version
     }
 
-    /// Returns a clone of the application search path list.
+    /// Returns the application search path list with environment variables expanded.
     ///
     /// This method provides access to the list of directories to be searched
     /// for applications. The list may include special paths like "WindowsStoreApp"
-    /// for Windows Store applications.
+    /// for Windows Store applications. The settings keep the paths as written,
+    /// see `path_expansion::expand_path`. The paths of the active profile
+    /// replace the general paths if the profile sets them.
     ///
     /// # Returns
     ///
-    /// A vector of directory paths as strings.
-    pub fn get_application_search_path_list(&self) -> &Vec<String> {
-        log::debug!(
-            "Retrieving application search paths: {:?}",
-            self.application_search_path_list
-        );
-        &self.application_search_path_list
+    /// A vector of expanded directory paths as strings.
+    pub fn get_application_search_path_list(&self) -> Vec<String> {
+        let paths = self.profile_value(&self.application_search_path_list, |profile| {
+            profile.application_search_path_list.as_ref()
+        });
+        log::debug!("Retrieving application search paths: {:?}", paths);
+        paths.iter().map(|path| expand_path(path)).collect()
     }
 
     /// Returns the application search interval on startup in minutes.
@@ -152,12 +903,15 @@ impl Settings {
 
     /// Returns the width of the main application window.
     ///
+    /// The width of the active profile is used if the profile sets it.
+    ///
     /// # Returns
     ///
     /// The window width in logical pixels.
     pub fn get_width(&self) -> u32 {
-        log::debug!("Retrieving window width: {}", self.width);
-        self.width
+        let width = *self.profile_value(&self.width, |profile| profile.width.as_ref());
+        log::debug!("Retrieving window width: {}", width);
+        width
     }
 
     /// Returns the auto startup setting.
@@ -176,29 +930,545 @@ impl Settings {
     /// Returns the global shortcut key.
     ///
     /// This shortcut key is used to show or hide the application
-    /// from anywhere in the system.
+    /// from anywhere in the system. The shortcut key of the active profile
+    /// is used if the profile sets it.
     ///
     /// # Returns
     ///
     /// The shortcut key combination as a string.
     pub fn get_shortcut_key(&self) -> &String {
-        log::debug!("Retrieving shortcut key: {}", self.shortcut_key);
-        &self.shortcut_key
+        let shortcut_key =
+            self.profile_value(&self.shortcut_key, |profile| profile.shortcut_key.as_ref());
+        log::debug!("Retrieving shortcut key: {}", shortcut_key);
+        shortcut_key
+    }
+
+    /// Returns whether the local automation API is enabled.
+    ///
+    /// When true, other programs of the same user can search, launch and pin
+    /// applications through a named pipe (Windows) or a Unix domain socket.
+    ///
+    /// # Returns
+    ///
+    /// A boolean value indicating whether the automation API is enabled.
+    pub fn get_automation_api_enabled(&self) -> bool {
+        log::debug!(
+            "Retrieving automation API setting: {}",
+            self.automation_api_enabled
+        );
+        self.automation_api_enabled
+    }
+
+    /// Returns the paths of the hidden applications with environment variables expanded.
+    ///
+    /// The paths of the active profile replace the general paths if the profile sets them.
+    ///
+    /// # Returns
+    ///
+    /// A vector of expanded application paths.
+    pub fn get_application_hide_list(&self) -> Vec<String> {
+        let hide_list = self.profile_value(&self.application_hide_list, |profile| {
+            profile.application_hide_list.as_ref()
+        });
+        log::debug!("Retrieving application hide list: {:?}", hide_list);
+        hide_list.iter().map(|path| expand_path(path)).collect()
     }
 
     /// Returns the list of application name aliases.
     ///
     /// This method provides access to the list of aliases for application names,
     /// which can be used to refer to applications by alternative names.
+    /// Environment variables in the application IDs and paths are expanded.
+    /// The aliases of the active profile replace the general aliases if the profile sets them.
     /// # Returns
     ///
-    /// A vector of `ApplicationNameAlias` objects.
-    pub fn get_application_name_aliases(&self) -> &Vec<ApplicationNameAlias> {
+    /// A vector of `ApplicationNameAlias` objects with expanded paths.
+    pub fn get_application_name_aliases(&self) -> Vec<ApplicationNameAlias> {
+        let aliases = self.profile_value(&self.application_name_aliases, |profile| {
+            profile.application_name_aliases.as_ref()
+        });
+        log::debug!("Retrieving application name aliases: {:?}", aliases);
+        aliases
+            .iter()
+            .map(|alias| ApplicationNameAlias {
+                app_id: expand_path(&alias.app_id),
+                path: expand_path(&alias.path),
+                ..alias.clone()
+            })
+            .collect()
+    }
+
+    /// Returns the display name and icon overrides of the applications.
+    ///
+    /// Environment variables in the application IDs and icon paths are expanded.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `ApplicationOverride` objects with expanded paths.
+    pub fn get_application_overrides(&self) -> Vec<ApplicationOverride> {
+        log::debug!(
+            "Retrieving application overrides: {:?}",
+            self.application_overrides
+        );
+        self.application_overrides
+            .iter()
+            .map(|entry| ApplicationOverride {
+                app_id: expand_path(&entry.app_id),
+                icon: expand_path(&entry.icon),
+                ..entry.clone()
+            })
+            .collect()
+    }
+
+    /// Returns the custom entries of the search results.
+    ///
+    /// Environment variables in the commands, working directories and icon paths are expanded.
+    /// The arguments are passed to the program as written.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `CustomEntry` objects with expanded paths.
+    pub fn get_custom_entries(&self) -> Vec<CustomEntry> {
+        log::debug!("Retrieving custom entries: {:?}", self.custom_entries);
+        self.custom_entries
+            .iter()
+            .map(|entry| CustomEntry {
+                command: expand_path(&entry.command),
+                working_dir: expand_path(&entry.working_dir),
+                icon: expand_path(&entry.icon),
+                ..entry.clone()
+            })
+            .collect()
+    }
+
+    /// Returns the web search keywords.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `WebSearchKeyword` objects
+    pub fn get_web_search_keywords(&self) -> Vec<WebSearchKeyword> {
         log::debug!(
-            "Retrieving application name aliases: {:?}",
-            self.application_name_aliases
+            "Retrieving web search keywords: {:?}",
+            self.web_search_keywords
         );
-        &self.application_name_aliases
+        self.web_search_keywords.clone()
+    }
+
+    /// Returns the web search offered for every query.
+    ///
+    /// # Returns
+    ///
+    /// The keyword named by `web_search_fallback`, or `None` if it is empty or unknown
+    pub fn get_web_search_fallback(&self) -> Option<WebSearchKeyword> {
+        let fallback = self.web_search_fallback.trim();
+        if fallback.is_empty() {
+            return None;
+        }
+        self.web_search_keywords
+            .iter()
+            .find(|v| v.keyword.trim().eq_ignore_ascii_case(fallback))
+            .cloned()
+    }
+
+    /// Returns the active profile.
+    ///
+    /// # Returns
+    ///
+    /// The active profile, or `None` if no profile is active or the profile does not exist
+    pub fn get_active_profile(&self) -> Option<&SettingsProfile> {
+        if self.active_profile.is_empty() {
+            return None;
+        }
+        self.profiles.iter().find(|v| v.name == self.active_profile)
+    }
+
+    /// Returns the names of the profiles.
+    ///
+    /// # Returns
+    ///
+    /// The names of the profiles in the order of the settings file
+    pub fn get_profile_names(&self) -> Vec<String> {
+        self.profiles.iter().map(|v| v.name.clone()).collect()
+    }
+
+    /// Returns the profile whose usage statistics are kept separately.
+    ///
+    /// # Returns
+    ///
+    /// The name of the active profile if it keeps separate usage statistics,
+    /// or `None` if the shared usage statistics are used
+    pub fn get_usage_profile(&self) -> Option<&str> {
+        self.get_active_profile()
+            .filter(|profile| profile.separate_usage)
+            .map(|profile| profile.name.as_str())
+    }
+
+    /// Activates a profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the profile, or an empty string to use the general settings
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if there is no profile with the name
+    pub fn set_active_profile(&mut self, name: &str) -> KasuriResult<()> {
+        if !name.is_empty() && !self.profiles.iter().any(|v| v.name == name) {
+            return Err(KasuriError::Settings(format!("Unknown profile: {}", name)));
+        }
+        log::info!("Activating profile: '{}'", name);
+        self.active_profile = name.to_string();
+        Ok(())
+    }
+
+    /// Adds an alias for the application with the given ID.
+    ///
+    /// The alias is added to the aliases of the active profile if the profile sets them,
+    /// otherwise to the general aliases. An entry for the application ID is created
+    /// if there is none yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the application as written to the settings file
+    /// * `alias` - The alias to add
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::InvalidSettings` if the alias is empty
+    /// or already used for any application
+    pub fn add_alias(&mut self, app_id: &str, alias: &str) -> KasuriResult<()> {
+        let alias = alias.trim();
+        let error = if alias.is_empty() {
+            Some("Alias is empty".to_string())
+        } else if self
+            .get_application_name_aliases()
+            .iter()
+            .flat_map(|v| &v.aliases)
+            .any(|v| v.trim().eq_ignore_ascii_case(alias))
+        {
+            Some(format!("Duplicate alias: {}", alias))
+        } else {
+            None
+        };
+        if let Some(message) = error {
+            return Err(KasuriError::InvalidSettings(vec![SettingsFieldError::new(
+                "application_name_aliases",
+                None,
+                message,
+            )]));
+        }
+
+        let active_profile = self.active_profile.clone();
+        let aliases = match self
+            .profiles
+            .iter_mut()
+            .find(|v| !active_profile.is_empty() && v.name == active_profile)
+            .and_then(|profile| profile.application_name_aliases.as_mut())
+        {
+            Some(aliases) => aliases,
+            None => &mut self.application_name_aliases,
+        };
+        let entry = ApplicationNameAlias::for_app_id(app_id, vec![alias.to_string()]);
+        log::info!("Adding alias '{}' for application: {}", alias, app_id);
+        match aliases.iter_mut().find(|v| v.has_same_target(&entry)) {
+            Some(existing) => existing.aliases.push(alias.to_string()),
+            None => aliases.push(entry),
+        }
+        Ok(())
+    }
+
+    /// Returns the value of the active profile, or the general value
+    /// if no profile is active or the profile does not set the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The general value
+    /// * `profile_value` - Returns the value of a profile, `None` if it is not set
+    fn profile_value<'a, T>(
+        &'a self,
+        value: &'a T,
+        profile_value: impl Fn(&'a SettingsProfile) -> Option<&'a T>,
+    ) -> &'a T {
+        self.get_active_profile()
+            .and_then(profile_value)
+            .unwrap_or(value)
+    }
+
+    /// Compares these settings with newer settings.
+    ///
+    /// The settings replaced by the active profile are compared
+    /// as they are in effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The newer settings
+    ///
+    /// # Returns
+    ///
+    /// A `SettingsChanges` telling which settings differ
+    pub fn diff(&self, other: &Settings) -> SettingsChanges {
+        SettingsChanges {
+            application_search_path_list: self.get_application_search_path_list()
+                != other.get_application_search_path_list(),
+            application_search_interval_on_startup_minute: self
+                .application_search_interval_on_startup_minute
+                != other.application_search_interval_on_startup_minute,
+            log_level: self.log_level != other.log_level,
+            width: self.get_width() != other.get_width(),
+            auto_startup: self.auto_startup != other.auto_startup,
+            shortcut_key: self.get_shortcut_key() != other.get_shortcut_key(),
+            automation_api_enabled: self.automation_api_enabled != other.automation_api_enabled,
+            application_hide_list: self.get_application_hide_list()
+                != other.get_application_hide_list(),
+            application_name_aliases: self.get_application_name_aliases()
+                != other.get_application_name_aliases(),
+            application_overrides: self.get_application_overrides()
+                != other.get_application_overrides(),
+            custom_entries: self.get_custom_entries() != other.get_custom_entries(),
+            web_search: self.web_search_keywords != other.web_search_keywords
+                || self.web_search_fallback != other.web_search_fallback,
+            profiles: self.active_profile != other.active_profile
+                || self.get_profile_names() != other.get_profile_names(),
+            usage_profile: self.get_usage_profile() != other.get_usage_profile(),
+        }
+    }
+
+    /// Creates settings from the values of a settings file of any version.
+    ///
+    /// Used for settings that do not come from the settings file, such as the
+    /// settings in an export archive. The values are migrated to `SETTINGS_VERSION`
+    /// and checked like the values of the settings file, see `load_and_validate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values as in a settings file
+    ///
+    /// # Returns
+    ///
+    /// The settings and the problems found in the values
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if the values cannot be migrated
+    pub fn from_values(values: &toml::Table) -> KasuriResult<(Self, Vec<SettingsFieldError>)> {
+        let mut document: toml_edit::DocumentMut = toml::to_string(values)?
+            .parse()
+            .map_err(|e: toml_edit::TomlError| KasuriError::Settings(e.to_string()))?;
+        settings_migration::migrate(&mut document)?;
+        let table: toml::Table = toml::from_str(&document.to_string())?;
+        let (mut settings, mut errors) = Self::from_table(&table);
+        let validation_errors = settings.validate();
+        settings.fall_back_invalid_fields(&validation_errors);
+        errors.extend(validation_errors);
+        Ok((settings, errors))
+    }
+
+    /// Rewrites every path in the settings, including the paths in the profiles.
+    ///
+    /// The paths are passed as written, without expanding them.
+    /// `WindowsStoreApp` in the search paths is not a path and is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - Returns the new form of a path
+    pub fn map_paths(&mut self, map: impl Fn(&str) -> String) {
+        let map_search_paths = |paths: &mut Vec<String>| {
+            paths
+                .iter_mut()
+                .filter(|path| {
+                    *path != SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP
+                })
+                .for_each(|path| *path = map(path));
+        };
+        let map_paths =
+            |paths: &mut Vec<String>| paths.iter_mut().for_each(|path| *path = map(path));
+        let map_aliases = |aliases: &mut Vec<ApplicationNameAlias>| {
+            aliases.iter_mut().for_each(|alias| {
+                alias.app_id = map(&alias.app_id);
+                alias.path = map(&alias.path);
+            })
+        };
+
+        map_search_paths(&mut self.application_search_path_list);
+        map_paths(&mut self.application_hide_list);
+        map_aliases(&mut self.application_name_aliases);
+        self.application_overrides.iter_mut().for_each(|entry| {
+            entry.app_id = map(&entry.app_id);
+            entry.icon = map(&entry.icon);
+        });
+        self.custom_entries.iter_mut().for_each(|entry| {
+            entry.command = map(&entry.command);
+            entry.working_dir = map(&entry.working_dir);
+            entry.icon = map(&entry.icon);
+        });
+        for profile in &mut self.profiles {
+            if let Some(paths) = profile.application_search_path_list.as_mut() {
+                map_search_paths(paths);
+            }
+            if let Some(paths) = profile.application_hide_list.as_mut() {
+                map_paths(paths);
+            }
+            if let Some(aliases) = profile.application_name_aliases.as_mut() {
+                map_aliases(aliases);
+            }
+        }
+    }
+
+    /// Adds the entries of other settings that these settings do not have yet.
+    ///
+    /// Search paths, hidden applications, aliases that are not used yet,
+    /// overrides of other applications, custom entries, web search keywords
+    /// and profiles with a new name are added. Search paths that do not
+    /// exist on this machine are skipped with a warning. All other settings are kept,
+    /// so that merging settings from another machine does not change the appearance
+    /// or the shortcut key that were chosen on this machine.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The settings to merge, e.g. from an export archive
+    /// * `is_locked` - Returns whether a setting must be left unchanged, see `Policy::is_enforced`
+    ///
+    /// # Returns
+    ///
+    /// A description of each added entry, empty if nothing was added
+    pub fn merge(&mut self, other: &Settings, is_locked: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut added = vec![];
+        let same_path = |a: &str, b: &str| expand_path(a).eq_ignore_ascii_case(&expand_path(b));
+
+        if !is_locked("application_search_path_list") {
+            for path in &other.application_search_path_list {
+                if self
+                    .application_search_path_list
+                    .iter()
+                    .any(|v| same_path(v, path))
+                {
+                    continue;
+                }
+                if path != SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP
+                    && !Path::new(&expand_path(path)).is_dir()
+                {
+                    log::warn!("Skipping search path that does not exist: {}", path);
+                } else {
+                    added.push(format!("Search path: {}", path));
+                    self.application_search_path_list.push(path.clone());
+                }
+            }
+        }
+        if !is_locked("application_hide_list") {
+            for path in &other.application_hide_list {
+                if !self
+                    .application_hide_list
+                    .iter()
+                    .any(|v| same_path(v, path))
+                {
+                    added.push(format!("Hidden application: {}", path));
+                    self.application_hide_list.push(path.clone());
+                }
+            }
+        }
+        if !is_locked("application_name_aliases") {
+            added.extend(self.merge_aliases(&other.application_name_aliases));
+        }
+        if !is_locked("application_overrides") {
+            for entry in &other.application_overrides {
+                if !self
+                    .application_overrides
+                    .iter()
+                    .any(|v| v.app_id.eq_ignore_ascii_case(&entry.app_id))
+                {
+                    added.push(format!("Override: {}", entry.app_id));
+                    self.application_overrides.push(entry.clone());
+                }
+            }
+        }
+        if !is_locked("custom_entries") {
+            for entry in &other.custom_entries {
+                if !self
+                    .custom_entries
+                    .iter()
+                    .any(|v| v.name.trim().eq_ignore_ascii_case(entry.name.trim()))
+                {
+                    added.push(format!("Custom entry: {}", entry.name));
+                    self.custom_entries.push(entry.clone());
+                }
+            }
+        }
+        if !is_locked("web_search_keywords") {
+            for entry in &other.web_search_keywords {
+                if !self
+                    .web_search_keywords
+                    .iter()
+                    .any(|v| v.keyword.trim().eq_ignore_ascii_case(entry.keyword.trim()))
+                {
+                    added.push(format!("Web search keyword: {}", entry.keyword));
+                    self.web_search_keywords.push(entry.clone());
+                }
+            }
+        }
+        if !is_locked("profiles") {
+            for profile in &other.profiles {
+                if !self
+                    .profiles
+                    .iter()
+                    .any(|v| v.name.trim().eq_ignore_ascii_case(profile.name.trim()))
+                {
+                    added.push(format!("Profile: {}", profile.name));
+                    self.profiles.push(profile.clone());
+                }
+            }
+        }
+        log::debug!("Merged settings, added: {:?}", added);
+        added
+    }
+
+    /// Adds the aliases that are not used yet.
+    ///
+    /// An alias is skipped if it is already used for any application. The other aliases
+    /// are added to the entry with the same criteria, or to a new entry if there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `aliases` - The aliases to add
+    ///
+    /// # Returns
+    ///
+    /// A description of each added alias, empty if nothing was added
+    pub fn merge_aliases(&mut self, aliases: &[ApplicationNameAlias]) -> Vec<String> {
+        let mut added = vec![];
+        for entry in aliases {
+            for alias in &entry.aliases {
+                let is_taken = self
+                    .application_name_aliases
+                    .iter()
+                    .flat_map(|v| &v.aliases)
+                    .any(|v| v.trim().eq_ignore_ascii_case(alias.trim()));
+                if is_taken {
+                    log::debug!("Skipping alias that is already used: {}", alias);
+                    continue;
+                }
+                added.push(format!("Alias: {} = {}", alias, entry.describe_target()));
+                match self
+                    .application_name_aliases
+                    .iter_mut()
+                    .find(|v| v.has_same_target(entry))
+                {
+                    Some(existing) => existing.aliases.push(alias.clone()),
+                    None => self.application_name_aliases.push(ApplicationNameAlias {
+                        aliases: vec![alias.clone()],
+                        ..entry.clone()
+                    }),
+                }
+            }
+        }
+        added
     }
 
     /// Checks if the settings file exists in the expected location.
@@ -206,11 +1476,14 @@ impl Settings {
     /// This is a helper method used to determine whether default settings
     /// need to be created during application initialization.
     ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    ///
     /// # Returns
     ///
     /// `true` if the settings file exists, `false` otherwise.
-    fn is_existing_settings_file() -> bool {
-        let path = Self::get_settings_file_path();
+    fn is_existing_settings_file(path: &Path) -> bool {
         let exists = path.exists();
         log::debug!(
             "Checking if settings file exists at {}: {}",
@@ -224,11 +1497,17 @@ impl Settings {
     ///
     /// This method reads the settings file, parses its contents as TOML,
     /// and constructs a Settings object. It handles partial settings by
-    /// filling in missing values with defaults.
+    /// filling in missing values with defaults. Values of the wrong type
+    /// are replaced with defaults as well and reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `policy` - The machine-wide policy layered over the file
     ///
     /// # Returns
     ///
-    /// A `Result` containing the loaded `Settings` object.
+    /// A `Result` containing the loaded `Settings` object and the fields that had the wrong type.
     ///
     /// # Errors
     ///
@@ -237,8 +1516,10 @@ impl Settings {
     /// - The file contents cannot be read
     /// - The file is empty
     /// - The TOML parsing fails
-    fn load_from_file() -> KasuriResult<Self> {
-        let path = Self::get_settings_file_path();
+    fn load_from_file(
+        path: &Path,
+        policy: &Policy,
+    ) -> KasuriResult<(Self, Vec<SettingsFieldError>)> {
         log::debug!("Opening settings file: {:?}", path);
         let mut file = File::open(path)?;
 
@@ -248,49 +1529,242 @@ impl Settings {
 
         if size == 0 {
             log::warn!("Settings file is empty");
-            return Err("Settings file is empty".into());
+            return Err(KasuriError::Settings("Settings file is empty".to_string()));
         }
 
         log::debug!("Parsing settings from TOML");
-        let partial_settings: PartialSettings = toml::from_str(&buf)?;
+        let mut table: toml::Table = toml::from_str(&buf)?;
+        // Files that have not been migrated, e.g. backups, are migrated in memory.
+        let mut document: toml_edit::DocumentMut = buf
+            .parse()
+            .map_err(|e: toml_edit::TomlError| KasuriError::Settings(e.to_string()))?;
+        if settings_migration::migrate(&mut document)? {
+            table = toml::from_str(&document.to_string())?;
+        }
+        policy.apply(&mut table);
 
+        let (settings, errors) = Self::from_table(&table);
+        log::debug!("Settings loaded successfully: {:?}", settings);
+        Ok((settings, errors))
+    }
+
+    /// Creates settings from the values of a parsed settings file.
+    ///
+    /// Missing values and values of the wrong type are replaced with defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The parsed settings file
+    ///
+    /// # Returns
+    ///
+    /// The settings and the fields that had the wrong type
+    fn from_table(table: &toml::Table) -> (Self, Vec<SettingsFieldError>) {
         log::debug!("Creating default settings to fill in any missing values");
         let default_settings = Self::default();
 
         log::debug!("Merging parsed settings with default values");
+        let mut errors = vec![];
         let settings = Settings {
-            application_search_path_list: partial_settings
-                .application_search_path_list
-                .unwrap_or_else(|| default_settings.application_search_path_list),
-            application_search_interval_on_startup_minute: partial_settings
-                .application_search_interval_on_startup_minute
-                .unwrap_or_else(|| default_settings.application_search_interval_on_startup_minute),
-            log_level: partial_settings
-                .log_level
-                .unwrap_or_else(|| default_settings.log_level),
-            width: partial_settings
-                .width
-                .unwrap_or_else(|| default_settings.width),
-            auto_startup: partial_settings
-                .auto_startup
-                .unwrap_or_else(|| default_settings.auto_startup),
-            shortcut_key: partial_settings
-                .shortcut_key
-                .unwrap_or_else(|| default_settings.shortcut_key),
-            application_name_aliases: partial_settings
-                .application_name_aliases
-                .unwrap_or_else(|| default_settings.application_name_aliases),
+            // A file without the version was written before the format was versioned.
+            settings_version: Self::read_field(table, SETTINGS_VERSION_KEY, 0, &mut errors),
+            application_search_path_list: Self::read_field(
+                table,
+                "application_search_path_list",
+                default_settings.application_search_path_list,
+                &mut errors,
+            ),
+            application_search_interval_on_startup_minute: Self::read_field(
+                table,
+                "application_search_interval_on_startup_minute",
+                default_settings.application_search_interval_on_startup_minute,
+                &mut errors,
+            ),
+            log_level: Self::read_field(
+                table,
+                "log_level",
+                default_settings.log_level,
+                &mut errors,
+            ),
+            width: Self::read_field(table, "width", default_settings.width, &mut errors),
+            auto_startup: Self::read_field(
+                table,
+                "auto_startup",
+                default_settings.auto_startup,
+                &mut errors,
+            ),
+            shortcut_key: Self::read_field(
+                table,
+                "shortcut_key",
+                default_settings.shortcut_key,
+                &mut errors,
+            ),
+            automation_api_enabled: Self::read_field(
+                table,
+                "automation_api_enabled",
+                default_settings.automation_api_enabled,
+                &mut errors,
+            ),
+            application_hide_list: Self::read_field(
+                table,
+                "application_hide_list",
+                default_settings.application_hide_list,
+                &mut errors,
+            ),
+            active_profile: Self::read_field(
+                table,
+                "active_profile",
+                default_settings.active_profile,
+                &mut errors,
+            ),
+            application_name_aliases: Self::read_field(
+                table,
+                "application_name_aliases",
+                default_settings.application_name_aliases,
+                &mut errors,
+            ),
+            application_overrides: Self::read_field(
+                table,
+                "application_overrides",
+                default_settings.application_overrides,
+                &mut errors,
+            ),
+            custom_entries: Self::read_field(
+                table,
+                "custom_entries",
+                default_settings.custom_entries,
+                &mut errors,
+            ),
+            web_search_keywords: Self::read_field(
+                table,
+                "web_search_keywords",
+                default_settings.web_search_keywords,
+                &mut errors,
+            ),
+            web_search_fallback: Self::read_field(
+                table,
+                "web_search_fallback",
+                default_settings.web_search_fallback,
+                &mut errors,
+            ),
+            profiles: Self::read_field(table, "profiles", default_settings.profiles, &mut errors),
         };
+        (settings, errors)
+    }
 
-        log::debug!("Settings loaded successfully: {:?}", settings);
-        Ok(settings)
+    /// Reads a single field from the parsed settings file.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The parsed settings file
+    /// * `field` - Name of the field
+    /// * `default` - Value used if the field is missing or has the wrong type
+    /// * `errors` - Collects the error if the field has the wrong type
+    ///
+    /// # Returns
+    ///
+    /// The value of the field, or the default value
+    fn read_field<T: DeserializeOwned>(
+        table: &toml::Table,
+        field: &str,
+        default: T,
+        errors: &mut Vec<SettingsFieldError>,
+    ) -> T {
+        let Some(value) = table.get(field) else {
+            return default;
+        };
+        match value.clone().try_into() {
+            Ok(value) => value,
+            Err(e) => {
+                errors.push(SettingsFieldError::new(
+                    field,
+                    None,
+                    format!("Invalid value: {}", e.message()),
+                ));
+                default
+            }
+        }
+    }
+
+    /// Replaces the invalid fields with their default values.
+    ///
+    /// Invalid aliases, overrides, custom entries, hidden application paths and profiles
+    /// are removed, the other entries are kept.
+    /// Search paths are kept, see `load_and_validate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `errors` - The problems found by `validate`
+    fn fall_back_invalid_fields(&mut self, errors: &[SettingsFieldError]) {
+        if errors.is_empty() {
+            return;
+        }
+        let default_settings = Self::default();
+        for error in errors {
+            match error.field.as_str() {
+                "log_level" => self.log_level = default_settings.log_level.clone(),
+                "width" => self.width = default_settings.width,
+                "shortcut_key" => self.shortcut_key = default_settings.shortcut_key.clone(),
+                "active_profile" => self.active_profile = default_settings.active_profile.clone(),
+                "web_search_fallback" => {
+                    self.web_search_fallback = default_settings.web_search_fallback.clone()
+                }
+                _ => {}
+            }
+        }
+        Self::remove_invalid_entries(
+            &mut self.application_hide_list,
+            "application_hide_list",
+            errors,
+        );
+        Self::remove_invalid_entries(
+            &mut self.application_name_aliases,
+            "application_name_aliases",
+            errors,
+        );
+        Self::remove_invalid_entries(
+            &mut self.application_overrides,
+            "application_overrides",
+            errors,
+        );
+        Self::remove_invalid_entries(&mut self.custom_entries, "custom_entries", errors);
+        Self::remove_invalid_entries(&mut self.web_search_keywords, "web_search_keywords", errors);
+        Self::remove_invalid_entries(&mut self.profiles, "profiles", errors);
+    }
+
+    /// Removes the entries of a list field that have an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The entries of the list field
+    /// * `field` - Name of the list field
+    /// * `errors` - The problems found by `validate`
+    fn remove_invalid_entries<T>(entries: &mut Vec<T>, field: &str, errors: &[SettingsFieldError]) {
+        let invalid_indices: Vec<usize> = errors
+            .iter()
+            .filter(|e| e.field == field)
+            .filter_map(|e| e.index)
+            .collect();
+        let mut index = 0;
+        entries.retain(|_| {
+            let keep = !invalid_indices.contains(&index);
+            index += 1;
+            keep
+        });
     }
 
     /// Saves current settings to the settings file.
     ///
-    /// This method serializes the Settings object to TOML format
-    /// and writes it to the settings file. It uses pretty formatting
-    /// to make the file more human-readable.
+    /// If the settings file exists, only the settings that have changed are
+    /// updated in the existing document, so that comments, the order of the keys
+    /// and keys unknown to this version are kept. Otherwise the Settings object is
+    /// serialized to TOML with pretty formatting. The file is replaced
+    /// atomically and the previous version is kept as a backup,
+    /// see `settings_backup::write_atomically`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
     ///
     /// # Returns
     ///
@@ -299,37 +1773,302 @@ impl Settings {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The file cannot be created
     /// - The settings cannot be serialized to TOML
-    /// - The data cannot be written to the file
-    pub fn save(self) -> KasuriResult<()> {
-        let path = Self::get_settings_file_path();
-        log::debug!("Creating settings file: {:?}", path);
-        let mut file = File::create(path)?;
+    /// - The temporary file cannot be written or renamed to the settings file
+    pub fn save(self, path: &Path) -> KasuriResult<()> {
+        self.save_with_policy(path, &Policy::default())
+    }
 
+    /// Saves current settings to the settings file without the values set by a policy.
+    ///
+    /// Works like `save`. Enforced settings are never written, and a new settings
+    /// file does not contain the settings that equal the default values of the policy,
+    /// see `load_with_policy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `policy` - The machine-wide policy the settings were loaded with
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure of the save operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The settings cannot be serialized to TOML
+    /// - The temporary file cannot be written or renamed to the settings file
+    pub fn save_with_policy(self, path: &Path, policy: &Policy) -> KasuriResult<()> {
         log::debug!("Serializing settings to TOML");
-        let settings_str = toml::to_string_pretty(&self)?;
+        let mut new_str = toml::to_string_pretty(&self)?;
+        if !policy.is_empty() {
+            new_str = Self::remove_policy_values(&new_str, policy)?;
+        }
 
-        log::debug!("Writing {} bytes to settings file", settings_str.len());
-        file.write_all(settings_str.as_bytes())?;
+        let settings_str = match std::fs::read_to_string(path) {
+            Ok(existing_str) if !existing_str.trim().is_empty() => {
+                match self.update_document(&existing_str, &new_str, policy) {
+                    Ok(settings_str) if settings_str == existing_str => {
+                        log::debug!("Settings file is unchanged, skipping save");
+                        return Ok(());
+                    }
+                    Ok(settings_str) => settings_str,
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to update the existing settings file, rewriting it: {}",
+                            e
+                        );
+                        new_str
+                    }
+                }
+            }
+            _ => new_str,
+        };
+
+        log::debug!(
+            "Writing {} bytes to settings file: {:?}",
+            settings_str.len(),
+            path
+        );
+        settings_backup::write_atomically(path, &settings_str)?;
 
         log::info!("Settings saved successfully");
         Ok(())
     }
 
-    /// Returns the path to the settings file.
+    /// Applies the settings to the existing settings document.
+    ///
+    /// Settings that are missing in the document are compared with their default
+    /// values, so that a sparse settings file stays sparse. Settings enforced by
+    /// the policy are left as they are in the document. Invalid settings, which
+    /// fall back to other values on loading, are left as they are in the document
+    /// unless they have been changed since, see `fall_back_invalid_values`.
+    ///
+    /// # Arguments
+    ///
+    /// * `existing_str` - Contents of the existing settings file
+    /// * `new_str` - The settings serialized with `toml::to_string_pretty`
+    /// * `policy` - The machine-wide policy the settings were loaded with
     ///
-    /// This method constructs the path to the settings file based on the current executable's directory.
-    /// It assumes the settings file is located in the same directory as the executable.
     /// # Returns
     ///
-    /// A `PathBuf` representing the path to the settings file.
-    fn get_settings_file_path() -> PathBuf {
-        std::env::current_exe()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .join(SETTINGS_FILE_NAME)
+    /// The updated contents of the settings file
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if the existing settings file is not valid TOML.
+    fn update_document(
+        &self,
+        existing_str: &str,
+        new_str: &str,
+        policy: &Policy,
+    ) -> KasuriResult<String> {
+        let mut document: toml_edit::DocumentMut = existing_str
+            .parse()
+            .map_err(|e: toml_edit::TomlError| KasuriError::Settings(e.to_string()))?;
+        let new_document: toml_edit::DocumentMut = new_str
+            .parse()
+            .map_err(|e: toml_edit::TomlError| KasuriError::Settings(e.to_string()))?;
+        let mut old_values: toml::Table = toml::from_str(existing_str)?;
+        // The enforced values of the profiles were removed on loading, they are kept in the file.
+        policy.remove_enforced_from_profiles(&mut old_values);
+        Self::fall_back_invalid_values(&mut old_values, &document, policy)?;
+        let mut new_values = toml::Table::try_from(self)?;
+        new_values.retain(|key, _| !policy.is_enforced(key));
+        let default_values = toml::Table::try_from(Self::default())?;
+        policy
+            .get_defaults()
+            .clone()
+            .into_iter()
+            .chain(default_values)
+            .for_each(|(key, value)| {
+                old_values.entry(key).or_insert(value);
+            });
+
+        Self::update_table(
+            document.as_table_mut(),
+            &old_values,
+            &new_values,
+            new_document.as_table(),
+            &[],
+        );
+        Ok(document.to_string())
+    }
+
+    /// Replaces the invalid values of a settings document with the values they fall back to.
+    ///
+    /// The settings in memory only contain the values that were used after loading,
+    /// e.g. the valid aliases. Comparing with these values keeps the invalid values
+    /// in the file, so that the user can still correct them.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values of the settings document
+    /// * `document` - The settings document
+    /// * `policy` - The machine-wide policy the settings were loaded with
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if the document cannot be migrated
+    fn fall_back_invalid_values(
+        values: &mut toml::Table,
+        document: &toml_edit::DocumentMut,
+        policy: &Policy,
+    ) -> KasuriResult<()> {
+        let mut document = document.clone();
+        settings_migration::migrate(&mut document)?;
+        let mut table: toml::Table = toml::from_str(&document.to_string())?;
+        policy.apply(&mut table);
+        let (mut settings, mut errors) = Self::from_table(&table);
+        let validation_errors = settings.validate();
+        settings.fall_back_invalid_fields(&validation_errors);
+        errors.extend(validation_errors);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        let loaded_values = toml::Table::try_from(&settings)?;
+        for error in &errors {
+            // Search paths that do not exist are kept on loading, for example.
+            if let (Some(value), Some(loaded_value)) = (
+                values.get_mut(&error.field),
+                loaded_values.get(&error.field),
+            ) && table.get(&error.field) != Some(loaded_value)
+            {
+                *value = loaded_value.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the settings set by a policy from a new settings document.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_str` - The settings serialized with `toml::to_string_pretty`
+    /// * `policy` - The machine-wide policy the settings were loaded with
+    ///
+    /// # Returns
+    ///
+    /// The settings without the enforced settings and the settings
+    /// that equal the default values of the policy
+    fn remove_policy_values(new_str: &str, policy: &Policy) -> KasuriResult<String> {
+        let mut document: toml_edit::DocumentMut = new_str
+            .parse()
+            .map_err(|e: toml_edit::TomlError| KasuriError::Settings(e.to_string()))?;
+        let values: toml::Table = toml::from_str(new_str)?;
+        for (key, value) in &values {
+            if policy.is_enforced(key) || policy.get_defaults().get(key) == Some(value) {
+                log::debug!("Not writing setting set by the policy: {}", key);
+                document.remove(key);
+            }
+        }
+        Ok(document.to_string())
+    }
+
+    /// Updates the keys of a table whose values have changed.
+    ///
+    /// Keys that are not in `new_values` are left alone,
+    /// unless they are optional keys that have been removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table in the existing document
+    /// * `old_values` - The values of the table before the change
+    /// * `new_values` - The values of the table after the change
+    /// * `new_table` - The new table, formatted for writing
+    /// * `optional_keys` - Keys of the table that are left out when they are not set
+    fn update_table(
+        table: &mut toml_edit::Table,
+        old_values: &toml::Table,
+        new_values: &toml::Table,
+        new_table: &toml_edit::Table,
+        optional_keys: &[&str],
+    ) {
+        for key in optional_keys {
+            if old_values.contains_key(*key) && !new_values.contains_key(*key) {
+                log::debug!("Removing setting: {}", key);
+                table.remove(key);
+            }
+        }
+        for (key, new_value) in new_values {
+            let old_value = old_values.get(key);
+            if old_value == Some(new_value) {
+                continue;
+            }
+            let Some(new_item) = new_table.get(key) else {
+                continue;
+            };
+            log::debug!("Updating setting: {}", key);
+            let optional_keys: &[&str] = match key.as_str() {
+                "profiles" => &SettingsProfile::OPTIONAL_KEYS,
+                _ => &[],
+            };
+            match table.get_mut(key) {
+                Some(item) => {
+                    Self::update_item(item, old_value, new_value, new_item, optional_keys)
+                }
+                None => {
+                    table.insert(key, new_item.clone());
+                }
+            }
+        }
+    }
+
+    /// Updates an item whose value has changed.
+    ///
+    /// Tables and arrays of tables with the same number of entries are updated
+    /// key by key. Other values are replaced, keeping their surrounding
+    /// whitespace and comments.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item in the existing document
+    /// * `old_value` - The value of the item before the change
+    /// * `new_value` - The value of the item after the change
+    /// * `new_item` - The new item, formatted for writing
+    /// * `optional_keys` - Keys of the tables in the item that are left out when they are not set
+    fn update_item(
+        item: &mut toml_edit::Item,
+        old_value: Option<&toml::Value>,
+        new_value: &toml::Value,
+        new_item: &toml_edit::Item,
+        optional_keys: &[&str],
+    ) {
+        let empty = toml::Table::new();
+        match (item, new_value, new_item) {
+            (
+                toml_edit::Item::Table(table),
+                toml::Value::Table(new_values),
+                toml_edit::Item::Table(new_table),
+            ) => {
+                let old_values = old_value.and_then(|v| v.as_table()).unwrap_or(&empty);
+                Self::update_table(table, old_values, new_values, new_table, optional_keys);
+            }
+            (
+                toml_edit::Item::ArrayOfTables(tables),
+                toml::Value::Array(new_values),
+                toml_edit::Item::ArrayOfTables(new_tables),
+            ) if tables.len() == new_values.len() && tables.len() == new_tables.len() => {
+                let old_values = old_value.and_then(|v| v.as_array());
+                for (i, table) in tables.iter_mut().enumerate() {
+                    let old_values = old_values
+                        .and_then(|v| v.get(i))
+                        .and_then(|v| v.as_table())
+                        .unwrap_or(&empty);
+                    let new_values = new_values[i].as_table().unwrap_or(&empty);
+                    if let Some(new_table) = new_tables.get(i) {
+                        Self::update_table(table, old_values, new_values, new_table, optional_keys);
+                    }
+                }
+            }
+            (toml_edit::Item::Value(value), _, toml_edit::Item::Value(new)) => {
+                let decor = value.decor().clone();
+                *value = new.clone();
+                *value.decor_mut() = decor;
+            }
+            (item, _, new_item) => *item = new_item.clone(),
+        }
     }
 }
 
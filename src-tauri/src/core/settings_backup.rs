@@ -0,0 +1,286 @@
+//! Atomic writes and rotating backups of the settings file.
+//!
+//! The settings file is never written in place. The new contents are written to
+//! a temporary file next to it, which is then renamed over the settings file, so
+//! that a crash or a full disk cannot leave a truncated file behind. Before the
+//! file is replaced, the previous version is copied to the backup directory,
+//! where the newest `SETTINGS_BACKUP_COUNT` versions are kept.
+
+use crate::{KasuriError, KasuriResult};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Number of backups that are kept
+pub const SETTINGS_BACKUP_COUNT: usize = 10;
+/// Name of the backup directory next to the settings file
+const BACKUP_DIR_NAME: &str = "settings_backups";
+/// Prefix of the backup file names
+const BACKUP_FILE_PREFIX: &str = "settings-";
+/// Extension of the backup file names
+const BACKUP_FILE_EXTENSION: &str = "toml";
+/// Extension appended to the settings file for the temporary file
+const TEMPORARY_FILE_EXTENSION: &str = "tmp";
+/// Extension appended to the settings file when a corrupt file is set aside
+const CORRUPT_FILE_EXTENSION: &str = "corrupt";
+
+/// A backup of the settings file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SettingsBackup {
+    /// File name of the backup, used to restore it
+    pub name: String,
+
+    /// Time the backup was created, in RFC 3339 format
+    pub created_at: String,
+
+    /// Size of the backup in bytes
+    pub size: u64,
+}
+
+/// Returns the backup directory of a settings file.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+///
+/// # Returns
+///
+/// The directory next to the settings file that holds its backups
+pub fn get_backup_dir(settings_file: &Path) -> PathBuf {
+    settings_file
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(BACKUP_DIR_NAME)
+}
+
+/// Writes the settings file atomically, keeping a backup of the previous version.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+/// * `contents` - The new contents of the settings file
+///
+/// # Returns
+///
+/// A `KasuriResult<()>` indicating success or failure of the write
+///
+/// # Errors
+///
+/// Returns `KasuriError::Io` if the temporary file cannot be written or renamed.
+/// The settings file is left unchanged in that case.
+pub fn write_atomically(settings_file: &Path, contents: &str) -> KasuriResult<()> {
+    let temporary_file = append_extension(settings_file, TEMPORARY_FILE_EXTENSION);
+    log::debug!(
+        "Writing temporary settings file: {}",
+        temporary_file.display()
+    );
+    let result = (|| {
+        let mut file = File::create(&temporary_file)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temporary_file);
+        return Err(e.into());
+    }
+
+    if let Err(e) = create_backup(settings_file) {
+        log::warn!("Failed to back up the settings file: {}", e);
+    }
+
+    log::debug!("Replacing settings file: {}", settings_file.display());
+    fs::rename(&temporary_file, settings_file).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary_file);
+    })?;
+    Ok(())
+}
+
+/// Copies the current settings file to the backup directory and removes the oldest backups.
+///
+/// Nothing is copied if the settings file does not exist or is empty.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+///
+/// # Returns
+///
+/// A `KasuriResult<()>` indicating success or failure of the backup
+pub fn create_backup(settings_file: &Path) -> KasuriResult<()> {
+    match fs::metadata(settings_file) {
+        Ok(metadata) if metadata.len() > 0 => {}
+        _ => {
+            log::debug!("No settings file to back up: {}", settings_file.display());
+            return Ok(());
+        }
+    }
+
+    let backup_dir = get_backup_dir(settings_file);
+    fs::create_dir_all(&backup_dir)?;
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+    // The sequence number keeps the names unique and sortable within the same millisecond.
+    let backup_file = (0..)
+        .map(|sequence| {
+            backup_dir.join(format!(
+                "{}{}-{:03}.{}",
+                BACKUP_FILE_PREFIX, timestamp, sequence, BACKUP_FILE_EXTENSION
+            ))
+        })
+        .find(|path| !path.exists())
+        .unwrap_or_default();
+    log::debug!("Backing up settings file to: {}", backup_file.display());
+    fs::copy(settings_file, &backup_file)?;
+
+    list_backups(settings_file)?
+        .iter()
+        .skip(SETTINGS_BACKUP_COUNT)
+        .for_each(|backup| {
+            log::debug!("Removing old settings backup: {}", backup.name);
+            if let Err(e) = fs::remove_file(backup_dir.join(&backup.name)) {
+                log::warn!(
+                    "Failed to remove old settings backup {}: {}",
+                    backup.name,
+                    e
+                );
+            }
+        });
+    Ok(())
+}
+
+/// Lists the backups of the settings file.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+///
+/// # Returns
+///
+/// The backups, newest first. Empty if no backup has been created yet.
+pub fn list_backups(settings_file: &Path) -> KasuriResult<Vec<SettingsBackup>> {
+    let backup_dir = get_backup_dir(settings_file);
+    if !backup_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut backups: Vec<SettingsBackup> = fs::read_dir(&backup_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !is_backup_name(&name) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let created_at = metadata
+                .modified()
+                .map(|time| DateTime::<Local>::from(time).to_rfc3339())
+                .unwrap_or_default();
+            Some(SettingsBackup {
+                name,
+                created_at,
+                size: metadata.len(),
+            })
+        })
+        .collect();
+    // The names contain the timestamp, so they sort chronologically.
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// Returns the path of a backup.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+/// * `name` - File name of the backup as returned by `list_backups`
+///
+/// # Returns
+///
+/// The path of the backup
+///
+/// # Errors
+///
+/// Returns `KasuriError::Settings` if the name is not the name of an existing backup
+pub fn get_backup_path(settings_file: &Path, name: &str) -> KasuriResult<PathBuf> {
+    let path = get_backup_dir(settings_file).join(name);
+    if !is_backup_name(name) || !path.is_file() {
+        return Err(KasuriError::Settings(format!(
+            "Settings backup not found: {}",
+            name
+        )));
+    }
+    Ok(path)
+}
+
+/// Sets a corrupt settings file aside, so that it can be inspected later.
+///
+/// # Arguments
+///
+/// * `settings_file` - Path of the settings file
+///
+/// # Returns
+///
+/// The path the corrupt file was moved to
+pub fn set_aside_corrupt_file(settings_file: &Path) -> KasuriResult<PathBuf> {
+    let corrupt_file = append_extension(settings_file, CORRUPT_FILE_EXTENSION);
+    fs::rename(settings_file, &corrupt_file)?;
+    Ok(corrupt_file)
+}
+
+/// Checks whether a file name is the name of a backup.
+///
+/// Names with path separators are rejected, so that a name received from
+/// the frontend cannot point outside of the backup directory.
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with(BACKUP_FILE_PREFIX)
+        && name.ends_with(&format!(".{}", BACKUP_FILE_EXTENSION))
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// Appends an extension to a path, e.g. `settings.toml` becomes `settings.toml.tmp`.
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomically_keeps_backups() {
+        let root = tempfile::tempdir().unwrap();
+        let settings_file = root.path().join("settings.toml");
+
+        for i in 0..SETTINGS_BACKUP_COUNT + 3 {
+            write_atomically(&settings_file, &format!("width = {}\n", 200 + i)).unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(&settings_file).unwrap(),
+            format!("width = {}\n", 200 + SETTINGS_BACKUP_COUNT + 2)
+        );
+        assert!(!append_extension(&settings_file, TEMPORARY_FILE_EXTENSION).exists());
+        let backups = list_backups(&settings_file).unwrap();
+        assert_eq!(backups.len(), SETTINGS_BACKUP_COUNT);
+        let newest = get_backup_path(&settings_file, &backups[0].name).unwrap();
+        assert_eq!(
+            fs::read_to_string(newest).unwrap(),
+            format!("width = {}\n", 200 + SETTINGS_BACKUP_COUNT + 1)
+        );
+    }
+
+    #[test]
+    fn test_get_backup_path_rejects_other_files() {
+        let root = tempfile::tempdir().unwrap();
+        let settings_file = root.path().join("settings.toml");
+        fs::write(&settings_file, "width = 800\n").unwrap();
+
+        assert!(get_backup_path(&settings_file, "settings-missing.toml").is_err());
+        assert!(get_backup_path(&settings_file, "../settings.toml").is_err());
+        assert!(get_backup_path(&settings_file, "settings-/../../settings.toml").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! Migrations of the settings file between format versions.
+//!
+//! The format version is stored in the `settings_version` key. Files written
+//! before the key was introduced have version 0. Each migration upgrades the
+//! document by one version and works on the parsed document, so that comments
+//! and keys unknown to this version are kept.
+//!
+//! To change the format, add a migration to `MIGRATIONS` and increase
+//! `SETTINGS_VERSION`, which must equal the number of migrations.
+
+use crate::core::settings_backup;
+use crate::{KasuriError, KasuriResult};
+use std::fs;
+use std::path::Path;
+use toml_edit::{Array, DocumentMut, Item, TableLike, Value};
+
+/// Current format version of the settings file
+pub const SETTINGS_VERSION: u32 = 2;
+/// Key of the format version in the settings file
+pub const SETTINGS_VERSION_KEY: &str = "settings_version";
+
+/// A migration that upgrades the settings document by one version
+type Migration = fn(&mut DocumentMut) -> KasuriResult<()>;
+
+/// Migrations in order, the migration at index `n` upgrades version `n` to `n + 1`
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Returns the format version of a settings document.
+///
+/// # Arguments
+///
+/// * `document` - The parsed settings file
+///
+/// # Returns
+///
+/// The value of `settings_version`, or 0 if the key is missing
+///
+/// # Errors
+///
+/// Returns `KasuriError::Settings` if `settings_version` is not a non-negative integer.
+pub fn get_version(document: &DocumentMut) -> KasuriResult<u32> {
+    match document.get(SETTINGS_VERSION_KEY) {
+        None => Ok(0),
+        Some(item) => item
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                KasuriError::Settings(format!(
+                    "Invalid {}: {}",
+                    SETTINGS_VERSION_KEY,
+                    item.to_string().trim()
+                ))
+            }),
+    }
+}
+
+/// Upgrades a settings document to `SETTINGS_VERSION`.
+///
+/// Documents written by a newer version of KASURI are left unchanged.
+///
+/// # Arguments
+///
+/// * `document` - The parsed settings file
+///
+/// # Returns
+///
+/// `true` if the document was migrated, `false` if it was already up to date
+///
+/// # Errors
+///
+/// Returns `KasuriError::Settings` if the version is invalid or a migration fails.
+pub fn migrate(document: &mut DocumentMut) -> KasuriResult<bool> {
+    let version = get_version(document)?;
+    if version > SETTINGS_VERSION {
+        log::warn!(
+            "Settings file was written by a newer version (settings version {}, supported {})",
+            version,
+            SETTINGS_VERSION
+        );
+        return Ok(false);
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating settings from version {} to {}", from, from + 1);
+        migration(document)?;
+        document[SETTINGS_VERSION_KEY] = toml_edit::value((from + 1) as i64);
+    }
+    Ok(version < SETTINGS_VERSION)
+}
+
+/// Upgrades the settings file to `SETTINGS_VERSION` and writes it back.
+///
+/// The previous version of the file is kept as a backup. Files that are empty
+/// or cannot be parsed are left alone, so that loading them reports the error.
+///
+/// # Arguments
+///
+/// * `path` - Path of the settings file
+///
+/// # Returns
+///
+/// `true` if the file was migrated, `false` if it was already up to date
+///
+/// # Errors
+///
+/// Returns an error if a migration fails or the migrated file cannot be written.
+pub fn migrate_file(path: &Path) -> KasuriResult<bool> {
+    let document = fs::read_to_string(path)
+        .ok()
+        .filter(|contents| !contents.trim().is_empty())
+        .and_then(|contents| contents.parse::<DocumentMut>().ok());
+    let Some(mut document) = document else {
+        log::debug!(
+            "Skipping migration of unreadable settings file: {}",
+            path.display()
+        );
+        return Ok(false);
+    };
+    if !migrate(&mut document)? {
+        return Ok(false);
+    }
+    settings_backup::write_atomically(path, &document.to_string())?;
+    log::info!("Settings file migrated to version {}", SETTINGS_VERSION);
+    Ok(true)
+}
+
+/// Version 0 files were written before the format was versioned.
+/// Their keys are unchanged in version 1, which only adds `settings_version`.
+fn migrate_v0_to_v1(_document: &mut DocumentMut) -> KasuriResult<()> {
+    Ok(())
+}
+
+/// Version 2 allows several aliases per application. The `alias` of each entry of
+/// `application_name_aliases`, in the general settings and in the profiles,
+/// becomes the list `aliases`.
+fn migrate_v1_to_v2(document: &mut DocumentMut) -> KasuriResult<()> {
+    migrate_alias_list(document.as_table_mut());
+    if let Some(profiles) = document.get_mut("profiles") {
+        for_each_table(profiles, migrate_alias_list);
+    }
+    Ok(())
+}
+
+/// Replaces `alias` with `aliases` in the alias entries of a table, see `migrate_v1_to_v2`.
+fn migrate_alias_list(table: &mut dyn TableLike) {
+    let Some(entries) = table.get_mut("application_name_aliases") else {
+        return;
+    };
+    for_each_table(entries, |entry| {
+        if entry.contains_key("aliases") {
+            return;
+        }
+        let Some(alias) = entry.remove("alias") else {
+            return;
+        };
+        match alias.into_value() {
+            Ok(alias) => {
+                let aliases = Array::from_iter([alias.decorated("", "")]);
+                entry.insert("aliases", Item::Value(Value::Array(aliases)));
+            }
+            Err(alias) => log::warn!("Skipping invalid alias: {}", alias.to_string().trim()),
+        }
+    });
+}
+
+/// Calls a function for each table of an array of tables or an array of inline tables.
+///
+/// Items of other types are left unchanged, so that loading them reports the error.
+fn for_each_table(item: &mut Item, mut f: impl FnMut(&mut dyn TableLike)) {
+    match item {
+        Item::ArrayOfTables(tables) => tables.iter_mut().for_each(|table| f(table)),
+        Item::Value(Value::Array(array)) => array
+            .iter_mut()
+            .filter_map(Value::as_inline_table_mut)
+            .for_each(|table| f(table)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_adds_version() {
+        let mut document: DocumentMut = "# comment\nwidth = 900\n".parse().unwrap();
+        assert_eq!(get_version(&document).unwrap(), 0);
+
+        assert!(migrate(&mut document).unwrap());
+        assert_eq!(get_version(&document).unwrap(), SETTINGS_VERSION);
+        assert!(document.to_string().starts_with("# comment\nwidth = 900\n"));
+
+        assert!(!migrate(&mut document).unwrap());
+    }
+
+    #[test]
+    fn test_migrate_keeps_newer_versions() {
+        let source = format!(
+            "{} = {}\nwidth = 900\n",
+            SETTINGS_VERSION_KEY,
+            SETTINGS_VERSION + 1
+        );
+        let mut document: DocumentMut = source.parse().unwrap();
+        assert!(!migrate(&mut document).unwrap());
+        assert_eq!(document.to_string(), source);
+    }
+
+    #[test]
+    fn test_migrate_v1_to_v2_converts_aliases() {
+        let mut document: DocumentMut = r#"settings_version = 1
+application_name_aliases = [{ path = 'C:\apps\a.exe', alias = "a" }]
+
+[[profiles]]
+name = "Work"
+
+[[profiles.application_name_aliases]]
+path = 'C:\apps\b.exe' # editor
+alias = "b"
+"#
+        .parse()
+        .unwrap();
+
+        assert!(migrate(&mut document).unwrap());
+        assert_eq!(
+            document.to_string(),
+            r#"settings_version = 2
+application_name_aliases = [{ path = 'C:\apps\a.exe', aliases = ["a"] }]
+
+[[profiles]]
+name = "Work"
+
+[[profiles.application_name_aliases]]
+path = 'C:\apps\b.exe' # editor
+aliases = ["b"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_get_version_rejects_invalid_values() {
+        let document: DocumentMut = "settings_version = \"one\"\n".parse().unwrap();
+        assert!(get_version(&document).is_err());
+        let document: DocumentMut = "settings_version = -1\n".parse().unwrap();
+        assert!(get_version(&document).is_err());
+    }
+}
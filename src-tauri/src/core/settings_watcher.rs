@@ -0,0 +1,117 @@
+//! Detection of external changes to the settings file.
+//!
+//! The settings file is polled instead of relying on file system notifications,
+//! because editors save files in very different ways (in place, by renaming a
+//! temporary file, or by deleting and recreating the file).
+
+use crate::KasuriResult;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Default interval between two checks of the settings file
+pub const SETTINGS_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Watches the settings file on a background thread.
+///
+/// Watching stops when the watcher is dropped.
+pub struct SettingsWatcher {
+    /// Set to stop the background thread
+    stopped: Arc<AtomicBool>,
+}
+
+/// Modification time and size of a file, `None` if the file does not exist
+type FileStamp = Option<(SystemTime, u64)>;
+
+impl SettingsWatcher {
+    /// Starts watching the settings file.
+    ///
+    /// `on_change` is called on the background thread whenever the modification
+    /// time or the size of the file changes. Changes made by KASURI itself are
+    /// reported as well, so the callback should ignore settings that did not change.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the settings file
+    /// * `interval` - Interval between two checks of the file
+    /// * `on_change` - Called when the file has changed
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<SettingsWatcher>` that keeps watching until it is dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Io` if the background thread cannot be started
+    pub fn start<F>(path: &Path, interval: Duration, mut on_change: F) -> KasuriResult<Self>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        log::debug!("Watching settings file: {}", path.display());
+        let path = path.to_path_buf();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let mut last_stamp = Self::get_stamp(&path);
+        thread::Builder::new()
+            .name("settings-watcher".to_string())
+            .spawn(move || {
+                while !thread_stopped.load(Ordering::Relaxed) {
+                    thread::sleep(interval);
+                    let stamp = Self::get_stamp(&path);
+                    if stamp == last_stamp || thread_stopped.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    last_stamp = stamp;
+                    if stamp.is_none() {
+                        log::debug!("Settings file was removed: {}", path.display());
+                        continue;
+                    }
+                    log::info!("Settings file changed: {}", path.display());
+                    on_change();
+                }
+                log::debug!("Stopped watching settings file: {}", path.display());
+            })?;
+        Ok(Self { stopped })
+    }
+
+    /// Reads the modification time and size of a file.
+    fn get_stamp(path: &Path) -> FileStamp {
+        let metadata = fs::metadata(path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+}
+
+impl Drop for SettingsWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_watcher_reports_changes() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("settings.toml");
+        fs::write(&path, "width = 800\n").unwrap();
+        let (sender, receiver) = channel();
+        let watcher = SettingsWatcher::start(&path, Duration::from_millis(10), move || {
+            let _ = sender.send(());
+        })
+        .unwrap();
+
+        fs::write(&path, "width = 1000\n").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+
+        drop(watcher);
+        thread::sleep(Duration::from_millis(50));
+        fs::write(&path, "width = 1200\n").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
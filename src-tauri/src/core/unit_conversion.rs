@@ -0,0 +1,579 @@
+//! Offline unit conversion for queries such as `5 mi to km` or `2h30m in seconds`.
+//!
+//! A conversion query is a quantity, a joining word (`to`, `in`, `as`, `into`, `=` or `->`)
+//! and the unit to convert to. The quantity may be made of several parts of the same
+//! category that are added up, e.g. `5 ft 11 in` or `1h 30min`.
+//!
+//! Units are looked up without regard to case, spaces or a leading `°` or `deg`, and plural
+//! forms such as `miles` or `inches` are accepted. As a consequence `MB` and `mb` are both
+//! megabytes, bits are only written as `bit`, `kbit`, `Mbit` and `Gbit`.
+//!
+//! The registry of units is embedded below. Every unit converts to the base unit of its
+//! category as `value * factor + offset`.
+
+use crate::core::calculator::{Calculation, format_real};
+
+/// Words that join the quantity and the unit to convert to
+const JOINERS: [&str; 5] = ["to", "in", "as", "into", "="];
+
+/// Category of a unit, only units of the same category can be converted into each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Base unit: meter
+    Length,
+    /// Base unit: kilogram
+    Mass,
+    /// Base unit: kelvin
+    Temperature,
+    /// Base unit: byte
+    DataSize,
+    /// Base unit: second
+    Time,
+    /// Base unit: square meter
+    Area,
+    /// Base unit: meter per second
+    Speed,
+}
+
+/// A unit of the registry.
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    /// Category of the unit
+    pub category: Category,
+    /// Symbol shown in the result, e.g. `km`
+    pub symbol: &'static str,
+    /// Names the unit is written as, in lower case and in the singular
+    pub names: &'static [&'static str],
+    /// Size of the unit in the base unit of its category
+    pub factor: f64,
+    /// Zero point of the unit in the base unit, only set for temperatures
+    pub offset: f64,
+}
+
+/// Shorthand to define a unit whose zero point is the zero of the base unit.
+const fn unit(
+    category: Category,
+    symbol: &'static str,
+    names: &'static [&'static str],
+    factor: f64,
+) -> Unit {
+    Unit {
+        category,
+        symbol,
+        names,
+        factor,
+        offset: 0.0,
+    }
+}
+
+/// The units that can be converted.
+///
+/// When a name belongs to several units, e.g. `m` for meter and minute, the unit whose
+/// category fits the rest of the query is used, the first one if several fit.
+pub const UNITS: &[Unit] = &[
+    // Length
+    unit(
+        Category::Length,
+        "nm",
+        &["nm", "nanometer", "nanometre"],
+        1e-9,
+    ),
+    unit(
+        Category::Length,
+        "µm",
+        &["µm", "um", "micrometer", "micrometre", "micron"],
+        1e-6,
+    ),
+    unit(
+        Category::Length,
+        "mm",
+        &["mm", "millimeter", "millimetre"],
+        1e-3,
+    ),
+    unit(
+        Category::Length,
+        "cm",
+        &["cm", "centimeter", "centimetre"],
+        1e-2,
+    ),
+    unit(Category::Length, "m", &["m", "meter", "metre"], 1.0),
+    unit(
+        Category::Length,
+        "km",
+        &["km", "kilometer", "kilometre"],
+        1e3,
+    ),
+    unit(Category::Length, "in", &["in", "inch", "\""], 0.0254),
+    unit(Category::Length, "ft", &["ft", "foot", "feet", "'"], 0.3048),
+    unit(Category::Length, "yd", &["yd", "yard"], 0.9144),
+    unit(Category::Length, "mi", &["mi", "mile"], 1609.344),
+    unit(Category::Length, "nmi", &["nmi", "nautical mile"], 1852.0),
+    // Mass
+    unit(
+        Category::Mass,
+        "mg",
+        &["mg", "milligram", "milligramme"],
+        1e-6,
+    ),
+    unit(Category::Mass, "g", &["g", "gram", "gramme"], 1e-3),
+    unit(
+        Category::Mass,
+        "kg",
+        &["kg", "kilogram", "kilogramme", "kilo"],
+        1.0,
+    ),
+    unit(Category::Mass, "t", &["t", "tonne", "metric ton"], 1e3),
+    unit(Category::Mass, "oz", &["oz", "ounce"], 0.028349523125),
+    unit(Category::Mass, "lb", &["lb", "pound"], 0.45359237),
+    unit(Category::Mass, "st", &["st", "stone"], 6.35029318),
+    // Temperature
+    Unit {
+        category: Category::Temperature,
+        symbol: "°C",
+        names: &["c", "celsius", "centigrade"],
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        category: Category::Temperature,
+        symbol: "°F",
+        names: &["f", "fahrenheit"],
+        factor: 5.0 / 9.0,
+        offset: 273.15 - 32.0 * 5.0 / 9.0,
+    },
+    unit(Category::Temperature, "K", &["k", "kelvin"], 1.0),
+    // Data size
+    unit(Category::DataSize, "bit", &["bit"], 0.125),
+    unit(Category::DataSize, "kbit", &["kbit", "kilobit"], 125.0),
+    unit(Category::DataSize, "Mbit", &["mbit", "megabit"], 125e3),
+    unit(Category::DataSize, "Gbit", &["gbit", "gigabit"], 125e6),
+    unit(Category::DataSize, "B", &["b", "byte"], 1.0),
+    unit(Category::DataSize, "KB", &["kb", "kilobyte"], 1e3),
+    unit(Category::DataSize, "MB", &["mb", "megabyte"], 1e6),
+    unit(Category::DataSize, "GB", &["gb", "gigabyte"], 1e9),
+    unit(Category::DataSize, "TB", &["tb", "terabyte"], 1e12),
+    unit(Category::DataSize, "PB", &["pb", "petabyte"], 1e15),
+    unit(Category::DataSize, "KiB", &["kib", "kibibyte"], 1024.0),
+    unit(Category::DataSize, "MiB", &["mib", "mebibyte"], 1048576.0),
+    unit(
+        Category::DataSize,
+        "GiB",
+        &["gib", "gibibyte"],
+        1073741824.0,
+    ),
+    unit(
+        Category::DataSize,
+        "TiB",
+        &["tib", "tebibyte"],
+        1099511627776.0,
+    ),
+    unit(
+        Category::DataSize,
+        "PiB",
+        &["pib", "pebibyte"],
+        1125899906842624.0,
+    ),
+    // Time
+    unit(Category::Time, "ns", &["ns", "nanosecond"], 1e-9),
+    unit(Category::Time, "µs", &["µs", "us", "microsecond"], 1e-6),
+    unit(Category::Time, "ms", &["ms", "msec", "millisecond"], 1e-3),
+    unit(Category::Time, "s", &["s", "sec", "second"], 1.0),
+    unit(Category::Time, "min", &["min", "m", "minute"], 60.0),
+    unit(Category::Time, "h", &["h", "hr", "hour"], 3600.0),
+    unit(Category::Time, "d", &["d", "day"], 86400.0),
+    unit(Category::Time, "wk", &["wk", "w", "week"], 604800.0),
+    // Average month and year of the Gregorian calendar
+    unit(Category::Time, "mo", &["mo", "month"], 2629746.0),
+    unit(Category::Time, "yr", &["yr", "y", "year"], 31556952.0),
+    // Area
+    unit(
+        Category::Area,
+        "mm²",
+        &[
+            "mm²",
+            "mm2",
+            "sq mm",
+            "square millimeter",
+            "square millimetre",
+        ],
+        1e-6,
+    ),
+    unit(
+        Category::Area,
+        "cm²",
+        &[
+            "cm²",
+            "cm2",
+            "sq cm",
+            "square centimeter",
+            "square centimetre",
+        ],
+        1e-4,
+    ),
+    unit(
+        Category::Area,
+        "m²",
+        &["m²", "m2", "sq m", "square meter", "square metre"],
+        1.0,
+    ),
+    unit(Category::Area, "ha", &["ha", "hectare"], 1e4),
+    unit(
+        Category::Area,
+        "km²",
+        &[
+            "km²",
+            "km2",
+            "sq km",
+            "square kilometer",
+            "square kilometre",
+        ],
+        1e6,
+    ),
+    unit(
+        Category::Area,
+        "in²",
+        &["in²", "in2", "sq in", "square inch"],
+        0.00064516,
+    ),
+    unit(
+        Category::Area,
+        "ft²",
+        &["ft²", "ft2", "sq ft", "square foot", "square feet"],
+        0.09290304,
+    ),
+    unit(
+        Category::Area,
+        "yd²",
+        &["yd²", "yd2", "sq yd", "square yard"],
+        0.83612736,
+    ),
+    unit(Category::Area, "ac", &["ac", "acre"], 4046.8564224),
+    unit(
+        Category::Area,
+        "mi²",
+        &["mi²", "mi2", "sq mi", "square mile"],
+        2589988.110336,
+    ),
+    // Speed
+    unit(
+        Category::Speed,
+        "m/s",
+        &["m/s", "mps", "meter per second", "metre per second"],
+        1.0,
+    ),
+    unit(
+        Category::Speed,
+        "km/h",
+        &[
+            "km/h",
+            "kmh",
+            "kph",
+            "kmph",
+            "kilometer per hour",
+            "kilometre per hour",
+        ],
+        1.0 / 3.6,
+    ),
+    unit(
+        Category::Speed,
+        "ft/s",
+        &["ft/s", "foot per second", "feet per second"],
+        0.3048,
+    ),
+    unit(
+        Category::Speed,
+        "mph",
+        &["mph", "mi/h", "mile per hour"],
+        0.44704,
+    ),
+    unit(
+        Category::Speed,
+        "kn",
+        &["kn", "kt", "knot"],
+        1852.0 / 3600.0,
+    ),
+];
+
+/// Converts a search query such as `5 mi to km`.
+///
+/// # Arguments
+///
+/// * `query` - The search query typed by the user
+///
+/// # Returns
+///
+/// The conversion with the value in the unit converted to, or `None` if the query is not
+/// a conversion or its units are unknown or of different categories
+pub fn convert(query: &str) -> Option<Calculation> {
+    let expression = query.trim();
+    let normalized = expression.replace("->", " to ").replace('→', " to ");
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    // "in" is also a unit, so every joining word is tried, e.g. "5 in in cm"
+    for (i, word) in words.iter().enumerate().skip(1) {
+        if !JOINERS.contains(&word.to_lowercase().as_str()) {
+            continue;
+        }
+        let quantity = words[..i].join(" ");
+        let target = words[i + 1..].join(" ");
+        if let Some((value, unit)) = convert_quantity(&quantity, &target) {
+            let value = format_real(value);
+            log::debug!("Converted '{}' = {} {}", expression, value, unit.symbol);
+            return Some(Calculation {
+                expression: expression.to_string(),
+                value,
+                unit: unit.symbol.to_string(),
+            });
+        }
+    }
+    None
+}
+
+/// Converts a quantity into a unit.
+///
+/// # Arguments
+///
+/// * `quantity` - The quantity to convert, e.g. `5 ft 11 in`
+/// * `target` - The name of the unit to convert to, e.g. `cm`
+///
+/// # Returns
+///
+/// The value in the unit converted to and that unit, or `None` if the conversion is not possible
+fn convert_quantity(quantity: &str, target: &str) -> Option<(f64, &'static Unit)> {
+    let parts = parse_quantity(quantity)?;
+    let targets = find_units(target);
+    let (category, target) = targets.iter().find_map(|target| {
+        parts
+            .iter()
+            .all(|(_, units)| units.iter().any(|v| v.category == target.category))
+            .then_some((target.category, *target))
+    })?;
+    // Temperatures have different zero points and cannot be added up
+    if category == Category::Temperature && parts.len() > 1 {
+        return None;
+    }
+    let base: f64 = parts
+        .iter()
+        .map(|(number, units)| {
+            let unit = units.iter().find(|v| v.category == category).unwrap();
+            number * unit.factor + unit.offset
+        })
+        .sum();
+    let value = (base - target.offset) / target.factor;
+    value.is_finite().then_some((value, target))
+}
+
+/// Splits a quantity such as `2h30m` or `5 ft 11 in` into numbers and their units.
+///
+/// # Returns
+///
+/// The numbers with the units their unit names may stand for,
+/// or `None` if a number or a unit is missing or unknown
+fn parse_quantity(quantity: &str) -> Option<Vec<(f64, Vec<&'static Unit>)>> {
+    let mut parts = vec![];
+    let mut rest = quantity.trim();
+    while !rest.is_empty() {
+        let number_length = rest
+            .char_indices()
+            .take_while(|&(i, c)| {
+                c.is_ascii_digit() || c == '.' || (i == 0 && c == '-' && parts.is_empty())
+            })
+            .count();
+        let number: f64 = rest[..number_length].parse().ok()?;
+        rest = rest[number_length..].trim_start();
+
+        // The unit reaches to the next number, except for the 2 of units such as m2
+        let mut end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if end > 0
+            && rest[end..].starts_with('2')
+            && !rest[end + 1..].starts_with(|c: char| c.is_ascii_digit())
+            && !find_units(&rest[..end + 1]).is_empty()
+        {
+            end += 1;
+        }
+        let units = find_units(&rest[..end]);
+        if units.is_empty() {
+            return None;
+        }
+        parts.push((number, units));
+        rest = rest[end..].trim_start();
+    }
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// Finds the units that a name may stand for.
+///
+/// # Arguments
+///
+/// * `name` - The name of the unit as typed, e.g. `Miles`, `°F` or `sq ft`
+///
+/// # Returns
+///
+/// The units with the name, in the order of `UNITS`, empty if the name is unknown
+pub fn find_units(name: &str) -> Vec<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    let name = name.trim_start_matches('°');
+    let name = ["degrees ", "degree ", "deg "]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(name);
+    let name = name
+        .strip_prefix("deg")
+        .filter(|v| ["c", "f"].contains(v))
+        .unwrap_or(name);
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // Try the name as typed, then in the singular, e.g. "miles per hour" or "inches"
+    let singular = |suffix: &str| {
+        name.split(' ')
+            .map(|word| match word.strip_suffix(suffix) {
+                Some(stem) if word.len() > 2 => stem,
+                _ => word,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for candidate in [name.clone(), singular("s"), singular("es")] {
+        let units: Vec<&Unit> = UNITS
+            .iter()
+            .filter(|unit| unit.names.contains(&candidate.as_str()))
+            .collect();
+        if !units.is_empty() {
+            return units;
+        }
+    }
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let cases = [
+            // Length
+            ("5 mi to km", "8.04672 km"),
+            ("5mi->km", "8.04672 km"),
+            ("10 Miles in kilometers", "16.09344 km"),
+            ("1 km to ft", "3280.83989501 ft"),
+            ("5 ft 11 in to cm", "180.34 cm"),
+            ("12 in in cm", "30.48 cm"),
+            ("3 inches to mm", "76.2 mm"),
+            ("1 nautical mile in m", "1852 m"),
+            // Mass
+            ("1 kg in lb", "2.20462262185 lb"),
+            ("16 oz to g", "453.59237 g"),
+            ("2 pounds as kilograms", "0.90718474 kg"),
+            // Temperature
+            ("72 f in c", "22.2222222222 °C"),
+            ("-40 °C to °F", "-40 °F"),
+            ("100 degrees celsius to fahrenheit", "212 °F"),
+            ("0 K in degC", "-273.15 °C"),
+            // Data size
+            ("3.5 GiB in MB", "3758.096384 MB"),
+            ("1 GB to MiB", "953.674316406 MiB"),
+            ("8 bits in bytes", "1 B"),
+            ("100 Mbit to MB", "12.5 MB"),
+            ("1 TiB = GiB", "1024 GiB"),
+            // Time
+            ("2h30m in seconds", "9000 s"),
+            ("1h 30min to minutes", "90 min"),
+            ("1 hour 30 minutes in h", "1.5 h"),
+            ("1 week to days", "7 d"),
+            ("90 s to min", "1.5 min"),
+            ("1500 ms in s", "1.5 s"),
+            // Area
+            ("1 acre to m2", "4046.8564224 m²"),
+            ("100 sq ft in square meters", "9.290304 m²"),
+            ("2 ha to km²", "0.02 km²"),
+            // Speed
+            ("100 km/h to mph", "62.1371192237 mph"),
+            ("60 miles per hour in kph", "96.56064 km/h"),
+            ("10 m/s into km/h", "36 km/h"),
+            ("20 knots to km/h", "37.04 km/h"),
+        ];
+        for (query, expected) in cases {
+            let conversion = convert(query);
+            let actual = conversion.map(|v| format!("{} {}", v.value, v.unit));
+            assert_eq!(actual.as_deref(), Some(expected), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_not_converted() {
+        let queries = [
+            "",
+            "notepad",
+            "5 mi",
+            "mi to km",
+            "to km",
+            "5 mi to",
+            "5 mi to kg",
+            "5 lightyears to km",
+            "5 km to parsec",
+            "1 m 2 kg to g",
+            "20 c 5 f to k",
+            "5 to km",
+            "1.2.3 km to m",
+            "5 - 3 km to m",
+            "install to desktop",
+        ];
+        for query in queries {
+            assert_eq!(convert(query), None, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_find_units() {
+        let cases = [
+            ("m", vec!["m", "min"]),
+            ("Meters", vec!["m"]),
+            ("metres", vec!["m"]),
+            ("inches", vec!["in"]),
+            ("feet", vec!["ft"]),
+            ("  sq   FT ", vec!["ft²"]),
+            ("°f", vec!["°F"]),
+            ("deg c", vec!["°C"]),
+            ("MB", vec!["MB"]),
+            ("hrs", vec!["h"]),
+            ("lbs", vec!["lb"]),
+            ("ms", vec!["ms"]),
+            ("kilometers per hour", vec!["km/h"]),
+            ("parsec", vec![]),
+        ];
+        for (name, expected) in cases {
+            let symbols: Vec<&str> = find_units(name).iter().map(|v| v.symbol).collect();
+            assert_eq!(symbols, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_registry_names_are_unambiguous_within_a_category() {
+        for (i, a) in UNITS.iter().enumerate() {
+            for b in &UNITS[i + 1..] {
+                if a.category != b.category {
+                    continue;
+                }
+                for name in a.names {
+                    assert!(
+                        !b.names.contains(name),
+                        "{} is used by {} and {}",
+                        name,
+                        a.symbol,
+                        b.symbol
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_result_row() {
+        let conversion = convert("  5 mi to km ").unwrap();
+        assert_eq!(conversion.get_result_name(), "5 mi to km = 8.04672 km");
+        assert_eq!(conversion.get_app_id(), "calc:5 mi to km");
+        assert_eq!(conversion.value, "8.04672");
+    }
+}
@@ -0,0 +1,251 @@
+//! Web search keywords that open a URL built from the search query.
+//!
+//! A query such as `g rust lifetimes` starts with the keyword `g`. The rest of the query
+//! is encoded and inserted into the URL template of the keyword in place of `{query}`,
+//! e.g. `https://www.google.com/search?q={query}`.
+//!
+//! Web searches are shown as result rows next to the applications. Their IDs are
+//! `web:` followed by the keyword and the query, so that the URL is built again from
+//! the settings when the row is launched.
+
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the IDs of web search result rows
+pub const APP_ID_PREFIX: &str = "web:";
+
+/// Placeholder in the URL template that is replaced with the encoded query
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
+/// Characters that are encoded, all but the unreserved characters of RFC 3986
+const ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// How the query is encoded before it is inserted into the URL template.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlEncoding {
+    /// Encoded for a query string, spaces become `+`
+    #[default]
+    Query,
+    /// Encoded for a path segment, spaces become `%20`
+    Path,
+    /// Inserted as typed, e.g. for issue keys such as `ABC-123`
+    None,
+}
+
+/// A keyword that searches the web, configured in the settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchKeyword {
+    /// The first word of the query that selects this search, e.g. `g`
+    pub keyword: String,
+
+    /// Name of the search shown in the result row, the keyword if empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+
+    /// URL opened in the default browser, `{query}` is replaced with the encoded query
+    pub url_template: String,
+
+    /// How the query is encoded
+    #[serde(default)]
+    pub encoding: UrlEncoding,
+}
+
+impl WebSearchKeyword {
+    /// Builds the URL of a search.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for, without the keyword
+    ///
+    /// # Returns
+    ///
+    /// The URL template with every `{query}` replaced with the encoded query
+    pub fn build_url(&self, query: &str) -> String {
+        let url = self
+            .url_template
+            .replace(QUERY_PLACEHOLDER, &encode_query(query, self.encoding));
+        log::debug!("Built web search URL for '{}': {}", self.keyword, url);
+        url
+    }
+
+    /// Returns the text shown in the result row of a search.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for, without the keyword
+    ///
+    /// # Returns
+    ///
+    /// The title of the search and the query, e.g. `Google: rust lifetimes`
+    pub fn get_result_name(&self, query: &str) -> String {
+        let title = if self.title.trim().is_empty() {
+            &self.keyword
+        } else {
+            &self.title
+        };
+        format!("{}: {}", title.trim(), query)
+    }
+
+    /// Returns the ID of the result row of a search.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for, without the keyword
+    ///
+    /// # Returns
+    ///
+    /// `web:` followed by the keyword and the query, which `parse_app_id` reads back
+    pub fn get_app_id(&self, query: &str) -> String {
+        format!("{}{} {}", APP_ID_PREFIX, self.keyword.trim(), query)
+    }
+}
+
+/// Encodes a query for insertion into a URL.
+///
+/// # Arguments
+///
+/// * `query` - The text to encode
+/// * `encoding` - How the text is encoded
+///
+/// # Returns
+///
+/// The encoded text, all characters except `A-Z a-z 0-9 - . _ ~` are percent-encoded as UTF-8
+/// unless the encoding is `UrlEncoding::None`
+pub fn encode_query(query: &str, encoding: UrlEncoding) -> String {
+    match encoding {
+        UrlEncoding::Query => utf8_percent_encode(query, ENCODE_SET)
+            .to_string()
+            .replace("%20", "+"),
+        UrlEncoding::Path => utf8_percent_encode(query, ENCODE_SET).to_string(),
+        UrlEncoding::None => query.to_string(),
+    }
+}
+
+/// Finds the keyword that a search query starts with.
+///
+/// The keyword is the first word of the query and is compared without regard to case.
+/// A keyword without any text to search for does not match.
+///
+/// # Arguments
+///
+/// * `query` - The search query typed by the user
+/// * `keywords` - The keywords from the settings
+///
+/// # Returns
+///
+/// The matching keyword and the trimmed rest of the query, or `None` if no keyword matches
+pub fn parse_query<'a, 'b>(
+    query: &'a str,
+    keywords: &'b [WebSearchKeyword],
+) -> Option<(&'b WebSearchKeyword, &'a str)> {
+    let (word, rest) = query.trim_start().split_once(char::is_whitespace)?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let keyword = keywords
+        .iter()
+        .find(|v| v.keyword.trim().eq_ignore_ascii_case(word))?;
+    log::debug!("Query matches web search keyword '{}'", keyword.keyword);
+    Some((keyword, rest))
+}
+
+/// Reads the keyword and the query back from the ID of a result row.
+///
+/// # Arguments
+///
+/// * `app_id` - The ID of the result row, see `WebSearchKeyword::get_app_id`
+/// * `keywords` - The keywords from the settings
+///
+/// # Returns
+///
+/// The keyword and the query, or `None` if the ID is not a web search of a known keyword
+pub fn parse_app_id<'a, 'b>(
+    app_id: &'a str,
+    keywords: &'b [WebSearchKeyword],
+) -> Option<(&'b WebSearchKeyword, &'a str)> {
+    parse_query(app_id.strip_prefix(APP_ID_PREFIX)?, keywords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords() -> Vec<WebSearchKeyword> {
+        vec![
+            WebSearchKeyword {
+                keyword: "g".to_string(),
+                title: "Google".to_string(),
+                url_template: "https://www.google.com/search?q={query}".to_string(),
+                encoding: UrlEncoding::Query,
+            },
+            WebSearchKeyword {
+                keyword: "jira".to_string(),
+                title: String::new(),
+                url_template: "https://example.atlassian.net/browse/{query}".to_string(),
+                encoding: UrlEncoding::None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let keywords = keywords();
+        let cases = [
+            ("g rust lifetimes", Some(("g", "rust lifetimes"))),
+            ("  G   rust  ", Some(("g", "rust"))),
+            ("jira\tABC-123", Some(("jira", "ABC-123"))),
+            ("g", None),
+            ("g   ", None),
+            ("go rust", None),
+            ("google rust", None),
+            ("", None),
+        ];
+        for (query, expected) in cases {
+            let actual = parse_query(query, &keywords).map(|(k, q)| (k.keyword.as_str(), q));
+            assert_eq!(actual, expected, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_encode_query() {
+        let cases = [
+            ("rust lifetimes", UrlEncoding::Query, "rust+lifetimes"),
+            ("a+b=c&d", UrlEncoding::Query, "a%2Bb%3Dc%26d"),
+            ("c++ / c#", UrlEncoding::Path, "c%2B%2B%20%2F%20c%23"),
+            ("日本", UrlEncoding::Path, "%E6%97%A5%E6%9C%AC"),
+            ("~file_name-1.0", UrlEncoding::Query, "~file_name-1.0"),
+            ("ABC-123 x", UrlEncoding::None, "ABC-123 x"),
+        ];
+        for (query, encoding, expected) in cases {
+            assert_eq!(encode_query(query, encoding), expected, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_build_url_and_result_row() {
+        let keywords = keywords();
+        assert_eq!(
+            keywords[0].build_url("rust lifetimes"),
+            "https://www.google.com/search?q=rust+lifetimes"
+        );
+        assert_eq!(
+            keywords[1].build_url("ABC-123"),
+            "https://example.atlassian.net/browse/ABC-123"
+        );
+        assert_eq!(keywords[0].get_result_name("rust"), "Google: rust");
+        assert_eq!(keywords[1].get_result_name("ABC-1"), "jira: ABC-1");
+
+        let app_id = keywords[0].get_app_id("rust lifetimes");
+        assert_eq!(app_id, "web:g rust lifetimes");
+        let (keyword, query) = parse_app_id(&app_id, &keywords).unwrap();
+        assert_eq!((keyword.keyword.as_str(), query), ("g", "rust lifetimes"));
+        assert!(parse_app_id("custom:g rust", &keywords).is_none());
+        assert!(parse_app_id("web:bing rust", &keywords).is_none());
+    }
+}
@@ -3,25 +3,45 @@ pub mod model;
 mod repositories;
 mod service;
 
+use crate::core::archive::{
+    ApplicationMatch, ArchivedApplication, ImportReport, ImportedApplication, KasuriArchive,
+    PathMapping, map_path, match_application,
+};
+use crate::core::calculator::{self, Calculation};
+use crate::core::launcher_import::{LauncherData, LauncherImportTarget, match_entry};
+use crate::core::log::set_log_level_str;
+use crate::core::path_expansion::{contract_path, expand_path};
+use crate::core::paths::KasuriPaths;
+use crate::core::policy::Policy;
 use crate::core::settings::{
-    SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP, Settings,
+    ApplicationNameAlias, SETTINGS_VALUE_APPLICATION_SEARCH_PATH_LIST_WINDOWS_STORE_APP, Settings,
+    SettingsChanges,
 };
+use crate::core::unit_conversion;
+use crate::core::web_search::{self, WebSearchKeyword};
 use crate::model::AppForView;
-use crate::model::application::Application;
+use crate::model::action::{ActionForView, ActionId, ActionOutcome};
+use crate::model::application::{Application, ApplicationKind};
+use crate::model::event::KasuriEvent;
+use crate::model::launch_failure::LaunchFailure;
+use crate::model::stats::{ApplicationUsage, KasuriStats, MOST_USED_APPLICATION_LIMIT};
 use crate::repositories::application_repository::ApplicationRepository;
 use crate::repositories::kasuri_repository::KasuriRepository;
 use crate::repositories::repository_initializer::RepositoryInitializer;
 use crate::service::fuzzy_sorter::FuzzySorter;
+use std::fs;
 use std::path::PathBuf;
-use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, Sender, channel};
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::Manager;
+
+pub use crate::core::error::KasuriError;
 
 /// Represents a Result type for Kasuri operations.
 ///
-/// This type alias simplifies error handling throughout the application by
-/// wrapping any error type in a Box<dyn std::error::Error>.
-pub type KasuriResult<T> = Result<T, Box<dyn std::error::Error>>;
+/// This type alias simplifies error handling throughout the application.
+/// Errors are reported as `KasuriError`, which can be told apart by kind.
+pub type KasuriResult<T> = Result<T, KasuriError>;
 
 /// Main application controller for Kasuri.
 ///
@@ -30,6 +50,10 @@ pub type KasuriResult<T> = Result<T, Box<dyn std::error::Error>>;
 pub struct Kasuri {
     /// Application settings loaded from configuration file.
     pub settings: Settings,
+    /// Locations of the settings file, the database and the caches.
+    pub paths: KasuriPaths,
+    /// Machine-wide policy layered over the settings.
+    pub policy: Policy,
     /// Repository for application data access.
     application_repository: ApplicationRepository,
     /// Repository for Kasuri's internal data.
@@ -38,6 +62,8 @@ pub struct Kasuri {
     fuzzy_sorter: FuzzySorter,
     /// In-memory cache of available applications.
     app_cache: Option<Vec<Application>>,
+    /// Channels of the subscribers that receive `KasuriEvent`s.
+    event_subscribers: Mutex<Vec<Sender<KasuriEvent>>>,
 }
 
 /// Maximum number of search results to display to the user.
@@ -47,28 +73,51 @@ impl Kasuri {
     /// Creates a new Kasuri instance with the provided settings.
     ///
     /// Initializes the application with custom settings instead of loading from the file.
+    /// If the active profile keeps its usage statistics separately, the database
+    /// of the profile is used, see `KasuriPaths::get_profile_database_file`.
     ///
     /// # Arguments
     ///
     /// * `settings` - The application settings to use for initialization
+    /// * `paths` - The locations of the database and the caches
     ///
     /// # Returns
     ///
     /// A `KasuriResult<Self>` containing the initialized Kasuri instance or an error
-    pub fn with_settings(settings: Settings) -> KasuriResult<Self> {
-        let repository_initializer = RepositoryInitializer::new();
+    pub fn with_settings(settings: Settings, paths: KasuriPaths) -> KasuriResult<Self> {
+        let database_file = paths.get_profile_database_file(settings.get_usage_profile());
+        let repository_initializer = RepositoryInitializer::new(&database_file);
         let repositories = repository_initializer.get_repositories()?;
         let application_repository = repositories.application_repository;
         let kasuri_repository = repositories.kasuri_repository;
         Ok(Self {
             settings,
+            paths,
+            policy: Policy::default(),
             application_repository,
             kasuri_repository,
             fuzzy_sorter: FuzzySorter::new(),
             app_cache: None,
+            event_subscribers: Mutex::new(vec![]),
         })
     }
 
+    /// Sets the machine-wide policy the settings were loaded with.
+    ///
+    /// The policy is used when the settings are reloaded, see `reload_settings`.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The machine-wide policy
+    ///
+    /// # Returns
+    ///
+    /// The Kasuri instance for chaining
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// Initializes the Kasuri instance by loading applications into the cache.
     ///
     /// This method should be called after creating a Kasuri instance and before using it.
@@ -76,11 +125,114 @@ impl Kasuri {
     /// # Returns
     ///
     /// A `KasuriResult<()>` indicating success or failure of the initialization
-    pub fn init(&mut self, app_handle: &tauri::AppHandle) -> KasuriResult<()> {
-        self.set_app_cache(self.load_applications_from_search_path_if_needed(app_handle)?)?;
+    pub fn init(&mut self) -> KasuriResult<()> {
+        self.set_app_cache(self.load_applications_from_search_path_if_needed()?)?;
         Ok(())
     }
 
+    /// Applies new settings without restarting the application.
+    ///
+    /// The settings that are handled by the core are applied right away:
+    /// the log level is changed, the aliases are reassigned, hidden applications
+    /// are removed from the cache, and the search paths are rescanned if they changed.
+    /// If a profile with separate usage statistics is activated or deactivated,
+    /// the database is switched and the applications are rescanned into it. Settings handled by the UI layer
+    /// (shortcut key, window width, auto startup) are reported in the result.
+    /// A failed rescan is logged, the applications found before are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The new settings
+    ///
+    /// # Returns
+    ///
+    /// A `SettingsChanges` telling which settings have changed
+    pub fn apply_settings(&mut self, settings: Settings) -> SettingsChanges {
+        let changes = self.settings.diff(&settings);
+        // Settings that are not in effect, such as the values of inactive profiles,
+        // are kept as well, so that saving the settings does not undo their changes.
+        self.settings = settings;
+        if !changes.is_any() {
+            log::debug!("Settings in effect have not changed");
+            return changes;
+        }
+        log::info!("Applying changed settings: {:?}", changes);
+        if changes.log_level {
+            set_log_level_str(self.settings.get_log_level());
+        }
+        if changes.usage_profile {
+            if let Err(e) = self.switch_database() {
+                log::error!("Failed to switch the database after settings change: {}", e);
+            }
+        } else if changes.application_search_path_list || changes.custom_entries {
+            if let Err(e) = self.load_applications_to_cache() {
+                log::error!("Failed to rescan applications after settings change: {}", e);
+            }
+        } else if changes.application_hide_list
+            || changes.application_name_aliases
+            || changes.application_overrides
+        {
+            // Applications that are no longer hidden are still in the repository,
+            // with the names that the aliases match before the display names replace them.
+            match self.load_application_from_repository() {
+                Ok(mut applications) => {
+                    self.setup_applications_icon_path(&mut applications);
+                    let _ = self.set_app_cache(applications);
+                }
+                Err(e) => {
+                    log::error!("Failed to reload applications after settings change: {}", e)
+                }
+            }
+        }
+        changes
+    }
+
+    /// Activates a settings profile, saves it as the active profile and applies it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the profile, or an empty string to use the general settings
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<SettingsChanges>` telling which settings have changed
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if there is no profile with the name,
+    /// `KasuriError::InvalidSettings` if the active profile is locked by the policy,
+    /// or the error of saving the settings file
+    pub fn switch_profile(&mut self, name: &str) -> KasuriResult<SettingsChanges> {
+        let mut settings = self.settings.clone();
+        settings.set_active_profile(name)?;
+        let errors = self.policy.check(&self.settings, &settings);
+        if !errors.is_empty() {
+            return Err(KasuriError::InvalidSettings(errors));
+        }
+        settings
+            .clone()
+            .save_with_policy(self.paths.get_settings_file(), &self.policy)?;
+        Ok(self.apply_settings(settings))
+    }
+
+    /// Loads the settings file again and applies the changed settings.
+    ///
+    /// Used when the settings file was edited outside of KASURI.
+    /// The policy set with `with_policy` is layered over the file.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<SettingsChanges>` telling which settings have changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file cannot be loaded, in which case
+    /// the current settings are kept
+    pub fn reload_settings(&mut self) -> KasuriResult<SettingsChanges> {
+        let settings = Settings::load_with_policy(self.paths.get_settings_file(), &self.policy)?;
+        Ok(self.apply_settings(settings))
+    }
+
     /// Handles application search requests by querying the application cache
     /// with the provided search term.
     ///
@@ -88,6 +240,10 @@ impl Kasuri {
     /// the top matches limited to the maximum display count. It uses the
     /// `sort_with_filter` method from `FuzzySorter` which filters results
     /// based on a minimum match score threshold.
+    /// A query that starts with a web search keyword gets the web search as the first row,
+    /// any other query gets the fallback web search of the settings as the last row,
+    /// see `web_search::parse_query`. An arithmetic or unit conversion query gets its
+    /// result as the very first row, see `calculate`.
     ///
     /// # Arguments
     ///
@@ -97,9 +253,28 @@ impl Kasuri {
     ///
     /// A vector of simplified application objects ready to be displayed in the UI
     pub fn handle_search_application(&self, query: &str) -> Vec<AppForView> {
-        if let Some(applications) = self.app_cache.as_ref().map(|v| &v[..]) {
+        let keywords = self.settings.get_web_search_keywords();
+        let mut first_rows: Vec<AppForView> = Self::calculate(query)
+            .iter()
+            .map(Self::calculation_view)
+            .collect();
+        let mut last_rows = vec![];
+        match web_search::parse_query(query, &keywords) {
+            Some((keyword, text)) => first_rows.push(Self::web_search_view(keyword, text)),
+            None if !query.trim().is_empty() => {
+                if let Some(fallback) = self.settings.get_web_search_fallback() {
+                    last_rows.push(Self::web_search_view(&fallback, query.trim()));
+                }
+            }
+            None => {}
+        }
+
+        let applications = if let Some(applications) = self.app_cache.as_ref().map(|v| &v[..]) {
             let sorted_apps = self.fuzzy_sorter.sort_with_filter(query, applications);
-            let limit = std::cmp::min(sorted_apps.len(), SEARCH_RESULT_LIMIT);
+            let limit = std::cmp::min(
+                sorted_apps.len(),
+                SEARCH_RESULT_LIMIT.saturating_sub(first_rows.len() + last_rows.len()),
+            );
             sorted_apps[..limit]
                 .iter()
                 .map(|app| AppForView {
@@ -111,13 +286,439 @@ impl Kasuri {
         } else {
             log::warn!("Application cache is not initialized, returning empty search results");
             vec![]
+        };
+        first_rows
+            .into_iter()
+            .chain(applications)
+            .chain(last_rows)
+            .collect()
+    }
+
+    /// Creates the result row of a web search.
+    ///
+    /// # Arguments
+    ///
+    /// * `keyword` - The keyword of the web search
+    /// * `query` - The text to search for, without the keyword
+    ///
+    /// # Returns
+    ///
+    /// The result row, without an icon
+    fn web_search_view(keyword: &WebSearchKeyword, query: &str) -> AppForView {
+        AppForView {
+            name: keyword.get_result_name(query),
+            app_id: keyword.get_app_id(query),
+            icon_path: String::new(),
+        }
+    }
+
+    /// Creates the result row of a calculation.
+    ///
+    /// # Arguments
+    ///
+    /// * `calculation` - The calculated query
+    ///
+    /// # Returns
+    ///
+    /// The result row, without an icon
+    fn calculation_view(calculation: &Calculation) -> AppForView {
+        AppForView {
+            name: calculation.get_result_name(),
+            app_id: calculation.get_app_id(),
+            icon_path: String::new(),
+        }
+    }
+
+    /// Calculates a query with the calculator or, if it is not arithmetic,
+    /// with the unit converter.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query typed by the user
+    ///
+    /// # Returns
+    ///
+    /// The calculation or conversion, or `None` if the query is neither
+    fn calculate(query: &str) -> Option<Calculation> {
+        calculator::evaluate(query).or_else(|| unit_conversion::convert(query))
+    }
+
+    /// Calculates the query of a calculator result row again.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the result row, see `Calculation::get_app_id`
+    ///
+    /// # Returns
+    ///
+    /// The calculation of the result row
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::ApplicationNotFound` if the query of the row cannot be calculated
+    fn get_calculation(app_id: &str) -> KasuriResult<Calculation> {
+        calculator::parse_app_id(app_id)
+            .and_then(Self::calculate)
+            .ok_or_else(|| KasuriError::ApplicationNotFound(app_id.to_string()))
+    }
+
+    /// Builds the URL of the web search of a result row.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the result row, see `WebSearchKeyword::get_app_id`
+    ///
+    /// # Returns
+    ///
+    /// The name of the result row and the URL to open
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::ApplicationNotFound` if the keyword is no longer in the settings
+    fn get_web_search_url(&self, app_id: &str) -> KasuriResult<(String, String)> {
+        let keywords = self.settings.get_web_search_keywords();
+        let (keyword, query) = web_search::parse_app_id(app_id, &keywords)
+            .ok_or_else(|| KasuriError::ApplicationNotFound(app_id.to_string()))?;
+        Ok((keyword.get_result_name(query), keyword.build_url(query)))
+    }
+
+    /// Opens the web search of a result row in the default browser.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The ID of the result row, see `WebSearchKeyword::get_app_id`
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of opening the browser
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::ApplicationNotFound` if the keyword is no longer in the settings
+    /// and `KasuriError::Launch` if the browser could not be opened
+    fn launch_web_search(&self, app_id: &str) -> KasuriResult<()> {
+        let (name, url) = self.get_web_search_url(app_id)?;
+        log::info!("Opening web search: {}", url);
+        open::that_detached(&url).map_err(|e| {
+            log::error!("Failed to open web search '{}': {}", url, e);
+            KasuriError::Launch(LaunchFailure {
+                app_id: app_id.to_string(),
+                name: Some(name),
+                message: format!("Failed to open {}: {}", url, e),
+                failure_count: 0,
+            })
+        })
+    }
+
+    /// Subscribes to the events of this Kasuri instance.
+    ///
+    /// The subscription ends when the returned receiver is dropped.
+    ///
+    /// # Returns
+    ///
+    /// A receiver that gets every `KasuriEvent` published after this call
+    pub fn subscribe(&self) -> Receiver<KasuriEvent> {
+        let (sender, receiver) = channel();
+        self.event_subscribers.lock().unwrap().push(sender);
+        log::debug!("New event subscriber registered");
+        receiver
+    }
+
+    /// Returns all applications in the application cache.
+    ///
+    /// # Returns
+    ///
+    /// A slice of the cached applications, empty if the cache is not initialized
+    pub fn get_applications(&self) -> &[Application] {
+        self.app_cache.as_deref().unwrap_or_default()
+    }
+
+    /// Collects statistics about the applications in the application cache.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<KasuriStats>` containing the statistics or an error
+    pub fn handle_get_stats(&self) -> KasuriResult<KasuriStats> {
+        let applications = self.get_applications();
+        let mut most_used_applications: Vec<ApplicationUsage> = applications
+            .iter()
+            .filter(|app| app.usage_recency_score > 0.0)
+            .map(|app| ApplicationUsage {
+                name: app.name.clone(),
+                app_id: app.app_id.clone(),
+                usage_recency_score: app.usage_recency_score,
+                launch_failure_count: app.launch_failure_count,
+            })
+            .collect();
+        let used_application_count = most_used_applications.len();
+        most_used_applications
+            .sort_by(|a, b| b.usage_recency_score.total_cmp(&a.usage_recency_score));
+        most_used_applications.truncate(MOST_USED_APPLICATION_LIMIT);
+
+        Ok(KasuriStats {
+            application_count: applications.len(),
+            used_application_count,
+            failing_application_count: applications
+                .iter()
+                .filter(|app| app.launch_failure_count > 0)
+                .count(),
+            last_application_search_time: self
+                .kasuri_repository
+                .get_last_application_search_time()?,
+            most_used_applications,
+        })
+    }
+
+    /// Exports the settings and the usage statistics to an archive.
+    ///
+    /// Paths are written in a portable form, see `contract_path`, and the settings
+    /// enforced by the policy are left out. Only the applications that have been
+    /// used or pinned are exported, from the database of the active profile.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<KasuriArchive>` containing the archive or an error
+    pub fn handle_export_archive(&self) -> KasuriResult<KasuriArchive> {
+        let mut settings = self.settings.clone();
+        settings.map_paths(contract_path);
+        let mut values = toml::Table::try_from(&settings)?;
+        for field in self.policy.get_enforced_fields() {
+            values.remove(&field);
+        }
+        let applications: Vec<ArchivedApplication> = self
+            .application_repository
+            .get_application_records()?
+            .into_iter()
+            .filter(|record| record.usage_count > 0 || record.pinned)
+            .map(|record| ArchivedApplication {
+                app_id: contract_path(&record.app_id),
+                name: record.name,
+                usage_count: record.usage_count,
+                last_used: record.last_used,
+                pinned: record.pinned,
+            })
+            .collect();
+        log::info!("Exporting {} applications", applications.len());
+        Ok(KasuriArchive::new(values, applications))
+    }
+
+    /// Merges an archive into the current settings and usage statistics.
+    ///
+    /// The paths in the archive are remapped with `mappings` and then expanded.
+    /// New list entries of the settings are added, see `Settings::merge`, and saved
+    /// unless they are enforced by the policy. The usage statistics are added to the
+    /// matching applications, see `match_application`. Settings are merged first,
+    /// so that the applications of imported search paths are found as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive` - The archive to import
+    /// * `mappings` - Replacements for the start of the paths in the archive
+    /// * `dry_run` - `true` to only report what would be imported
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<ImportReport>` describing what was imported
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Settings` if the settings of the archive cannot be read,
+    /// or the error of saving the settings file or updating the database
+    pub fn handle_import_archive(
+        &mut self,
+        archive: &KasuriArchive,
+        mappings: &[PathMapping],
+        dry_run: bool,
+    ) -> KasuriResult<ImportReport> {
+        log::info!(
+            "Importing archive exported at {} (dry run: {})",
+            archive.exported_at,
+            dry_run
+        );
+        let (mut imported, errors) = Settings::from_values(&archive.settings)?;
+        errors
+            .iter()
+            .for_each(|e| log::warn!("Invalid setting in archive, skipping it: {}", e));
+        imported.map_paths(|path| map_path(mappings, path));
+        let mut settings = self.settings.clone();
+        let settings_added = settings.merge(&imported, |field| self.policy.is_enforced(field));
+        if !dry_run && !settings_added.is_empty() {
+            settings
+                .clone()
+                .save_with_policy(self.paths.get_settings_file(), &self.policy)?;
+            self.apply_settings(settings);
+        }
+
+        let mut report = ImportReport {
+            dry_run,
+            settings_added,
+            ..Default::default()
+        };
+        let records = self.application_repository.get_application_records()?;
+        let local_app_ids: Vec<&str> = records
+            .iter()
+            .map(|record| record.app_id.as_str())
+            .collect();
+        for application in &archive.applications {
+            let app_id = expand_path(&map_path(mappings, &application.app_id));
+            let (local_app_id, remapped) = match match_application(&app_id, &local_app_ids) {
+                ApplicationMatch::Same(local_app_id) => (local_app_id, false),
+                ApplicationMatch::Remapped(local_app_id) => (local_app_id, true),
+                ApplicationMatch::NotFound => {
+                    log::debug!("Archived application not found: {}", application.app_id);
+                    report
+                        .applications_not_found
+                        .push(application.app_id.clone());
+                    continue;
+                }
+            };
+            if !dry_run {
+                self.application_repository.merge_usage(
+                    local_app_id,
+                    application.usage_count,
+                    application.last_used,
+                    application.pinned,
+                )?;
+            }
+            report.applications.push(ImportedApplication {
+                name: application.name.clone(),
+                app_id: local_app_id.to_string(),
+                archived_app_id: application.app_id.clone(),
+                remapped,
+                usage_count: application.usage_count,
+                pinned: application.pinned,
+            });
+        }
+
+        if !dry_run && !report.applications.is_empty() {
+            self.reload_applications_from_repository()?;
+        }
+        log::info!(
+            "Imported {} settings entries and {} applications, {} applications not found",
+            report.settings_added.len(),
+            report.applications.len(),
+            report.applications_not_found.len()
+        );
+        Ok(report)
+    }
+
+    /// Merges the aliases and the usage history of another launcher.
+    ///
+    /// The entries are matched to the applications in the database, see `match_entry`.
+    /// Aliases are added to the settings and saved unless the aliases are enforced
+    /// by the policy, see `Settings::merge_aliases`, and the usage counts are added
+    /// to the usage statistics of the active profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data read from the other launcher, see `Launcher::read`
+    /// * `dry_run` - `true` to only report what would be imported
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<ImportReport>` describing what was imported
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of saving the settings file or updating the database
+    pub fn handle_import_launcher(
+        &mut self,
+        data: &LauncherData,
+        dry_run: bool,
+    ) -> KasuriResult<ImportReport> {
+        log::info!(
+            "Importing {} aliases and {} usage records from another launcher (dry run: {})",
+            data.aliases.len(),
+            data.usage.len(),
+            dry_run
+        );
+        let records = self.application_repository.get_application_records()?;
+        let targets: Vec<LauncherImportTarget> = records
+            .iter()
+            .map(|record| LauncherImportTarget {
+                app_id: &record.app_id,
+                name: &record.name,
+                path: &record.path,
+            })
+            .collect();
+        let mut report = ImportReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        let mut aliases = vec![];
+        for alias in &data.aliases {
+            match match_entry(&alias.target, None, &targets) {
+                Some(target) => aliases.push(ApplicationNameAlias::for_app_id(
+                    &contract_path(target.app_id),
+                    vec![alias.alias.clone()],
+                )),
+                None => report
+                    .aliases_not_found
+                    .push(format!("{}: {}", alias.alias, alias.target)),
+            }
+        }
+        let mut settings = self.settings.clone();
+        if self.policy.is_enforced("application_name_aliases") {
+            log::warn!("Aliases are enforced by the policy, skipping the imported aliases");
+        } else {
+            report.settings_added = settings.merge_aliases(&aliases);
+        }
+
+        for usage in &data.usage {
+            let Some(target) = match_entry(&usage.name, usage.path.as_deref(), &targets) else {
+                log::debug!("Launcher record not found: {}", usage.key);
+                report.applications_not_found.push(usage.key.clone());
+                continue;
+            };
+            if !dry_run {
+                self.application_repository.merge_usage(
+                    target.app_id,
+                    usage.usage_count,
+                    usage.last_used,
+                    false,
+                )?;
+            }
+            let matched_by_path = usage
+                .path
+                .as_deref()
+                .is_some_and(|path| path.eq_ignore_ascii_case(target.path));
+            report.applications.push(ImportedApplication {
+                name: target.name.to_string(),
+                app_id: target.app_id.to_string(),
+                archived_app_id: usage.key.clone(),
+                remapped: !matched_by_path,
+                usage_count: usage.usage_count,
+                pinned: false,
+            });
+        }
+
+        if !dry_run {
+            if !report.settings_added.is_empty() {
+                settings
+                    .clone()
+                    .save_with_policy(self.paths.get_settings_file(), &self.policy)?;
+                self.apply_settings(settings);
+            }
+            if !report.applications.is_empty() {
+                self.reload_applications_from_repository()?;
+            }
         }
+        log::info!(
+            "Imported {} aliases and {} usage records, {} records not found",
+            report.settings_added.len(),
+            report.applications.len(),
+            report.applications_not_found.len()
+        );
+        Ok(report)
     }
 
     /// Launches the specified application using its app ID.
     ///
     /// This method retrieves the application from the cache and invokes its launch method.
-    /// If the application is not found in the cache, an error is logged.
+    /// A failed launch is recorded in the repository, so that applications that
+    /// repeatedly fail to launch are demoted in the search results.
+    ///
     /// # Arguments
     ///
     /// * `app_id` - The unique identifier of the application to launch
@@ -128,76 +729,342 @@ impl Kasuri {
     ///
     /// # Errors
     ///
-    /// Returns an error if the application cache is not initialized or if the application is not found
-    /// in the cache.
-    pub fn handle_launch_application(&self, app_id: &str) -> KasuriResult<()> {
-        let Some(app_cache) = &self.app_cache else {
-            return Err("Application cache is not initialized".into());
+    /// Returns `KasuriError::Scan` if the application cache is not initialized,
+    /// `KasuriError::ApplicationNotFound` if the application is not found in the cache,
+    /// and `KasuriError::Launch` with the recorded failure count if launching failed.
+    pub fn handle_launch_application(&mut self, app_id: &str) -> KasuriResult<()> {
+        if app_id.starts_with(web_search::APP_ID_PREFIX) {
+            return self.launch_web_search(app_id);
+        }
+        if app_id.starts_with(calculator::APP_ID_PREFIX) {
+            Self::get_calculation(app_id)?;
+            return Err(KasuriError::Action(
+                "Calculator results cannot be launched, copy them instead".to_string(),
+            ));
+        }
+        let app = self.find_application(app_id).map_err(|e| {
+            log::error!("Cannot launch application: {}", e);
+            e
+        })?;
+        log::debug!("Launching application: {}", app.name);
+
+        let failure_count = match app.launch() {
+            Ok(()) => {
+                self.update_usage(app);
+                0
+            }
+            Err(e) => {
+                let failure_count = self
+                    .application_repository
+                    .record_launch_failure(app)
+                    .unwrap_or_else(|e| {
+                        log::error!("Failed to record launch failure: {}", e);
+                        app.launch_failure_count + 1
+                    });
+                let mut failure = match e {
+                    KasuriError::Launch(failure) => failure,
+                    e => LaunchFailure::new(app, &e.to_string()),
+                };
+                failure.failure_count = failure_count;
+                self.set_launch_failure_count(app_id, failure_count);
+                return Err(KasuriError::Launch(failure));
+            }
         };
-        let app = app_cache.iter().find(|app| app.app_id == app_id);
-        if let Some(app) = app {
-            log::debug!("Launching application: {}", app.name);
-            app.launch()?;
-            let _ = self.application_repository.update_usage(&app).map_err(|e| {
-                log::error!("Failed to update application usage: {}", e);
-            });
-        } else {
-            log::error!("Application with ID {} not found in cache", app_id);
+        self.set_launch_failure_count(app_id, failure_count);
+        Ok(())
+    }
+
+    /// Removes the specified application from the repository and the cache.
+    ///
+    /// This is offered to the user when an application failed to launch.
+    /// The application is added again by the next scan if it is still found.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application to remove
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the operation
+    pub fn handle_remove_application(&mut self, app_id: &str) -> KasuriResult<()> {
+        log::info!("Removing application with ID: {}", app_id);
+        self.application_repository.delete_application(app_id)?;
+        if let Some(app_cache) = self.app_cache.as_mut() {
+            app_cache.retain(|app| app.app_id != app_id);
         }
+        self.notify_catalog_updated();
         Ok(())
     }
 
-    /// Forces a reload of applications into the cache from search paths.
+    /// Pins or unpins the specified application.
     ///
-    /// This method is typically used when the user explicitly requests a refresh
-    /// of application data, or when application data may have changed externally.
+    /// Pinned applications are ranked above other matches in the search results.
     ///
     /// # Arguments
     ///
-    /// * `app_handle` - The Tauri application handle, used to access app resources
+    /// * `app_id` - The unique identifier of the application
+    /// * `pinned` - `true` to pin the application, `false` to unpin it
     ///
     /// # Returns
     ///
-    /// A `KasuriResult<()>` indicating success or failure of the cache reload operation
-    pub fn load_applications_to_cache(
+    /// A `KasuriResult<()>` indicating success or failure of the operation
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the application is not found or if updating the repository fails
+    pub fn handle_set_pinned(&mut self, app_id: &str, pinned: bool) -> KasuriResult<()> {
+        let app = self.find_application(app_id)?;
+        log::info!("Setting pinned={} for application: {}", pinned, app.name);
+        self.application_repository.set_pinned(app_id, pinned)?;
+        if let Some(app) = self
+            .app_cache
+            .as_mut()
+            .and_then(|cache| cache.iter_mut().find(|app| app.app_id == app_id))
+        {
+            app.pinned = pinned;
+        }
+        self.notify_catalog_updated();
+        Ok(())
+    }
+
+    /// Adds an alias for the specified application and saves it to the settings.
+    ///
+    /// The alias is stored for the application ID, see `Settings::add_alias`,
+    /// so that it keeps working when the name of the application changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    /// * `alias` - The alias to add
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<SettingsChanges>` telling which settings have changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the application is not found,
+    /// `KasuriError::InvalidSettings` if the alias is empty or already used
+    /// or the aliases are locked by the policy, or the error of saving the settings file
+    pub fn handle_add_alias(&mut self, app_id: &str, alias: &str) -> KasuriResult<SettingsChanges> {
+        let app = self.find_application(app_id)?;
+        log::info!("Adding alias '{}' for application: {}", alias, app.name);
+        let mut settings = self.settings.clone();
+        settings.add_alias(&contract_path(app_id), alias)?;
+        let errors = self.policy.check(&self.settings, &settings);
+        if !errors.is_empty() {
+            return Err(KasuriError::InvalidSettings(errors));
+        }
+        settings
+            .clone()
+            .save_with_policy(self.paths.get_settings_file(), &self.policy)?;
+        Ok(self.apply_settings(settings))
+    }
+
+    /// Returns the actions available for the specified application.
+    ///
+    /// The available actions depend on the kind of the application, see
+    /// `Application::get_actions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<Vec<ActionForView>>` containing the actions ready to be displayed in the UI
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the application cache is not initialized or if the application is not found
+    pub fn handle_get_application_actions(&self, app_id: &str) -> KasuriResult<Vec<ActionForView>> {
+        if app_id.starts_with(web_search::APP_ID_PREFIX) {
+            self.get_web_search_url(app_id)?;
+            return Ok(vec![ActionId::Launch.into(), ActionId::CopyUrl.into()]);
+        }
+        if app_id.starts_with(calculator::APP_ID_PREFIX) {
+            Self::get_calculation(app_id)?;
+            return Ok(vec![ActionId::CopyValue.into()]);
+        }
+        let app = self.find_application(app_id)?;
+        Ok(app
+            .get_actions()
+            .into_iter()
+            .map(ActionForView::from)
+            .collect())
+    }
+
+    /// Executes an action on the specified application.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    /// * `action_id` - The identifier of the action to execute (see `ActionId`)
+    /// * `arguments` - Optional arguments for actions that require them
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<ActionOutcome>` describing what the UI still has to do
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the application or the action is unknown, if the action
+    /// is not provided by the application, or if executing the action fails
+    pub fn handle_execute_action(
         &mut self,
-        app_handle: &tauri::AppHandle,
-    ) -> KasuriResult<()> {
+        app_id: &str,
+        action_id: &str,
+        arguments: Option<&str>,
+    ) -> KasuriResult<ActionOutcome> {
+        let action_id = action_id
+            .parse::<ActionId>()
+            .map_err(|_| KasuriError::Action(format!("Unknown action: {}", action_id)))?;
+        if app_id.starts_with(web_search::APP_ID_PREFIX) {
+            return match action_id {
+                ActionId::Launch => self.launch_web_search(app_id).map(|_| ActionOutcome::Done),
+                ActionId::CopyUrl => Ok(ActionOutcome::CopyToClipboard(
+                    self.get_web_search_url(app_id)?.1,
+                )),
+                _ => Err(KasuriError::Action(format!(
+                    "Action '{}' is not available for web searches",
+                    action_id
+                ))),
+            };
+        }
+        if app_id.starts_with(calculator::APP_ID_PREFIX) {
+            return match action_id {
+                ActionId::CopyValue => Ok(ActionOutcome::CopyToClipboard(
+                    Self::get_calculation(app_id)?.value,
+                )),
+                _ => Err(KasuriError::Action(format!(
+                    "Action '{}' is not available for calculator results",
+                    action_id
+                ))),
+            };
+        }
+        let app = self.find_application(app_id)?;
+        if !app.get_actions().contains(&action_id) {
+            return Err(KasuriError::Action(format!(
+                "Action '{}' is not available for application '{}'",
+                action_id, app.name
+            )));
+        }
+        log::debug!("Executing action '{}' on '{}'", action_id, app.name);
+
+        match action_id {
+            ActionId::Launch => {
+                self.handle_launch_application(app_id)?;
+            }
+            ActionId::RunAsAdmin => {
+                app.launch_as_admin()?;
+                self.update_usage(app);
+            }
+            ActionId::OpenWithArguments => {
+                app.launch_with_arguments(arguments.unwrap_or_default())?;
+                self.update_usage(app);
+            }
+            ActionId::OpenFolder => app.open_containing_folder()?,
+            ActionId::CopyPath => return Ok(ActionOutcome::CopyToClipboard(app.path.clone())),
+            ActionId::CopyTarget => {
+                return Ok(ActionOutcome::CopyToClipboard(app.get_shortcut_target()?));
+            }
+            // Only web searches and calculator results have these, they are handled above.
+            ActionId::CopyUrl | ActionId::CopyValue => {}
+            // The UI adds aliases with `handle_add_alias`, which returns the changed settings.
+            ActionId::AddAlias => {
+                return Err(KasuriError::Action(
+                    "Aliases are added with add_application_alias".to_string(),
+                ));
+            }
+            ActionId::Properties => app.show_properties()?,
+            ActionId::ForgetUsage => {
+                self.application_repository.reset_usage(app)?;
+                if let Some(app) = self
+                    .app_cache
+                    .as_mut()
+                    .and_then(|cache| cache.iter_mut().find(|app| app.app_id == app_id))
+                {
+                    app.usage_recency_score = 0.0;
+                }
+                self.notify_catalog_updated();
+            }
+            ActionId::Pin => self.handle_set_pinned(app_id, true)?,
+            ActionId::Unpin => self.handle_set_pinned(app_id, false)?,
+        }
+        Ok(ActionOutcome::Done)
+    }
+
+    /// Forces a reload of applications into the cache from search paths.
+    ///
+    /// This method is typically used when the user explicitly requests a refresh
+    /// of application data, or when application data may have changed externally.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the cache reload operation
+    pub fn load_applications_to_cache(&mut self) -> KasuriResult<()> {
         log::debug!("Forcing reload of applications into cache");
-        self.load_applications_from_search_path(app_handle)?;
+        self.load_applications_from_search_path()?;
         let mut applications = self.load_application_from_repository()?;
-        self.setup_applications_icon_path(&mut applications, app_handle)?;
+        self.setup_applications_icon_path(&mut applications);
         self.set_app_cache(applications)?;
         Ok(())
     }
 
+    /// Loads the applications from the repository into the cache without rescanning.
+    ///
+    /// Used after the usage statistics in the repository have been changed.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the reload
+    fn reload_applications_from_repository(&mut self) -> KasuriResult<()> {
+        let mut applications = self.load_application_from_repository()?;
+        self.setup_applications_icon_path(&mut applications);
+        self.set_app_cache(applications)
+    }
+
+    /// Opens the database of the active profile and loads the applications into it.
+    ///
+    /// The search paths are always scanned, because the applications in the database
+    /// date from the last time the profile was active, and the profile may have
+    /// other search paths than the settings it replaces.
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<()>` indicating success or failure of the switch.
+    /// The previous database is kept if the new one cannot be opened.
+    fn switch_database(&mut self) -> KasuriResult<()> {
+        let database_file = self
+            .paths
+            .get_profile_database_file(self.settings.get_usage_profile());
+        log::info!("Switching database to: {}", database_file.display());
+        let repositories = RepositoryInitializer::new(&database_file).get_repositories()?;
+        self.application_repository = repositories.application_repository;
+        self.kasuri_repository = repositories.kasuri_repository;
+        self.load_applications_to_cache()
+    }
+
     /// Loads applications from search paths only if needed based on time interval.
     ///
     /// This method checks if a new application search is needed based on the time
     /// since the last search. If not needed, it loads applications from the repository.
     /// If needed, it performs a full search of the file system for applications.
     ///
-    /// # Arguments
-    ///
-    /// * `app_handle` - The Tauri application handle, used to access app resources
-    ///
     /// # Returns
     ///
     /// A `KasuriResult<Vec<Application>>` containing the loaded applications or an error
-    fn load_applications_from_search_path_if_needed(
-        &self,
-        app_handle: &tauri::AppHandle,
-    ) -> KasuriResult<Vec<Application>> {
+    fn load_applications_from_search_path_if_needed(&self) -> KasuriResult<Vec<Application>> {
         let mut applications: Vec<Application>;
 
         if self.is_search_application_needed() {
             log::debug!("Application search needed, scanning search paths");
-            self.load_applications_from_search_path(app_handle)?;
+            self.load_applications_from_search_path()?;
         }
 
         log::debug!("Application search not needed, loading from repository");
         applications = self.load_application_from_repository()?;
-        self.setup_applications_icon_path(&mut applications, app_handle)?;
+        self.setup_applications_icon_path(&mut applications);
 
         Ok(applications)
     }
@@ -218,21 +1085,16 @@ impl Kasuri {
     /// Loads applications from all configured search paths.
     ///
     /// This method scans all directories specified in the application settings,
-    /// collects application data, updates the repository, and sets up icon paths.
-    ///
-    /// # Arguments
-    ///
-    /// * `app_handle` - The Tauri application handle, used to access app resources
+    /// adds the custom entries of the settings,
+    /// collects application data, updates the repository, and creates the icons.
+    /// Failing to create the icons is logged but does not fail the scan.
     ///
     /// # Returns
     ///
-    /// A `KasuriResult<Vec<Application>>` containing the loaded applications or an error
-    fn load_applications_from_search_path(
-        &self,
-        app_handle: &tauri::AppHandle,
-    ) -> KasuriResult<()> {
+    /// A `KasuriResult<()>` indicating success or failure of the scan
+    fn load_applications_from_search_path(&self) -> KasuriResult<()> {
         log::debug!("Beginning application scan from configured search paths");
-        let cache_path = self.get_app_cache_path(app_handle)?;
+        let cache_path = self.paths.get_cache_dir().to_string_lossy().to_string();
         // Load applications from the specified paths
         let search_path_applications: Vec<Application> = self
             .settings
@@ -248,6 +1110,12 @@ impl Kasuri {
                     Application::from_path(path)
                 }
             })
+            .chain(
+                self.settings
+                    .get_custom_entries()
+                    .iter()
+                    .map(Application::from_custom_entry),
+            )
             .collect();
         log::debug!("Updating last application search time");
         self.kasuri_repository.set_last_application_search_time()?;
@@ -256,15 +1124,21 @@ impl Kasuri {
             "Found {} applications, updating repository",
             search_path_applications.len()
         );
-        let new_applications = self
+        // Custom entries have no file to extract an icon from.
+        let new_applications: Vec<&Application> = self
             .application_repository
-            .renew_applications(&search_path_applications[..])?;
+            .renew_applications(&search_path_applications[..])?
+            .into_iter()
+            .filter(|app| app.get_kind() != ApplicationKind::Custom)
+            .collect();
 
         log::debug!(
             "Creating application icons for {} new applications",
             new_applications.len()
         );
-        Application::create_app_icon(new_applications, &cache_path)?;
+        if let Err(e) = Application::create_app_icon(new_applications, &cache_path) {
+            log::warn!("Failed to create application icons: {}", e);
+        }
         Ok(())
     }
 
@@ -272,58 +1146,81 @@ impl Kasuri {
     ///
     /// This method updates each application's icon_path field to point to the
     /// correct location in the application cache directory.
+    /// Icons overridden in the settings and the icons of custom entries are copied
+    /// to the cache directory, see `copy_override_icon`, and used instead of the extracted icons.
+    /// Custom entries without an icon get no icon path.
     ///
     /// # Arguments
     ///
     /// * `applications` - A mutable reference to the vector of applications to update
-    /// * `app_handle` - The Tauri application handle, used to access app resources
-    ///
-    /// # Returns
-    ///
-    /// A `KasuriResult<()>` indicating success or failure of the operation
-    fn setup_applications_icon_path(
-        &self,
-        applications: &mut Vec<Application>,
-        app_handle: &tauri::AppHandle,
-    ) -> KasuriResult<()> {
-        let cache_path = PathBuf::from_str(self.get_app_cache_path(app_handle)?.as_str())?;
+    fn setup_applications_icon_path(&self, applications: &mut [Application]) {
+        let cache_path = self.paths.get_cache_dir();
         log::debug!(
             "Setting up icon paths using cache directory: {}",
             cache_path.display()
         );
+        let overrides = self.settings.get_application_overrides();
+        let custom_entries = self.settings.get_custom_entries();
 
         applications.iter_mut().for_each(|app| {
             let icon_name = app.get_icon_name();
-            let icon_path = cache_path.join(&icon_name).to_string_lossy().to_string();
+            let override_icon = overrides
+                .iter()
+                .find(|v| !v.icon.is_empty() && v.app_id.eq_ignore_ascii_case(&app.app_id))
+                .map(|v| &v.icon)
+                .or_else(|| {
+                    custom_entries
+                        .iter()
+                        .find(|v| !v.icon.is_empty() && v.get_app_id() == app.app_id)
+                        .map(|v| &v.icon)
+                })
+                .and_then(|icon| self.copy_override_icon(icon, &icon_name));
+            let icon_path = match override_icon {
+                Some(path) => path,
+                // The window shows a placeholder for custom entries without an icon.
+                None if app.get_kind() == ApplicationKind::Custom => {
+                    app.icon_path = None;
+                    return;
+                }
+                None => cache_path.join(&icon_name),
+            };
+            let icon_path = icon_path.to_string_lossy().to_string();
 
             log::debug!("Setting icon path for '{}': {}", app.name, icon_path);
             app.icon_path = Some(icon_path);
         });
-        Ok(())
     }
 
-    /// Retrieves the application cache directory path as a string.
+    /// Copies an icon overridden in the settings to the application cache directory.
     ///
-    /// This method gets the platform-specific application cache directory
-    /// where Kasuri stores cached data like application icons.
+    /// The window may only load icons from the cache directory, so the icon is copied
+    /// next to the extracted icon each time the icon paths are set up.
     ///
     /// # Arguments
     ///
-    /// * `app_handle` - The Tauri application handle, used to access app resources
+    /// * `source` - Path of the PNG file from the settings
+    /// * `icon_name` - File name of the extracted icon, see `Application::get_icon_name`
     ///
     /// # Returns
     ///
-    /// A `KasuriResult<String>` containing the cache directory path or an error
-    fn get_app_cache_path(&self, app_handle: &tauri::AppHandle) -> KasuriResult<String> {
-        let cache_path = app_handle
-            .path()
-            .app_cache_dir()?
-            .into_os_string()
-            .into_string()
-            .unwrap();
-
-        log::debug!("Application cache path: {}", cache_path);
-        Ok(cache_path)
+    /// The path of the copied icon, or `None` if the icon could not be copied
+    fn copy_override_icon(&self, source: &str, icon_name: &str) -> Option<PathBuf> {
+        let target = self
+            .paths
+            .get_cache_dir()
+            .join(format!("override_{}", icon_name));
+        let result =
+            fs::create_dir_all(self.paths.get_cache_dir()).and_then(|_| fs::copy(source, &target));
+        match result {
+            Ok(_) => {
+                log::debug!("Copied override icon {} to {}", source, target.display());
+                Some(target)
+            }
+            Err(e) => {
+                log::warn!("Failed to copy override icon {}: {}", source, e);
+                None
+            }
+        }
     }
 
     /// Check if the application search is needed based on the last search time and interval.
@@ -361,9 +1258,98 @@ impl Kasuri {
         elapsed_time > interval_seconds
     }
 
+    /// Finds an application in the cache by its app ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    ///
+    /// # Returns
+    ///
+    /// A `KasuriResult<&Application>` containing the cached application
+    ///
+    /// # Errors
+    ///
+    /// Returns `KasuriError::Scan` if the application cache is not initialized,
+    /// or `KasuriError::ApplicationNotFound` if the application is not found in the cache.
+    fn find_application(&self, app_id: &str) -> KasuriResult<&Application> {
+        let Some(app_cache) = &self.app_cache else {
+            return Err(KasuriError::Scan(
+                "Application cache is not initialized".to_string(),
+            ));
+        };
+        app_cache
+            .iter()
+            .find(|app| app.app_id == app_id)
+            .ok_or_else(|| KasuriError::ApplicationNotFound(app_id.to_string()))
+    }
+
+    /// Updates the cached launch failure count of an application.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    /// * `failure_count` - The number of consecutive failed launches
+    fn set_launch_failure_count(&mut self, app_id: &str, failure_count: i64) {
+        if let Some(app) = self
+            .app_cache
+            .as_mut()
+            .and_then(|cache| cache.iter_mut().find(|app| app.app_id == app_id))
+        {
+            app.launch_failure_count = failure_count;
+        }
+    }
+
+    /// Records a launch of the application in the repository and notifies the subscribers.
+    ///
+    /// Failures are only logged because the launch itself already succeeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - The application that was launched
+    fn update_usage(&self, app: &Application) {
+        let _ = self.application_repository.update_usage(app).map_err(|e| {
+            log::error!("Failed to update application usage: {}", e);
+        });
+        self.notify(KasuriEvent::AppLaunched {
+            app_id: app.app_id.clone(),
+            name: app.name.clone(),
+        });
+    }
+
+    /// Publishes an event to all subscribers.
+    ///
+    /// Subscribers whose receiver has been dropped are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to publish
+    fn notify(&self, event: KasuriEvent) {
+        let mut subscribers = self.event_subscribers.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+        log::debug!(
+            "Publishing event '{}' to {} subscribers",
+            event.get_name(),
+            subscribers.len()
+        );
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Notifies the subscribers that the application catalog has changed.
+    fn notify_catalog_updated(&self) {
+        self.notify(KasuriEvent::CatalogUpdated {
+            application_count: self.get_applications().len(),
+        });
+    }
+
     /// Sets the application cache with a list of applications.
-    /// This method updates the in-memory cache of applications
-    /// and assigns aliases to applications based on the settings.
+    /// This method updates the in-memory cache of applications,
+    /// attaches the custom entries of the settings, see `apply_custom_entries`,
+    /// leaves out the applications hidden in the settings,
+    /// assigns aliases to applications based on the settings
+    /// and replaces the names overridden in the settings.
     /// # Arguments
     ///
     /// * `applications` - A vector of `Application` objects to cache
@@ -371,29 +1357,115 @@ impl Kasuri {
     /// # Returns
     ///
     /// A `KasuriResult<()>` indicating success or failure of the operation
-    fn set_app_cache(&mut self, applications: Vec<Application>) -> KasuriResult<()> {
+    fn set_app_cache(&mut self, mut applications: Vec<Application>) -> KasuriResult<()> {
         log::debug!(
             "Setting application cache with {} applications",
             applications.len()
         );
-        let alias_map = self
-            .settings
-            .get_application_name_aliases()
-            .iter()
-            .map(|v| (&v.path, v))
-            .collect::<std::collections::HashMap<_, _>>();
-        self.app_cache = Some(
-            applications
-                .into_iter()
-                .map(|mut app| {
-                    if let Some(alias) = alias_map.get(&app.path).map(|v| v.alias.clone()) {
-                        log::debug!("Setting alias '{}' for application '{}'", alias, app.name);
-                        app.alias = Some(alias);
-                    }
-                    app
-                })
-                .collect::<Vec<_>>(),
-        );
+        self.apply_custom_entries(&mut applications);
+        let hide_list = self.settings.get_application_hide_list();
+        applications.retain(|app| {
+            let hidden = hide_list.contains(&app.path);
+            if hidden {
+                log::debug!("Hiding application '{}'", app.name);
+            }
+            !hidden
+        });
+        self.apply_aliases(&mut applications);
+        self.apply_display_names(&mut applications);
+        self.app_cache = Some(applications);
+        self.notify_catalog_updated();
         Ok(())
     }
+
+    /// Assigns the aliases from the settings to the applications.
+    ///
+    /// Aliases that are no longer in the settings are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `applications` - The applications to assign the aliases to
+    fn apply_aliases(&self, applications: &mut [Application]) {
+        let entries = self.settings.get_application_name_aliases();
+        applications.iter_mut().for_each(|app| {
+            app.aliases = entries
+                .iter()
+                .filter(|entry| entry.matches(&app.app_id, &app.name, &app.path))
+                .flat_map(|entry| entry.aliases.iter().cloned())
+                .collect();
+            if !app.aliases.is_empty() {
+                log::debug!(
+                    "Setting aliases {:?} for application '{}'",
+                    app.aliases,
+                    app.name
+                );
+            }
+        });
+    }
+
+    /// Attaches the definitions of the custom entries in the settings to their applications.
+    ///
+    /// Custom entries that are no longer in the settings are removed, and entries that
+    /// have not been stored in the repository yet are added without usage statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `applications` - The applications loaded from the repository
+    fn apply_custom_entries(&self, applications: &mut Vec<Application>) {
+        let mut entries = self.settings.get_custom_entries();
+        applications.retain_mut(|app| {
+            if app.get_kind() != ApplicationKind::Custom {
+                return true;
+            }
+            match entries.iter().position(|v| v.get_app_id() == app.app_id) {
+                Some(i) => {
+                    app.custom_entry = Some(entries.remove(i));
+                    true
+                }
+                None => {
+                    log::debug!(
+                        "Removing custom entry no longer in the settings: {}",
+                        app.name
+                    );
+                    false
+                }
+            }
+        });
+        if !entries.is_empty() {
+            log::debug!(
+                "Adding custom entries not in the repository yet: {:?}",
+                entries
+            );
+            let mut added: Vec<Application> =
+                entries.iter().map(Application::from_custom_entry).collect();
+            self.setup_applications_icon_path(&mut added);
+            applications.extend(added);
+        }
+    }
+
+    /// Replaces the names of the applications with the display names from the settings.
+    ///
+    /// The original name is kept as an alias, so that the application can still be
+    /// found by it. Must be called after `apply_aliases`, which matches the original names.
+    ///
+    /// # Arguments
+    ///
+    /// * `applications` - The applications to rename
+    fn apply_display_names(&self, applications: &mut [Application]) {
+        let overrides = self.settings.get_application_overrides();
+        applications.iter_mut().for_each(|app| {
+            let Some(entry) = overrides.iter().find(|v| {
+                !v.display_name.trim().is_empty() && v.app_id.eq_ignore_ascii_case(&app.app_id)
+            }) else {
+                return;
+            };
+            log::debug!(
+                "Showing application '{}' as '{}'",
+                app.name,
+                entry.display_name
+            );
+            let name = std::mem::replace(&mut app.name, entry.display_name.trim().to_string());
+            app.aliases.push(name);
+        });
+    }
 }
@@ -3,55 +3,75 @@
 
 mod ui;
 
-use crate::ui::MenuId;
+use crate::ui::TRAY_ICON_ID;
 use crate::ui::WINDOW_ID_MAIN;
+use crate::ui::automation::start_automation_api;
 use crate::ui::command::{
-    changed_content_size, close_window, get_default_settings, get_settings, launch_application,
-    restart_app, save_settings, search_application,
+    add_application_alias, changed_content_size, close_window, execute_action,
+    get_application_actions, get_default_settings, get_settings, get_settings_backups,
+    launch_application, remove_application, rescan_applications, restart_app,
+    restore_settings_backup, save_settings, search_application,
 };
-use crate::ui::event_handler::{on_global_shortcut, on_menu_event, on_tray_icon_event};
+use crate::ui::event_handler::{
+    apply_auto_startup, handle_instance_requests, on_global_shortcut, on_menu_event, on_open_url,
+    on_second_instance, on_settings_file_changed, on_tray_icon_event, update_tray_menu,
+};
+use crate::ui::instance_args::parse_instance_args;
 use kasuri::Kasuri;
-use kasuri::KasuriResult;
+use kasuri::core::automation::default_endpoint;
 use kasuri::core::log::init_logger;
 use kasuri::core::log::set_log_level_str;
+use kasuri::core::paths::KasuriPaths;
+use kasuri::core::policy::Policy;
 use kasuri::core::settings::Settings;
+use kasuri::core::settings_watcher::{SETTINGS_WATCH_INTERVAL, SettingsWatcher};
+use kasuri::{KasuriError, KasuriResult};
 use std::sync::Mutex;
-use tauri::menu::{Menu, MenuItem};
 use tauri::{App, LogicalSize, Manager};
-use tauri_plugin_autostart::ManagerExt;
-
-/// Tray icon ID
-const TRAY_ICON_ID: &str = "main";
+use tauri_plugin_deep_link::DeepLinkExt;
 
 /// Initializes and runs the Kasuri application.
 ///
 /// This function is the main entry point for the Kasuri application.
-/// It loads settings, sets up the Tauri application with necessary plugins,
-/// initializes the Kasuri controller, and launches the UI.
+/// It sets up the Tauri application with necessary plugins, prepares the data files,
+/// loads settings, initializes the Kasuri controller, and launches the UI.
+/// The data files are only touched in `setup`, which a second instance never reaches.
+///
+/// # Arguments
+///
+/// * `paths` - The locations of the settings file, the database and the caches
 ///
 /// # Returns
 ///
 /// Returns a `KasuriResult<()>` which is `Ok(())` if the application runs and exits normally,
 /// or an error if initialization fails.
-fn run() -> KasuriResult<()> {
-    log::info!("Starting Kasuri application");
-    let settings = Settings::load().map_err(|e| format!("Failed to load settings: {}", e))?;
-    set_log_level_str(settings.get_log_level().as_str());
-
+fn run(paths: KasuriPaths) -> KasuriResult<()> {
     tauri::Builder::default()
+        // Must be registered first, so that a second instance exits before doing anything else.
+        .plugin(tauri_plugin_single_instance::init(on_second_instance))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
             search_application,
             changed_content_size,
             close_window,
             launch_application,
+            get_application_actions,
+            execute_action,
+            remove_application,
+            add_application_alias,
+            rescan_applications,
             get_settings,
             get_default_settings,
             save_settings,
+            get_settings_backups,
+            restore_settings_backup,
             restart_app,
         ])
         .setup(move |app| {
+            let (policy, settings) = prepare_data_files(&paths)?;
             log::debug!("Setup started");
             log::debug!("Settings: {:#?}", settings);
             let _ = app.handle().plugin(
@@ -64,74 +84,141 @@ fn run() -> KasuriResult<()> {
                 tauri_plugin_autostart::MacosLauncher::LaunchAgent,
                 Some(vec![]),
             ));
-            if settings.get_auto_startup() {
-                if let Err(e) = app.autolaunch().enable() {
-                    log::error!("Failed to enable autolaunch: {}", e);
-                } else {
-                    log::debug!("Autolaunch enabled successfully");
-                }
-            } else if app.autolaunch().is_enabled().unwrap_or(false) {
-                if let Err(e) = app.autolaunch().disable() {
-                    log::error!("Failed to disable autolaunch: {}", e);
-                } else {
-                    log::debug!("Autolaunch disabled successfully");
-                }
-            }
+            apply_auto_startup(app.handle(), settings.get_auto_startup());
 
-            let mut kasuri = Kasuri::with_settings(settings)?;
-            kasuri.init(app.app_handle())?;
-            create_system_tray_menu(app)?;
+            // Tauri.toml only allows icons from $APPCACHE, which differs in portable mode.
+            app.asset_protocol_scope()
+                .allow_directory(paths.get_cache_dir(), false)?;
+            let mut kasuri = Kasuri::with_settings(settings, paths)?.with_policy(policy);
+            kasuri.init()?;
+            create_system_tray_menu(app, &kasuri.settings)?;
             app.get_window(WINDOW_ID_MAIN)
                 .expect("Failed to get main window")
                 .set_size(LogicalSize::new(*(&kasuri.settings.get_width()), 100))?;
+            let automation_endpoint = kasuri
+                .settings
+                .get_automation_api_enabled()
+                .then(|| default_endpoint(&kasuri.paths));
+            let settings_file = kasuri.paths.get_settings_file().to_path_buf();
             app.manage(Mutex::new(kasuri));
+            let app_handle = app.handle().clone();
+            let settings_watcher =
+                SettingsWatcher::start(&settings_file, SETTINGS_WATCH_INTERVAL, move || {
+                    on_settings_file_changed(&app_handle)
+                })?;
+            app.manage(settings_watcher);
+            if let Some(endpoint) = automation_endpoint {
+                start_automation_api(app.handle(), &endpoint);
+            }
 
+            handle_instance_requests(app.handle(), parse_instance_args(std::env::args().skip(1)));
+            register_deep_link(app);
             Ok(())
         })
         .run(tauri::generate_context!())
-        .expect("error while running kasuri");
-    Ok(())
+        .map_err(|e| KasuriError::Platform(format!("Failed to run Kasuri: {}", e)))
+}
+
+/// Prepares the data files and loads the policy and the settings.
+///
+/// Migrates the files of a previous version, initializes the logger,
+/// creates the data directories and loads the policy and the settings.
+/// Must only be called by the primary instance, so that a second launch
+/// leaves the files of the running instance alone.
+///
+/// # Arguments
+///
+/// * `paths` - The locations of the settings file, the database and the caches
+///
+/// # Returns
+///
+/// The policy and the settings, or an error if the directories could not be created
+/// or the settings could not be loaded
+fn prepare_data_files(paths: &KasuriPaths) -> KasuriResult<(Policy, Settings)> {
+    // Migrate before the logger opens the log directory, and report afterwards.
+    let migration = paths.migrate_legacy_files();
+    init_logger(paths.get_log_dir());
+    log::info!("Starting Kasuri application");
+    match migration {
+        Ok(migrated) => migrated
+            .iter()
+            .for_each(|path| log::info!("Migrated from previous version: {}", path.display())),
+        Err(e) => log::error!("Failed to migrate files from previous version: {}", e),
+    }
+    if paths.is_portable() {
+        log::info!("Running in portable mode");
+    }
+    if let Err(e) = paths.create_directories() {
+        log::error!("Failed to create data directories: {}", e);
+        return Err(e);
+    }
+    let policy = Policy::load_or_empty(paths.get_policy_file());
+    let settings = Settings::load_or_recover(paths.get_settings_file(), &policy)
+        .map_err(|e| KasuriError::Settings(format!("Failed to load settings: {}", e)))?;
+    set_log_level_str(settings.get_log_level().as_str());
+    Ok((policy, settings))
 }
 
 /// Creates and configures the system tray menu for the application.
 ///
 /// Sets up the tray icon, menu items, and event handlers for tray interactions.
 /// Currently includes an exit menu item and double-click behavior to show the main window.
+/// The menu is built by `update_tray_menu`, which is called again when the profiles change.
 ///
 /// # Arguments
 ///
 /// * `app` - The Tauri app instance
+/// * `settings` - The settings, for the profile submenu
 ///
 /// # Returns
 ///
-/// Returns a `KasuriResult<()>` indicating success or failure of the tray setup
-fn create_system_tray_menu(app: &App) -> KasuriResult<()> {
+/// Returns a `tauri::Result<()>` indicating success or failure of the tray setup
+fn create_system_tray_menu(app: &App, settings: &Settings) -> tauri::Result<()> {
     // See Tauri.toml for basic settings.
     let tray_icon_main = app.tray_by_id(TRAY_ICON_ID).unwrap();
-    let item_exit = MenuItem::with_id(app, MenuId::Exit, "Exit", true, None::<&str>)?;
-    let item_reload = MenuItem::with_id(app, MenuId::Reload, "Reload", true, None::<&str>)?;
-    let item_open_log_dir = MenuItem::with_id(
-        app,
-        MenuId::OpenLogDir,
-        "Open Log Directory",
-        true,
-        None::<&str>,
-    )?;
-    let item_settings = MenuItem::with_id(app, MenuId::Settings, "Settings", true, None::<&str>)?;
-    let menu = Menu::with_items(
-        app,
-        &[&item_settings, &item_reload, &item_open_log_dir, &item_exit],
-    )?;
-    tray_icon_main.set_menu(Some(menu))?;
+    update_tray_menu(app.handle(), settings)?;
     tray_icon_main.on_menu_event(on_menu_event);
     tray_icon_main.on_tray_icon_event(on_tray_icon_event);
     Ok(())
 }
 
+/// Registers the `kasuri://` scheme and handles deep links.
+///
+/// The installer registers the scheme as well, registering it again at runtime
+/// keeps it working for portable installations and development builds.
+/// A deep link that started the application is handled right away.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri app instance
+fn register_deep_link(app: &App) {
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::error!("Failed to register deep link scheme: {}", e);
+    }
+    let app_handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        on_open_url(
+            &app_handle,
+            event.urls().iter().map(|url| url.to_string()).collect(),
+        )
+    });
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => on_open_url(
+            app.handle(),
+            urls.iter().map(|url| url.to_string()).collect(),
+        ),
+        Ok(None) => {}
+        Err(e) => log::error!(
+            "Failed to get the deep link that started the application: {}",
+            e
+        ),
+    }
+}
+
 /// Main function to start the Kasuri application.
 fn main() {
-    init_logger();
-    if let Err(e) = run() {
+    if let Err(e) = run(KasuriPaths::default()) {
         log::error!("Kasuri error: {}", e);
         std::process::exit(1);
     }
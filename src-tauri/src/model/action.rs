@@ -0,0 +1,186 @@
+//! Module that defines the secondary actions available on a search result.
+//!
+//! Every result can be launched, but depending on the kind of application
+//! additional actions such as opening the containing folder or running elevated
+//! are offered. Each application kind contributes its own set of actions.
+
+/// Identifiers of the actions that can be executed on a search result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionId {
+    /// Launch the application normally
+    Launch,
+    /// Launch the application with administrator privileges
+    RunAsAdmin,
+    /// Launch the application with user supplied arguments
+    OpenWithArguments,
+    /// Open the folder containing the application
+    OpenFolder,
+    /// Copy the application path to the clipboard
+    CopyPath,
+    /// Copy the shortcut target path to the clipboard
+    CopyTarget,
+    /// Copy the URL of a web search to the clipboard
+    CopyUrl,
+    /// Copy the result of a calculation to the clipboard
+    CopyValue,
+    /// Show the file properties dialog
+    Properties,
+    /// Reset the usage statistics of the application
+    ForgetUsage,
+    /// Add an alias that finds the application
+    AddAlias,
+    /// Rank the application above other matches
+    Pin,
+    /// Stop ranking the application above other matches
+    Unpin,
+}
+
+impl ActionId {
+    /// Returns the human readable label of the action shown in the UI.
+    ///
+    /// # Returns
+    ///
+    /// The label of the action as a static string
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            ActionId::Launch => "Open",
+            ActionId::RunAsAdmin => "Run as Administrator",
+            ActionId::OpenWithArguments => "Open with Arguments...",
+            ActionId::OpenFolder => "Open Containing Folder",
+            ActionId::CopyPath => "Copy Path",
+            ActionId::CopyTarget => "Copy Target Path",
+            ActionId::CopyUrl => "Copy URL",
+            ActionId::CopyValue => "Copy Value",
+            ActionId::Properties => "Properties",
+            ActionId::ForgetUsage => "Forget Usage",
+            ActionId::AddAlias => "Add Alias...",
+            ActionId::Pin => "Pin to Top",
+            ActionId::Unpin => "Unpin",
+        }
+    }
+
+    /// Returns whether the action needs additional arguments from the user.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the UI should ask for arguments before executing the action
+    pub fn requires_arguments(&self) -> bool {
+        matches!(self, ActionId::OpenWithArguments | ActionId::AddAlias)
+    }
+}
+
+/// Converts ActionId to string for use in the UI layer
+impl std::fmt::Display for ActionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionId::Launch => write!(f, "launch"),
+            ActionId::RunAsAdmin => write!(f, "run-as-admin"),
+            ActionId::OpenWithArguments => write!(f, "open-with-arguments"),
+            ActionId::OpenFolder => write!(f, "open-folder"),
+            ActionId::CopyPath => write!(f, "copy-path"),
+            ActionId::CopyTarget => write!(f, "copy-target"),
+            ActionId::CopyUrl => write!(f, "copy-url"),
+            ActionId::CopyValue => write!(f, "copy-value"),
+            ActionId::Properties => write!(f, "properties"),
+            ActionId::ForgetUsage => write!(f, "forget-usage"),
+            ActionId::AddAlias => write!(f, "add-alias"),
+            ActionId::Pin => write!(f, "pin"),
+            ActionId::Unpin => write!(f, "unpin"),
+        }
+    }
+}
+
+/// Converts string to ActionId for parsing requests from the UI layer
+impl std::str::FromStr for ActionId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "launch" => Ok(ActionId::Launch),
+            "run-as-admin" => Ok(ActionId::RunAsAdmin),
+            "open-with-arguments" => Ok(ActionId::OpenWithArguments),
+            "open-folder" => Ok(ActionId::OpenFolder),
+            "copy-path" => Ok(ActionId::CopyPath),
+            "copy-target" => Ok(ActionId::CopyTarget),
+            "copy-url" => Ok(ActionId::CopyUrl),
+            "copy-value" => Ok(ActionId::CopyValue),
+            "properties" => Ok(ActionId::Properties),
+            "forget-usage" => Ok(ActionId::ForgetUsage),
+            "add-alias" => Ok(ActionId::AddAlias),
+            "pin" => Ok(ActionId::Pin),
+            "unpin" => Ok(ActionId::Unpin),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Simplified action data structure used for passing to the UI layer.
+#[derive(serde::Serialize)]
+pub struct ActionForView {
+    /// Identifier of the action, passed back when executing it
+    pub action_id: String,
+    /// Display label of the action
+    pub name: String,
+    /// Whether the UI should ask for arguments before executing the action
+    pub requires_arguments: bool,
+}
+
+impl From<ActionId> for ActionForView {
+    fn from(action_id: ActionId) -> Self {
+        Self {
+            action_id: action_id.to_string(),
+            name: action_id.get_label().to_string(),
+            requires_arguments: action_id.requires_arguments(),
+        }
+    }
+}
+
+/// Result of an executed action that the UI layer has to complete.
+///
+/// Some actions cannot be finished by the core alone (for example writing
+/// to the clipboard requires the UI toolkit), so they are returned here.
+#[derive(Debug, PartialEq)]
+pub enum ActionOutcome {
+    /// The action was completed by the core
+    Done,
+    /// The given text should be copied to the clipboard
+    CopyToClipboard(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_id_round_trip() {
+        for action_id in [
+            ActionId::Launch,
+            ActionId::RunAsAdmin,
+            ActionId::OpenWithArguments,
+            ActionId::OpenFolder,
+            ActionId::CopyPath,
+            ActionId::CopyTarget,
+            ActionId::CopyUrl,
+            ActionId::CopyValue,
+            ActionId::Properties,
+            ActionId::ForgetUsage,
+            ActionId::AddAlias,
+            ActionId::Pin,
+            ActionId::Unpin,
+        ] {
+            assert_eq!(action_id.to_string().parse(), Ok(action_id));
+        }
+        assert_eq!(ActionId::ForgetUsage.to_string(), "forget-usage");
+        assert_eq!("forget-usage".parse(), Ok(ActionId::ForgetUsage));
+        assert_eq!("Forget Usage".parse::<ActionId>(), Err(()));
+    }
+
+    #[test]
+    fn test_action_for_view() {
+        let view = ActionForView::from(ActionId::OpenWithArguments);
+        assert_eq!(view.action_id, "open-with-arguments");
+        assert_eq!(view.name, "Open with Arguments...");
+        assert!(view.requires_arguments);
+        assert!(!ActionForView::from(ActionId::Launch).requires_arguments);
+    }
+}
@@ -2,10 +2,17 @@
 /// This module provides functionality to work with Windows applications including
 /// standard executable files, shortcuts, and Windows Store apps.
 use md5::{Digest, Md5};
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{ffi::OsStr, path::PathBuf, str::FromStr};
 
-use crate::KasuriResult;
+use crate::core::settings::CustomEntry;
+use crate::model::action::ActionId;
+use crate::model::launch_failure::LaunchFailure;
+use crate::{KasuriError, KasuriResult};
 use crate::{
     repositories::application_repository::ApplicationRepositoryRecord,
     service::powershell::{PowerShell, PowerShellResult},
@@ -14,6 +21,13 @@ use walkdir::WalkDir;
 
 const GET_STORE_APP_SCRIPT: &str = include_str!("../scripts/get_store_app.ps1");
 const SAVE_APP_ICON_SCRIPT: &str = include_str!("../scripts/save_app_icon.ps1");
+const SHOW_PROPERTIES_SCRIPT: &str = include_str!("../scripts/show_properties.ps1");
+/// Extensions of the files that a custom entry starts as a process
+const PROGRAM_EXTENSIONS: [&str; 4] = ["exe", "com", "bat", "cmd"];
+#[cfg(windows)]
+const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 /// Represents an application that can be managed and launched by the KASURI application.
 ///
@@ -23,11 +37,33 @@ const SAVE_APP_ICON_SCRIPT: &str = include_str!("../scripts/save_app_icon.ps1");
 #[derive(Clone, Debug)]
 pub struct Application {
     pub name: String,
-    pub alias: Option<String>,
+    pub aliases: Vec<String>,
     pub app_id: String,
     pub path: String,
     pub icon_path: Option<String>,
     pub usage_recency_score: f64,
+    pub launch_failure_count: i64,
+    pub pinned: bool,
+    /// The definition of a custom entry from the settings, `None` for other applications
+    pub custom_entry: Option<CustomEntry>,
+}
+
+/// Kind of an application, determined by the format of its path.
+///
+/// The kind decides how an application is launched and which
+/// secondary actions it provides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApplicationKind {
+    /// Executable file (.exe)
+    Executable,
+    /// Shortcut file (.lnk)
+    Shortcut,
+    /// Windows Store application, identified by its package full name
+    StoreApp,
+    /// Custom entry from the settings, identified by `custom:` and its name
+    Custom,
+    /// Path format that cannot be handled
+    Unknown,
 }
 
 /// Structure representing a Windows Store application.
@@ -56,11 +92,32 @@ impl Application {
     pub fn new(name: String, app_id: String, path: String) -> Self {
         Self {
             name,
-            alias: None, // Alias is optional and can be set later
+            aliases: vec![], // Aliases are optional and can be set later
             app_id,
             path,
             icon_path: None,
             usage_recency_score: 0.0, // Default score
+            launch_failure_count: 0,
+            pinned: false,
+            custom_entry: None,
+        }
+    }
+
+    /// Creates an Application instance from a custom entry of the settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The custom entry with expanded paths, see `Settings::get_custom_entries`
+    ///
+    /// # Returns
+    ///
+    /// A new `Application` whose ID and path are `custom:` followed by the name of the entry
+    pub fn from_custom_entry(entry: &CustomEntry) -> Self {
+        log::debug!("Converting custom entry '{}' to Application", entry.name);
+        let app_id = entry.get_app_id();
+        Self {
+            custom_entry: Some(entry.clone()),
+            ..Self::new(entry.name.trim().to_string(), app_id.clone(), app_id)
         }
     }
 
@@ -216,7 +273,7 @@ impl Application {
             }
             Err(e) => {
                 log::error!("Failed to create app icons: {}", e);
-                return Err(format!("Icon extraction failed: {}", e).into());
+                return Err(KasuriError::Icon(format!("Icon extraction failed: {}", e)));
             }
         }
 
@@ -243,12 +300,74 @@ impl Application {
         icon_name
     }
 
+    /// Determines the kind of the application based on its path.
+    ///
+    /// # Returns
+    ///
+    /// The `ApplicationKind` of the application
+    pub fn get_kind(&self) -> ApplicationKind {
+        match self.path.as_str() {
+            path if path.starts_with(CustomEntry::APP_ID_PREFIX) => ApplicationKind::Custom,
+            path if path.ends_with(".exe") => ApplicationKind::Executable,
+            path if path.ends_with(".lnk") => ApplicationKind::Shortcut,
+            path if !path.contains("\\") => ApplicationKind::StoreApp,
+            _ => ApplicationKind::Unknown,
+        }
+    }
+
+    /// Returns the secondary actions that this application provides.
+    ///
+    /// Each application kind contributes its own actions. File based applications
+    /// can be opened in their folder or run elevated, while Windows Store apps
+    /// only support a reduced set of actions. Every launchable application
+    /// can get an alias and be pinned or unpinned.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `ActionId` in the order they should be displayed
+    pub fn get_actions(&self) -> Vec<ActionId> {
+        let mut actions = match self.get_kind() {
+            ApplicationKind::Executable => vec![
+                ActionId::Launch,
+                ActionId::RunAsAdmin,
+                ActionId::OpenWithArguments,
+                ActionId::OpenFolder,
+                ActionId::CopyPath,
+                ActionId::Properties,
+                ActionId::ForgetUsage,
+            ],
+            ApplicationKind::Shortcut => vec![
+                ActionId::Launch,
+                ActionId::RunAsAdmin,
+                ActionId::OpenWithArguments,
+                ActionId::OpenFolder,
+                ActionId::CopyPath,
+                ActionId::CopyTarget,
+                ActionId::Properties,
+                ActionId::ForgetUsage,
+            ],
+            ApplicationKind::StoreApp => {
+                vec![ActionId::Launch, ActionId::CopyPath, ActionId::ForgetUsage]
+            }
+            ApplicationKind::Custom => vec![ActionId::Launch, ActionId::ForgetUsage],
+            ApplicationKind::Unknown => return vec![],
+        };
+        actions.push(ActionId::AddAlias);
+        actions.push(if self.pinned {
+            ActionId::Unpin
+        } else {
+            ActionId::Pin
+        });
+        actions
+    }
+
     /// Launches the application based on its path type.
     ///
     /// This method determines the appropriate launch method based on the application path:
     /// - Executable files (.exe): Launches using the system's default handler
     /// - Shortcuts (.lnk): Launches using the system's default handler
     /// - Windows Store apps: Launches using PowerShell commands
+    /// - Custom entries: Opens or starts the command of the entry
     ///
     /// # Returns
     ///
@@ -257,22 +376,29 @@ impl Application {
         log::info!("Launching application: {}", self.name);
         log::debug!("Application path: {}", self.path);
 
-        match self.path.as_str() {
-            path if path.ends_with(".exe") => {
+        match self.get_kind() {
+            ApplicationKind::Executable => {
                 log::debug!("Launching as executable (.exe) file");
                 self.launch_exe()?
             }
-            path if path.ends_with(".lnk") => {
+            ApplicationKind::Shortcut => {
                 log::debug!("Launching as shortcut (.lnk) file");
                 self.launch_lnk()?
             }
-            path if !path.contains("\\") => {
+            ApplicationKind::StoreApp => {
                 log::debug!("Launching as Windows Store app");
                 self.launch_store_app()?
             }
-            _ => {
+            ApplicationKind::Custom => {
+                log::debug!("Launching as custom entry");
+                self.launch_custom_entry()?
+            }
+            ApplicationKind::Unknown => {
                 log::error!("Invalid application path format: {}", self.path);
-                return Err("Invalid application path".into());
+                return Err(KasuriError::Launch(LaunchFailure::new(
+                    self,
+                    "Invalid application path",
+                )));
             }
         }
 
@@ -280,6 +406,164 @@ impl Application {
         Ok(())
     }
 
+    /// Launches the application with administrator privileges.
+    ///
+    /// Uses PowerShell's `Start-Process -Verb RunAs`, which shows the UAC prompt.
+    /// Only executables and shortcuts can be run elevated.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or containing an error if the launch failed
+    pub fn launch_as_admin(&self) -> KasuriResult<()> {
+        log::info!("Launching application as administrator: {}", self.name);
+        self.ensure_file_based("run as administrator")?;
+        let command = format!(
+            "Start-Process -FilePath {} -Verb RunAs",
+            PowerShell::quote(&self.path)
+        );
+        PowerShell::new().run(&command).map_err(|e| {
+            log::error!("Failed to launch '{}' as administrator: {}", self.path, e);
+            e
+        })?;
+        log::debug!("Successfully initiated elevated launch process");
+        Ok(())
+    }
+
+    /// Launches the application with the given command line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The command line arguments passed to the application as-is
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or containing an error if the launch failed
+    pub fn launch_with_arguments(&self, arguments: &str) -> KasuriResult<()> {
+        log::info!(
+            "Launching application '{}' with arguments: {}",
+            self.name,
+            arguments
+        );
+        self.ensure_file_based("open with arguments")?;
+        let command = if arguments.trim().is_empty() {
+            format!("Start-Process -FilePath {}", PowerShell::quote(&self.path))
+        } else {
+            format!(
+                "Start-Process -FilePath {} -ArgumentList {}",
+                PowerShell::quote(&self.path),
+                PowerShell::quote(arguments)
+            )
+        };
+        PowerShell::new().run(&command).map_err(|e| {
+            log::error!("Failed to launch '{}' with arguments: {}", self.path, e);
+            e
+        })?;
+        log::debug!("Successfully initiated launch process with arguments");
+        Ok(())
+    }
+
+    /// Opens the folder containing the application in the file manager.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or containing an error if the folder could not be opened
+    pub fn open_containing_folder(&self) -> KasuriResult<()> {
+        self.ensure_file_based("open containing folder")?;
+        let folder = PathBuf::from(&self.path)
+            .parent()
+            .map(|p| p.to_path_buf())
+            .ok_or_else(|| {
+                KasuriError::Action("Application path has no parent folder".to_string())
+            })?;
+        log::debug!("Opening containing folder: {}", folder.display());
+        open::that_detached(folder.as_os_str()).map_err(|e| {
+            log::error!("Failed to open folder '{}': {}", folder.display(), e);
+            e
+        })?;
+        Ok(())
+    }
+
+    /// Resolves the target path of a shortcut (.lnk) application.
+    ///
+    /// Uses the WScript.Shell COM object through PowerShell to read the shortcut.
+    ///
+    /// # Returns
+    ///
+    /// The target path of the shortcut, or an error if the application is not a shortcut
+    pub fn get_shortcut_target(&self) -> KasuriResult<String> {
+        if self.get_kind() != ApplicationKind::Shortcut {
+            return Err(KasuriError::Action(
+                "Application is not a shortcut".to_string(),
+            ));
+        }
+        let command = format!(
+            "$OutputEncoding = [Text.Encoding]::UTF8\n(New-Object -ComObject WScript.Shell).CreateShortcut({}).TargetPath",
+            PowerShell::quote(&self.path)
+        );
+        let result = PowerShell::new().run(&command)?;
+        let target = result.stdout.trim().to_string();
+        log::debug!("Resolved shortcut target '{}' -> '{}'", self.path, target);
+        if target.is_empty() {
+            return Err(KasuriError::Platform(
+                "Shortcut has no target path".to_string(),
+            ));
+        }
+        Ok(target)
+    }
+
+    /// Shows the Windows file properties dialog of the application.
+    ///
+    /// The dialog is owned by the PowerShell process, so it is spawned without
+    /// waiting and keeps running until the dialog is closed.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or containing an error if the dialog could not be shown
+    pub fn show_properties(&self) -> KasuriResult<()> {
+        self.ensure_file_based("show properties")?;
+        let path = PathBuf::from(&self.path);
+        let (Some(folder), Some(file_name)) = (path.parent(), path.file_name()) else {
+            return Err(KasuriError::Action("Invalid application path".to_string()));
+        };
+        let command = SHOW_PROPERTIES_SCRIPT
+            .replace(
+                "{FOLDER_PATH}",
+                &PowerShell::quote(&folder.to_string_lossy()),
+            )
+            .replace(
+                "{FILE_NAME}",
+                &PowerShell::quote(&file_name.to_string_lossy()),
+            );
+        PowerShell::new().spawn(&command)
+    }
+
+    /// Ensures that the application is backed by a file on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - Description of the requested action, used in the error message
+    ///
+    /// # Returns
+    ///
+    /// An error if the application is not an executable or a shortcut
+    fn ensure_file_based(&self, action: &str) -> KasuriResult<()> {
+        match self.get_kind() {
+            ApplicationKind::Executable | ApplicationKind::Shortcut => Ok(()),
+            kind => {
+                log::warn!(
+                    "Action '{}' is not supported for application '{}' ({:?})",
+                    action,
+                    self.name,
+                    kind
+                );
+                Err(KasuriError::Action(format!(
+                    "Action '{}' is not supported for this application",
+                    action
+                )))
+            }
+        }
+    }
+
     /// Launches an executable (.exe) file application.
     ///
     /// Uses the `open` crate to launch the application in a detached process.
@@ -289,6 +573,7 @@ impl Application {
     /// A Result indicating success or containing an error if the launch failed
     fn launch_exe(&self) -> KasuriResult<()> {
         log::debug!("Launching executable: {}", self.path);
+        self.ensure_file_exists()?;
         open::that_detached(OsStr::new(self.path.as_str())).map_err(|e| {
             log::error!("Failed to launch executable '{}': {}", self.path, e);
             e
@@ -306,6 +591,7 @@ impl Application {
     /// A Result indicating success or containing an error if the launch failed
     fn launch_lnk(&self) -> KasuriResult<()> {
         log::debug!("Launching shortcut: {}", self.path);
+        self.ensure_file_exists()?;
         open::that_detached(OsStr::new(self.path.as_str())).map_err(|e| {
             log::error!("Failed to launch shortcut '{}': {}", self.path, e);
             e
@@ -314,6 +600,24 @@ impl Application {
         Ok(())
     }
 
+    /// Ensures that the file of the application still exists on disk.
+    ///
+    /// Opening a missing file would otherwise fail silently in the shell.
+    ///
+    /// # Returns
+    ///
+    /// An error if the application file does not exist
+    fn ensure_file_exists(&self) -> KasuriResult<()> {
+        if !PathBuf::from(&self.path).exists() {
+            log::error!("Application file does not exist: {}", self.path);
+            return Err(KasuriError::Launch(LaunchFailure::new(
+                self,
+                &format!("File not found: {}", self.path),
+            )));
+        }
+        Ok(())
+    }
+
     /// Launches a Windows Store application.
     ///
     /// Uses PowerShell to execute a command that launches the Windows Store app
@@ -352,6 +656,98 @@ impl Application {
         Ok(())
     }
 
+    /// Launches a custom entry from the settings.
+    ///
+    /// URLs, folders and files other than programs are opened with their default handler,
+    /// see `opens_with_default_handler`. Other commands are started as a process with the
+    /// arguments, working directory and environment variables of the entry.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or containing an error if the launch failed
+    fn launch_custom_entry(&self) -> KasuriResult<()> {
+        let Some(entry) = self.custom_entry.as_ref() else {
+            log::error!("Custom entry is no longer in the settings: {}", self.app_id);
+            return Err(KasuriError::Launch(LaunchFailure::new(
+                self,
+                "Custom entry not found in the settings",
+            )));
+        };
+        if Self::opens_with_default_handler(&entry.command) {
+            log::debug!(
+                "Opening custom entry with default handler: {}",
+                entry.command
+            );
+            return open::that_detached(OsStr::new(entry.command.as_str())).map_err(|e| {
+                log::error!("Failed to open '{}': {}", entry.command, e);
+                KasuriError::Launch(LaunchFailure::new(
+                    self,
+                    &format!("Failed to open {}: {}", entry.command, e),
+                ))
+            });
+        }
+
+        log::debug!(
+            "Starting custom entry: {} {:?}",
+            entry.command,
+            entry.arguments
+        );
+        let mut command = Command::new(&entry.command);
+        command
+            .args(&entry.arguments)
+            .envs(entry.get_environment())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if !entry.working_dir.is_empty() {
+            command.current_dir(&entry.working_dir);
+        }
+        #[cfg(windows)]
+        command.creation_flags(if entry.show_console {
+            CREATE_NEW_CONSOLE
+        } else {
+            CREATE_NO_WINDOW
+        });
+        command.spawn().map_err(|e| {
+            log::error!("Failed to start '{}': {}", entry.command, e);
+            KasuriError::Launch(LaunchFailure::new(
+                self,
+                &format!("Failed to start {}: {}", entry.command, e),
+            ))
+        })?;
+        log::debug!("Successfully started custom entry process");
+        Ok(())
+    }
+
+    /// Returns whether the command of a custom entry is opened with its default handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command of the custom entry
+    ///
+    /// # Returns
+    ///
+    /// `true` for URLs such as `https://` or `mailto:`, UNC paths, folders,
+    /// and files that are not programs; `false` for programs and scripts in `PROGRAM_EXTENSIONS`
+    /// and for names that are looked up in `PATH`
+    pub fn opens_with_default_handler(command: &str) -> bool {
+        // A scheme has at least two characters, so a drive letter such as `C:` is not one.
+        let is_url = command.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.len() > 1
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        });
+        let path = Path::new(command);
+        let is_document = path.extension().is_some_and(|ext| {
+            !PROGRAM_EXTENSIONS
+                .iter()
+                .any(|program| ext.eq_ignore_ascii_case(program))
+        });
+        is_url || command.starts_with(r"\\") || path.is_dir() || is_document
+    }
+
     /// Converts a WindowsStoreApp instance to an Application instance.
     ///
     /// # Arguments
@@ -401,11 +797,137 @@ impl From<ApplicationRepositoryRecord> for Application {
         let usage_recency_score = record.usage_count as f64 / (days_since_last_used as f64 + 1.0);
         Self {
             name: record.name,
-            alias: None,
+            aliases: vec![],
             app_id: record.app_id,
             path: record.path,
             icon_path: None,
             usage_recency_score,
+            launch_failure_count: record.launch_failure_count,
+            pinned: record.pinned,
+            custom_entry: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_entry_kind_and_actions() {
+        let entry = CustomEntry {
+            name: " Jira ".to_string(),
+            command: "https://example.atlassian.net".to_string(),
+            ..Default::default()
+        };
+        let app = Application::from_custom_entry(&entry);
+        assert_eq!(app.name, "Jira");
+        assert_eq!(app.app_id, "custom:Jira");
+        assert_eq!(app.get_kind(), ApplicationKind::Custom);
+        assert_eq!(
+            app.get_actions(),
+            vec![
+                ActionId::Launch,
+                ActionId::ForgetUsage,
+                ActionId::AddAlias,
+                ActionId::Pin,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_actions_by_kind() {
+        let app =
+            |path: &str| Application::new("App".to_string(), path.to_string(), path.to_string());
+        let exe = app(r"C:\Apps\app.exe");
+        assert_eq!(exe.get_kind(), ApplicationKind::Executable);
+        assert_eq!(
+            exe.get_actions(),
+            vec![
+                ActionId::Launch,
+                ActionId::RunAsAdmin,
+                ActionId::OpenWithArguments,
+                ActionId::OpenFolder,
+                ActionId::CopyPath,
+                ActionId::Properties,
+                ActionId::ForgetUsage,
+                ActionId::AddAlias,
+                ActionId::Pin,
+            ]
+        );
+        let lnk = app(r"C:\Apps\app.lnk");
+        assert_eq!(lnk.get_kind(), ApplicationKind::Shortcut);
+        assert_eq!(
+            lnk.get_actions(),
+            vec![
+                ActionId::Launch,
+                ActionId::RunAsAdmin,
+                ActionId::OpenWithArguments,
+                ActionId::OpenFolder,
+                ActionId::CopyPath,
+                ActionId::CopyTarget,
+                ActionId::Properties,
+                ActionId::ForgetUsage,
+                ActionId::AddAlias,
+                ActionId::Pin,
+            ]
+        );
+        let store = app("Microsoft.WindowsCalculator_8wekyb3d8bbwe!App");
+        assert_eq!(store.get_kind(), ApplicationKind::StoreApp);
+        assert_eq!(
+            store.get_actions(),
+            vec![
+                ActionId::Launch,
+                ActionId::CopyPath,
+                ActionId::ForgetUsage,
+                ActionId::AddAlias,
+                ActionId::Pin,
+            ]
+        );
+        let unknown = app(r"C:\Apps\readme.txt");
+        assert_eq!(unknown.get_kind(), ApplicationKind::Unknown);
+        assert!(unknown.get_actions().is_empty());
+    }
+
+    #[test]
+    fn test_actions_toggle_pin() {
+        let mut app = Application::new(
+            "App".to_string(),
+            r"C:\Apps\app.exe".to_string(),
+            r"C:\Apps\app.exe".to_string(),
+        );
+        assert_eq!(app.get_actions().last(), Some(&ActionId::Pin));
+        assert!(!app.get_actions().contains(&ActionId::Unpin));
+        app.pinned = true;
+        assert_eq!(app.get_actions().last(), Some(&ActionId::Unpin));
+        assert!(!app.get_actions().contains(&ActionId::Pin));
+    }
+
+    #[test]
+    fn test_opens_with_default_handler() {
+        for command in [
+            "https://example.atlassian.net",
+            "mailto:team@example.com",
+            r"\\fileserver\team",
+            r"C:\docs\notes.txt",
+            env!("CARGO_MANIFEST_DIR"),
+        ] {
+            assert!(
+                Application::opens_with_default_handler(command),
+                "{}",
+                command
+            );
+        }
+        for command in [
+            r"C:\tools\deploy.cmd",
+            r"C:\Program Files\App\app.EXE",
+            "powershell",
+        ] {
+            assert!(
+                !Application::opens_with_default_handler(command),
+                "{}",
+                command
+            );
         }
     }
 }
@@ -0,0 +1,45 @@
+//! Module that defines the events published by the Kasuri core.
+//!
+//! Events are delivered to every subscriber registered with `Kasuri::subscribe`,
+//! for example the connections of the automation API.
+
+use serde::Serialize;
+
+/// Events that happen in the Kasuri core.
+///
+/// The event is serialized without its name, which is available from `get_name`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum KasuriEvent {
+    /// An application was launched successfully
+    AppLaunched {
+        /// Unique identifier of the launched application
+        app_id: String,
+        /// Display name of the launched application
+        name: String,
+    },
+    /// The application catalog was rescanned or an application was changed or removed
+    CatalogUpdated {
+        /// Number of applications in the catalog
+        application_count: usize,
+    },
+}
+
+/// Name of the event published when an application was launched
+pub const EVENT_NAME_APP_LAUNCHED: &str = "app_launched";
+/// Name of the event published when the application catalog was updated
+pub const EVENT_NAME_CATALOG_UPDATED: &str = "catalog_updated";
+
+impl KasuriEvent {
+    /// Returns the name of the event.
+    ///
+    /// # Returns
+    ///
+    /// The name of the event as a static string, e.g. `app_launched`
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            KasuriEvent::AppLaunched { .. } => EVENT_NAME_APP_LAUNCHED,
+            KasuriEvent::CatalogUpdated { .. } => EVENT_NAME_CATALOG_UPDATED,
+        }
+    }
+}
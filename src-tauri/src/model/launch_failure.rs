@@ -0,0 +1,53 @@
+//! Module that describes why launching an application failed.
+//!
+//! Launch failures are reported back to the UI, so that the user can
+//! rescan the applications or remove a broken entry.
+
+use crate::model::application::Application;
+
+/// Details about a failed application launch, passed to the UI layer.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LaunchFailure {
+    /// Unique identifier of the application that failed to launch
+    pub app_id: String,
+    /// Display name of the application, if it is known
+    pub name: Option<String>,
+    /// Human readable description of the failure
+    pub message: String,
+    /// Number of consecutive failed launches recorded for the application
+    pub failure_count: i64,
+}
+
+impl LaunchFailure {
+    /// Creates a failure for the given application.
+    ///
+    /// The failure count is filled in when the failure is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - The application that failed to launch
+    /// * `message` - Description of the failure
+    ///
+    /// # Returns
+    ///
+    /// A new `LaunchFailure` for the application
+    pub fn new(application: &Application, message: &str) -> Self {
+        Self {
+            app_id: application.app_id.clone(),
+            name: Some(application.name.clone()),
+            message: message.to_string(),
+            failure_count: application.launch_failure_count,
+        }
+    }
+}
+
+impl std::fmt::Display for LaunchFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to launch application '{}': {}",
+            self.name.as_deref().unwrap_or(&self.app_id),
+            self.message
+        )
+    }
+}
@@ -1,4 +1,8 @@
+pub mod action;
 pub mod application;
+pub mod event;
+pub mod launch_failure;
+pub mod stats;
 
 /// Simplified application data structure used for passing to the UI layer.
 ///
@@ -13,3 +17,16 @@ pub struct AppForView {
     /// Path to the application icon
     pub icon_path: String,
 }
+
+/// Settings data structure used for passing to the settings window.
+///
+/// The settings are sent as they are, together with the names of the
+/// settings that are locked by the machine-wide policy.
+#[derive(serde::Serialize)]
+pub struct SettingsForView {
+    /// The settings in use
+    #[serde(flatten)]
+    pub settings: crate::core::settings::Settings,
+    /// Names of the settings enforced by the policy, as in the settings file
+    pub locked_fields: Vec<String>,
+}
@@ -0,0 +1,35 @@
+//! Module that describes usage statistics of the application catalog.
+//!
+//! The statistics are used by the command line interface to inspect
+//! the database and the ranking without opening the window.
+
+/// Maximum number of applications listed in `KasuriStats::most_used_applications`.
+pub const MOST_USED_APPLICATION_LIMIT: usize = 10;
+
+/// Statistics about the applications known to KASURI.
+#[derive(Debug, serde::Serialize)]
+pub struct KasuriStats {
+    /// Number of applications in the catalog
+    pub application_count: usize,
+    /// Number of applications that have been launched at least once
+    pub used_application_count: usize,
+    /// Number of applications whose last launch failed
+    pub failing_application_count: usize,
+    /// Time of the last application scan in seconds since the Unix epoch, 0 if never scanned
+    pub last_application_search_time: u64,
+    /// Applications with the highest usage score, best first
+    pub most_used_applications: Vec<ApplicationUsage>,
+}
+
+/// Usage of a single application.
+#[derive(Debug, serde::Serialize)]
+pub struct ApplicationUsage {
+    /// Display name of the application
+    pub name: String,
+    /// Unique identifier of the application
+    pub app_id: String,
+    /// Usage score that decays with the time since the last launch
+    pub usage_recency_score: f64,
+    /// Number of consecutive failed launches
+    pub launch_failure_count: i64,
+}
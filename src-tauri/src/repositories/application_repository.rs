@@ -16,6 +16,10 @@ pub struct ApplicationRepositoryRecord {
     pub usage_count: i64,
     /// Timestamp of the last time the application was used
     pub last_used: i64,
+    /// Number of consecutive failed launches of the application
+    pub launch_failure_count: i64,
+    /// Whether the application is pinned to the top of the search results
+    pub pinned: bool,
 }
 
 /// Repository for Application data and statistics
@@ -114,7 +118,8 @@ impl ApplicationRepository {
                 .iter()
                 .enumerate()
                 .for_each(|(i, app_id)| {
-                    let _ = statement.bind((i, app_id.as_str()));
+                    // SQLite parameters are 1-indexed
+                    let _ = statement.bind((i + 1, app_id.as_str()));
                 });
             while let Ok(Row) = statement.next() {}
         }
@@ -159,36 +164,59 @@ impl ApplicationRepository {
     ///
     /// Returns an error if the database query fails or if any row cannot be read
     pub fn get_applications(&self) -> KasuriResult<Vec<Application>> {
+        Ok(self
+            .get_application_records()?
+            .into_iter()
+            .map(Application::from)
+            .collect())
+    }
+
+    /// Retrieves the records of all applications stored in the database
+    ///
+    /// Unlike `get_applications`, the raw usage count and last used timestamp are kept,
+    /// e.g. for exporting them.
+    ///
+    /// # Returns
+    ///
+    /// A vector of ApplicationRepositoryRecord objects wrapped in KasuriResult
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database query fails or if any row cannot be read
+    pub fn get_application_records(&self) -> KasuriResult<Vec<ApplicationRepositoryRecord>> {
         let mut applications = vec![];
         log::debug!("Retrieving all applications from database");
         let mut statement = self
             .connection
-            .prepare("SELECT app_id, name, path, usage_count, last_used FROM applications")?;
+            .prepare("SELECT app_id, name, path, usage_count, last_used, launch_failure_count, pinned FROM applications")?;
         while let Ok(Row) = statement.next() {
             let app_id = statement.read::<String, _>(0)?;
             let name = statement.read::<String, _>(1)?;
             let path = statement.read::<String, _>(2)?;
             let usage_count = statement.read::<i64, _>(3)?;
             let last_used = statement.read::<i64, _>(4)?;
+            let launch_failure_count = statement.read::<i64, _>(5)?;
+            let pinned = statement.read::<i64, _>(6)? != 0;
             log::debug!(
-                "Retrieved application: app_id={}, name={}, path={}, usage_count={}, last_used={}",
+                "Retrieved application: app_id={}, name={}, path={}, usage_count={}, last_used={}, launch_failure_count={}, pinned={}",
                 app_id,
                 name,
                 path,
                 usage_count,
-                last_used
+                last_used,
+                launch_failure_count,
+                pinned
             );
 
-            applications.push(
-                (ApplicationRepositoryRecord {
-                    app_id,
-                    name,
-                    path,
-                    usage_count,
-                    last_used,
-                })
-                .into(),
-            );
+            applications.push(ApplicationRepositoryRecord {
+                app_id,
+                name,
+                path,
+                usage_count,
+                last_used,
+                launch_failure_count,
+                pinned,
+            });
         }
         log::debug!(
             "Retrieved {} applications from database",
@@ -197,6 +225,22 @@ impl ApplicationRepository {
         Ok(applications)
     }
 
+    /// Records a successful launch of an application
+    ///
+    /// This method increments the usage count, updates the last used timestamp
+    /// and clears the launch failure count of the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - The application that was launched
+    ///
+    /// # Returns
+    ///
+    /// Unit type wrapped in KasuriResult indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database update fails
     pub fn update_usage(&self, application: &Application) -> KasuriResult<()> {
         log::debug!(
             "Updating usage for application: app_id={},",
@@ -207,7 +251,7 @@ impl ApplicationRepository {
             return Ok(());
         }
         let mut statement = self.connection.prepare(
-            "UPDATE applications SET usage_count = usage_count + 1, last_used = (unixepoch()) WHERE app_id = ?",
+            "UPDATE applications SET usage_count = usage_count + 1, last_used = (unixepoch()), launch_failure_count = 0 WHERE app_id = ?",
         )?;
         statement.bind((1, application.app_id.as_str()))?;
         while let Ok(Row) = statement.next() {
@@ -219,6 +263,175 @@ impl ApplicationRepository {
         Ok(())
     }
 
+    /// Resets the usage statistics of an application
+    ///
+    /// The usage count and last used timestamp are cleared, so the application
+    /// no longer gets a ranking boost from past launches.
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - The application whose usage should be forgotten
+    ///
+    /// # Returns
+    ///
+    /// Unit type wrapped in KasuriResult indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database update fails
+    pub fn reset_usage(&self, application: &Application) -> KasuriResult<()> {
+        log::debug!(
+            "Resetting usage for application: app_id={}",
+            application.app_id
+        );
+        let mut statement = self.connection.prepare(
+            "UPDATE applications SET usage_count = 0, last_used = NULL WHERE app_id = ?",
+        )?;
+        statement.bind((1, application.app_id.as_str()))?;
+        while let Ok(Row) = statement.next() {}
+        Ok(())
+    }
+
+    /// Records a failed launch of an application
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - The application that failed to launch
+    ///
+    /// # Returns
+    ///
+    /// The number of consecutive failed launches wrapped in KasuriResult
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database update or query fails
+    pub fn record_launch_failure(&self, application: &Application) -> KasuriResult<i64> {
+        log::debug!(
+            "Recording launch failure for application: app_id={}",
+            application.app_id
+        );
+        let mut statement = self.connection.prepare(
+            "UPDATE applications SET launch_failure_count = launch_failure_count + 1, last_launch_failure = (unixepoch()) WHERE app_id = ?",
+        )?;
+        statement.bind((1, application.app_id.as_str()))?;
+        while let Ok(Row) = statement.next() {}
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT launch_failure_count FROM applications WHERE app_id = ?")?;
+        statement.bind((1, application.app_id.as_str()))?;
+        let mut failure_count = 0;
+        if let Ok(Row) = statement.next() {
+            failure_count = statement.read::<i64, _>(0)?;
+        }
+        log::debug!(
+            "Application app_id={} has failed to launch {} time(s)",
+            application.app_id,
+            failure_count
+        );
+        Ok(failure_count)
+    }
+
+    /// Pins or unpins an application
+    ///
+    /// Pinned applications are ranked above other matches in the search results.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    /// * `pinned` - `true` to pin the application, `false` to unpin it
+    ///
+    /// # Returns
+    ///
+    /// Unit type wrapped in KasuriResult indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database update fails
+    pub fn set_pinned(&self, app_id: &str, pinned: bool) -> KasuriResult<()> {
+        log::debug!(
+            "Setting pinned={} for application: app_id={}",
+            pinned,
+            app_id
+        );
+        let mut statement = self
+            .connection
+            .prepare("UPDATE applications SET pinned = ? WHERE app_id = ?")?;
+        statement.bind((1, pinned as i64))?;
+        statement.bind((2, app_id))?;
+        while let Ok(Row) = statement.next() {}
+        Ok(())
+    }
+
+    /// Adds usage statistics from another database to an application
+    ///
+    /// The usage counts are added, the later of the last used timestamps is kept
+    /// and the application is pinned if it is pinned in either database.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application
+    /// * `usage_count` - Number of times the application has been used
+    /// * `last_used` - Timestamp of the last time the application was used, 0 if never
+    /// * `pinned` - Whether the application is pinned
+    ///
+    /// # Returns
+    ///
+    /// Unit type wrapped in KasuriResult indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database update fails
+    pub fn merge_usage(
+        &self,
+        app_id: &str,
+        usage_count: i64,
+        last_used: i64,
+        pinned: bool,
+    ) -> KasuriResult<()> {
+        log::debug!(
+            "Merging usage for application: app_id={}, usage_count={}, last_used={}, pinned={}",
+            app_id,
+            usage_count,
+            last_used,
+            pinned
+        );
+        let mut statement = self.connection.prepare(
+            "UPDATE applications SET usage_count = usage_count + ?, last_used = NULLIF(MAX(IFNULL(last_used, 0), ?), 0), pinned = MAX(pinned, ?) WHERE app_id = ?",
+        )?;
+        statement.bind((1, usage_count))?;
+        statement.bind((2, last_used))?;
+        statement.bind((3, pinned as i64))?;
+        statement.bind((4, app_id))?;
+        while let Ok(Row) = statement.next() {}
+        Ok(())
+    }
+
+    /// Deletes an application from the database
+    ///
+    /// The application is added again by the next scan if it is still found in a search path.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The unique identifier of the application to delete
+    ///
+    /// # Returns
+    ///
+    /// Unit type wrapped in KasuriResult indicating success or failure
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails
+    pub fn delete_application(&self, app_id: &str) -> KasuriResult<()> {
+        log::info!("Deleting application from database: app_id={}", app_id);
+        let mut statement = self
+            .connection
+            .prepare("DELETE FROM applications WHERE app_id = ?")?;
+        statement.bind((1, app_id))?;
+        while let Ok(Row) = statement.next() {}
+        Ok(())
+    }
+
     /// Performs database migrations to ensure the schema is up to date
     ///
     /// This method checks the current database version and applies any necessary
@@ -251,6 +464,23 @@ impl ApplicationRepository {
                 )",
             )?;
         }
+        if db_version < 2 {
+            log::debug!(
+                "Adding launch failure columns to applications table as part of migration to version 2"
+            );
+            self.connection.execute(
+                "ALTER TABLE applications ADD COLUMN launch_failure_count INTEGER DEFAULT 0",
+            )?;
+            self.connection
+                .execute("ALTER TABLE applications ADD COLUMN last_launch_failure INTEGER")?;
+        }
+        if db_version < 3 {
+            log::debug!(
+                "Adding pinned column to applications table as part of migration to version 3"
+            );
+            self.connection
+                .execute("ALTER TABLE applications ADD COLUMN pinned INTEGER DEFAULT 0")?;
+        }
 
         log::debug!(
             "Database migration completed successfully to version {}",
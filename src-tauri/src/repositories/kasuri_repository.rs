@@ -1,4 +1,4 @@
-use crate::KasuriResult;
+use crate::{KasuriError, KasuriResult};
 use sqlite::{ConnectionThreadSafe, State::Row};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -61,7 +61,7 @@ impl KasuriRepository {
             .parse::<u64>()
             .map_err(|e| {
                 log::error!("Failed to parse last application search time: {}", e);
-                e
+                KasuriError::Database(format!("Invalid last application search time: {}", e))
             })?;
         log::debug!("Retrieved last application search time: {}", time);
         Ok(time)
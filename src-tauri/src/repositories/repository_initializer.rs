@@ -8,16 +8,18 @@ use crate::repositories::application_repository::ApplicationRepository;
 use crate::repositories::kasuri_repository::KasuriRepository;
 use sqlite::Connection;
 use sqlite::State::Row;
+use std::path::{Path, PathBuf};
 
-/// Name of the SQLite database file
-const DB_NAME: &str = "kasuri.db";
 /// Current database schema version
-const DB_VERSION: u32 = 1;
+const DB_VERSION: u32 = 3;
 
 /// Repository initializer responsible for setting up and managing database connections
 ///
 /// This struct handles the initialization of repositories and database version management.
-pub struct RepositoryInitializer {}
+pub struct RepositoryInitializer {
+    /// Path of the SQLite database file
+    database_file: PathBuf,
+}
 
 /// Container for all application repositories
 ///
@@ -32,12 +34,18 @@ pub struct Repositories {
 impl RepositoryInitializer {
     /// Creates a new instance of the RepositoryInitializer
     ///
+    /// # Arguments
+    ///
+    /// * `database_file` - Path of the SQLite database file
+    ///
     /// # Returns
     ///
     /// A new RepositoryInitializer instance
-    pub fn new() -> Self {
+    pub fn new(database_file: &Path) -> Self {
         log::debug!("Creating new RepositoryInitializer instance");
-        Self {}
+        Self {
+            database_file: database_file.to_path_buf(),
+        }
     }
 
     /// Initializes and returns all application repositories
@@ -51,11 +59,7 @@ impl RepositoryInitializer {
     ///
     /// * `KasuriResult<Repositories>` - A Result containing the initialized repositories or an error
     pub fn get_repositories(&self) -> KasuriResult<Repositories> {
-        let path = std::env::current_exe()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .join(DB_NAME);
+        let path = &self.database_file;
         log::info!(
             "Initializing application repositories with database: {:?}",
             path
@@ -63,7 +67,7 @@ impl RepositoryInitializer {
 
         // Open connection for version check
         log::debug!("Opening database connection for version check");
-        let connection = sqlite::Connection::open_thread_safe(&path)?;
+        let connection = sqlite::Connection::open_thread_safe(path)?;
 
         // Get current database version
         let db_version = self.get_db_version(&connection)?;
@@ -79,14 +83,14 @@ impl RepositoryInitializer {
 
         // Initialize ApplicationRepository with a new connection
         log::debug!("Opening database connection for ApplicationRepository");
-        let connection = sqlite::Connection::open_thread_safe(&path)?;
+        let connection = sqlite::Connection::open_thread_safe(path)?;
         log::debug!("Initializing ApplicationRepository");
         let application_repository =
             ApplicationRepository::with_connection(connection, db_version)?;
 
         // Update database version if needed
         log::debug!("Opening database connection for version update check");
-        let connection = sqlite::Connection::open_thread_safe(&path)?;
+        let connection = sqlite::Connection::open_thread_safe(path)?;
         if db_version < DB_VERSION {
             self.update_db_version(&connection)?;
         }
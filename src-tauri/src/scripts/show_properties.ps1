@@ -0,0 +1,24 @@
+$shell = New-Object -ComObject Shell.Application
+$folder = $shell.Namespace({FOLDER_PATH})
+if ($null -eq $folder) {
+    Write-Error "Folder not found"
+    exit 1
+}
+$item = $folder.ParseName({FILE_NAME})
+if ($null -eq $item) {
+    Write-Error "File not found"
+    exit 1
+}
+$item.InvokeVerb("properties")
+
+# The properties dialog belongs to this process and is closed when it exits,
+# so keep the process alive while the dialog is shown (at most 10 minutes).
+Start-Sleep -Seconds 1
+$deadline = (Get-Date).AddMinutes(10)
+while ((Get-Date) -lt $deadline) {
+    $process = Get-Process -Id $PID
+    if ([string]::IsNullOrEmpty($process.MainWindowTitle)) {
+        break
+    }
+    Start-Sleep -Milliseconds 500
+}
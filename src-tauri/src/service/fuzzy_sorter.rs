@@ -7,6 +7,18 @@ use std::cmp::Ordering;
 /// Applications with scores below this threshold will be filtered out.
 const MINIMUM_MATCH_SCORE: i64 = 19;
 
+/// Number of consecutive launch failures after which an application is demoted.
+const LAUNCH_FAILURE_DEMOTION_THRESHOLD: i64 = 2;
+
+/// Score subtracted from the match score for each launch failure at or above the threshold.
+const LAUNCH_FAILURE_PENALTY: i64 = 30;
+
+/// Maximum number of launch failures taken into account for the penalty.
+const LAUNCH_FAILURE_PENALTY_MAX_COUNT: i64 = 5;
+
+/// Score added to the match score of pinned applications.
+const PINNED_BONUS: i64 = 40;
+
 /// Service for fuzzy searching and sorting applications based on name relevance.
 ///
 /// This struct encapsulates the functionality needed to perform fuzzy matching
@@ -36,7 +48,8 @@ impl FuzzySorter {
     ///
     /// This method performs the following operations:
     /// 1. Calculates a fuzzy match score for each application name against the query
-    /// 2. Sorts applications by descending score (best matches first)
+    /// 2. Sorts applications by descending score (best matches first), promoting
+    ///    pinned applications and demoting applications that repeatedly failed to launch
     /// 3. Filters out applications with scores below MINIMUM_MATCH_SCORE
     ///
     /// # Arguments
@@ -65,16 +78,24 @@ impl FuzzySorter {
             .map(|app| {
                 let score = self.matcher.fuzzy_match(&app.name, query).unwrap_or(0);
                 let alias_score = app
-                    .alias
-                    .as_ref()
-                    .map_or(0, |a| self.matcher.fuzzy_match(&a, query).unwrap_or(0));
-                (app, score.max(alias_score))
+                    .aliases
+                    .iter()
+                    .filter_map(|a| self.matcher.fuzzy_match(a, query))
+                    .max()
+                    .unwrap_or(0);
+                let score = score.max(alias_score);
+                let pinned_bonus = if app.pinned { PINNED_BONUS } else { 0 };
+                (
+                    app,
+                    score,
+                    score + pinned_bonus - Self::get_launch_failure_penalty(app),
+                )
             })
             .collect::<Vec<_>>();
 
         // Sort applications by score in descending order
         log::debug!("Sorting applications by fuzzy match score");
-        applications_with_scores.sort_by(|a, b| match b.1.cmp(&a.1) {
+        applications_with_scores.sort_by(|a, b| match b.2.cmp(&a.2) {
             Ordering::Equal => {
                 b.0.usage_recency_score
                     .partial_cmp(&a.0.usage_recency_score)
@@ -87,8 +108,8 @@ impl FuzzySorter {
         let initial_count = applications_with_scores.len();
         let filtered_results = applications_with_scores
             .into_iter()
-            .filter(|(_, score)| *score > MINIMUM_MATCH_SCORE)
-            .map(|(app, score)| {
+            .filter(|(_, score, _)| *score > MINIMUM_MATCH_SCORE)
+            .map(|(app, score, _)| {
                 log::debug!(
                     "Fuzzy match score for '{}': {} (above threshold {})",
                     app.name,
@@ -108,6 +129,27 @@ impl FuzzySorter {
 
         filtered_results
     }
+
+    /// Calculates the ranking penalty for applications that repeatedly failed to launch.
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - The application to calculate the penalty for
+    ///
+    /// # Returns
+    ///
+    /// The score to subtract from the match score, 0 if the application is not demoted
+    fn get_launch_failure_penalty(application: &Application) -> i64 {
+        if application.launch_failure_count < LAUNCH_FAILURE_DEMOTION_THRESHOLD {
+            return 0;
+        }
+        let counted_failures = application
+            .launch_failure_count
+            .min(LAUNCH_FAILURE_PENALTY_MAX_COUNT)
+            - LAUNCH_FAILURE_DEMOTION_THRESHOLD
+            + 1;
+        counted_failures * LAUNCH_FAILURE_PENALTY
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +198,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fuzzy_sort_demotes_repeated_launch_failures() {
+        let mut broken = Application::new("Notepad".to_string(), "".to_string(), "".to_string());
+        broken.launch_failure_count = 3;
+        let mut once_failed =
+            Application::new("Notepad++".to_string(), "".to_string(), "".to_string());
+        once_failed.launch_failure_count = 1;
+        let applications = vec![broken, once_failed];
+        let sorter = FuzzySorter::new();
+
+        let results = sorter.sort_with_filter("notepad", &applications);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "Notepad++");
+        assert_eq!(results[1].name, "Notepad");
+    }
+
+    #[test]
+    fn test_fuzzy_sort_promotes_pinned_applications() {
+        let notepad = Application::new("Notepad".to_string(), "".to_string(), "".to_string());
+        let mut notepad_plus =
+            Application::new("Notepad++".to_string(), "".to_string(), "".to_string());
+        notepad_plus.pinned = true;
+        let calculator = Application::new("Calculator".to_string(), "".to_string(), "".to_string());
+        let applications = vec![notepad, notepad_plus, calculator];
+        let sorter = FuzzySorter::new();
+
+        let results = sorter.sort_with_filter("notepad", &applications);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "Notepad++");
+        assert_eq!(results[1].name, "Notepad");
+    }
+
+    #[test]
+    fn test_fuzzy_sort_matches_any_alias() {
+        let mut code = Application::new(
+            "Visual Studio Code".to_string(),
+            "".to_string(),
+            "".to_string(),
+        );
+        code.aliases = vec!["vsc".to_string(), "editor".to_string()];
+        let notepad = Application::new("Notepad".to_string(), "".to_string(), "".to_string());
+        let applications = vec![code, notepad];
+        let sorter = FuzzySorter::new();
+
+        for query in ["vsc", "editor"] {
+            let results = sorter.sort_with_filter(query, &applications);
+            assert_eq!(results[0].name, "Visual Studio Code");
+        }
+    }
+
     #[test]
     fn test_fuzzy_sort_empty_query() {
         let applications = vec![
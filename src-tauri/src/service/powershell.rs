@@ -1,9 +1,13 @@
-use crate::KasuriResult;
+use crate::{KasuriError, KasuriResult};
 use serde::de::DeserializeOwned;
-use std::io::{Error, ErrorKind, Write};
+use std::io::Write;
+#[cfg(windows)]
 use std::os::windows::process::CommandExt;
 use std::process::Command;
 
+/// Path of the PowerShell executable
+const POWERSHELL_PATH: &str = "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe";
+#[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 /// Service for executing PowerShell commands.
@@ -56,16 +60,7 @@ impl PowerShell {
 
         // Execute PowerShell with the script file
         log::debug!("Executing PowerShell with script file");
-        let output: std::process::Output =
-            Command::new("C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe")
-                .creation_flags(CREATE_NO_WINDOW)
-                .arg("-ExecutionPolicy")
-                .arg("Bypass")
-                .arg("-WindowStyle")
-                .arg("Hidden")
-                .arg("-File")
-                .arg(&temp_file_path)
-                .output()?;
+        let output: std::process::Output = self.command(&temp_file_path).output()?;
 
         // Clean up temporary file
         log::debug!("Cleaning up temporary script file");
@@ -90,12 +85,9 @@ impl PowerShell {
             log::error!("PowerShell stderr: {}", stderr);
             log::error!("PowerShell stdout: {}", stdout);
 
-            return Err(Box::new(Error::new(
-                ErrorKind::Other,
-                format!(
-                    "PowerShell command failed, stdout: {}, stderr: {}",
-                    stdout, stderr
-                ),
+            return Err(KasuriError::Platform(format!(
+                "PowerShell command failed, stdout: {}, stderr: {}",
+                stdout, stderr
             )));
         }
         log::debug!("PowerShell stdout length: {} bytes", stdout.len());
@@ -105,6 +97,70 @@ impl PowerShell {
             _stderr: stderr,
         })
     }
+    /// Starts a PowerShell command without waiting for it to finish.
+    ///
+    /// This is used for commands that keep running while the user interacts
+    /// with them, such as dialogs. The temporary script removes itself when done.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The PowerShell command to execute as a string
+    ///
+    /// # Returns
+    ///
+    /// * `KasuriResult<()>` - Success if the process was started, or an error
+    pub fn spawn(&self, command: &str) -> KasuriResult<()> {
+        let command = format!(
+            "{}\nRemove-Item -LiteralPath $PSCommandPath -ErrorAction SilentlyContinue",
+            command
+        );
+        let temp_file_path = self.create_temp_script(&command)?;
+        log::debug!("Spawning PowerShell with script file: {}", temp_file_path);
+        self.command(&temp_file_path).spawn()?;
+        Ok(())
+    }
+
+    /// Builds the command that executes a script file with PowerShell.
+    ///
+    /// On Windows the command is started without a console window.
+    ///
+    /// # Arguments
+    ///
+    /// * `script_path` - Path to the script file to execute
+    ///
+    /// # Returns
+    ///
+    /// * `Command` - The command, ready to be executed
+    fn command(&self, script_path: &str) -> Command {
+        let mut command = Command::new(POWERSHELL_PATH);
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+        command
+            .arg("-ExecutionPolicy")
+            .arg("Bypass")
+            .arg("-WindowStyle")
+            .arg("Hidden")
+            .arg("-File")
+            .arg(script_path);
+        command
+    }
+
+    /// Quotes a value as a single-quoted PowerShell string literal.
+    ///
+    /// Single quotes inside the value are escaped by doubling them, so the value
+    /// is never interpreted by PowerShell (no variable expansion).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to quote
+    ///
+    /// # Returns
+    ///
+    /// The quoted string literal
+    pub fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
     /// Creates a temporary PowerShell script file with the provided command.
     ///
     /// This method:
@@ -136,32 +192,32 @@ impl PowerShell {
             Ok(file) => file,
             Err(e) => {
                 log::error!("Failed to create temporary script file: {}", e);
-                return Err(Box::new(e));
+                return Err(e.into());
             }
         };
 
         // Write UTF-8 BOM (0xEF, 0xBB, 0xBF)
         if let Err(e) = file.write_all(&[0xEF, 0xBB, 0xBF]) {
             log::error!("Failed to write BOM to script file: {}", e);
-            return Err(Box::new(e));
+            return Err(e.into());
         }
 
         // Write the actual command
         if let Err(e) = file.write_all(command.as_bytes()) {
             log::error!("Failed to write command to script file: {}", e);
-            return Err(Box::new(e));
+            return Err(e.into());
         }
 
         // Flush and finalize the file
         if let Err(e) = file.flush() {
             log::error!("Failed to flush script file: {}", e);
-            return Err(Box::new(e));
+            return Err(e.into());
         }
 
         // Ensure file handle is valid
         if let Err(e) = file.try_clone() {
             log::error!("Failed to validate script file handle: {}", e);
-            return Err(Box::new(e));
+            return Err(e.into());
         }
 
         log::debug!("Temporary script file created successfully");
@@ -223,12 +279,9 @@ impl PowerShellResult {
                     log::debug!("Problematic output: {}", trimmed_output);
                 }
 
-                Err(Box::new(Error::new(
-                    ErrorKind::InvalidData,
-                    format!(
-                        "Failed to parse JSON output: {}. Output was: {}",
-                        e, trimmed_output
-                    ),
+                Err(KasuriError::Platform(format!(
+                    "Failed to parse JSON output: {}. Output was: {}",
+                    e, trimmed_output
                 )))
             }
         }
@@ -0,0 +1,50 @@
+//! Serves the local automation API from the window application.
+
+use kasuri::Kasuri;
+use kasuri::core::automation::{AutomationServer, KasuriAccess, start_server};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// The running automation API server, `None` while the API is not running
+static SERVER: Mutex<Option<AutomationServer>> = Mutex::new(None);
+
+/// Gives the automation API access to the Kasuri instance managed by Tauri.
+#[derive(Clone)]
+pub struct AppKasuriAccess(AppHandle);
+
+impl KasuriAccess for AppKasuriAccess {
+    fn with_kasuri<R>(&self, f: impl FnOnce(&mut Kasuri) -> R) -> R {
+        f(&mut self.0.state::<Mutex<Kasuri>>().lock().unwrap())
+    }
+}
+
+/// Starts the automation API on the given endpoint, unless it is already running.
+///
+/// Must be called after the Kasuri instance is managed by Tauri.
+/// A failure is logged, KASURI keeps running without the automation API.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `endpoint` - The named pipe or socket path to listen on
+pub fn start_automation_api(app: &AppHandle, endpoint: &str) {
+    let mut server = SERVER.lock().unwrap();
+    if server.is_some() {
+        log::debug!("Automation API is already running");
+        return;
+    }
+    match start_server(endpoint, AppKasuriAccess(app.clone())) {
+        Ok(started) => *server = Some(started),
+        Err(e) => log::error!("Failed to start automation API on {}: {}", endpoint, e),
+    }
+}
+
+/// Stops the automation API and disconnects its clients, if it is running.
+///
+/// The API can be started again with `start_automation_api`.
+pub fn stop_automation_api() {
+    match SERVER.lock().unwrap().take() {
+        Some(server) => server.stop(),
+        None => log::debug!("Automation API is not running"),
+    }
+}
@@ -1,9 +1,14 @@
 use super::WINDOW_ID_MAIN;
-use kasuri::Kasuri;
+use super::event_handler;
+use kasuri::core::calculator;
 use kasuri::core::settings::Settings;
-use kasuri::model::AppForView;
+use kasuri::core::settings_backup::{self, SettingsBackup};
+use kasuri::model::action::{ActionForView, ActionId, ActionOutcome};
+use kasuri::model::{AppForView, SettingsForView};
+use kasuri::{Kasuri, KasuriError};
 use std::sync::Mutex;
 use tauri::{LogicalSize, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 /// Tauri command for handling content size changes.
 ///
@@ -86,25 +91,198 @@ pub fn search_application(
 ///
 /// This function is called when the user selects an application to launch.
 /// It delegates to the Kasuri instance to handle the actual launching process.
+/// If the launch fails, the main window is shown again so that the UI can
+/// offer to rescan the applications or to remove the entry.
+/// Selecting a calculator result copies its value to the clipboard instead.
 ///
 /// # Arguments
 ///
 /// * `app_id` - The unique identifier of the application to launch
-/// * `app_state` - Tauri state containing the Kasuri instance
+/// * `app_handle` - Tauri app handle for accessing the Kasuri instance and the main window
 ///
 /// # Returns
 ///
-/// None
+/// `Ok(())` if the application was launched, or the error with the details of the failure
 #[tauri::command]
-pub fn launch_application(app_id: String, app_state: tauri::State<'_, Mutex<Kasuri>>) {
+pub fn launch_application(app_id: String, app_handle: tauri::AppHandle) -> Result<(), KasuriError> {
+    if app_id.starts_with(calculator::APP_ID_PREFIX) {
+        let app_state = app_handle.state::<Mutex<Kasuri>>();
+        return execute_action(
+            app_id,
+            ActionId::CopyValue.to_string(),
+            None,
+            app_handle.clone(),
+            app_state,
+        );
+    }
     log::debug!("Launching application with ID: {}", app_id);
-    let _ = app_state.lock().unwrap().handle_launch_application(&app_id);
+    event_handler::launch_application(&app_handle, &app_id)
+}
+
+/// Tauri command for removing an application from the application list.
+///
+/// This function is called when the user chooses to remove an entry
+/// that failed to launch.
+///
+/// # Arguments
+///
+/// * `app_id` - The unique identifier of the application to remove
+/// * `app_state` - Tauri state containing the Kasuri instance
+///
+/// # Returns
+///
+/// `Ok(())` if the application was removed successfully, or the error that occurred
+#[tauri::command]
+pub fn remove_application(
+    app_id: String,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<(), KasuriError> {
+    log::debug!("Removing application with ID: {}", app_id);
+    app_state
+        .lock()
+        .unwrap()
+        .handle_remove_application(&app_id)
+        .inspect_err(|e| log::error!("Failed to remove application: {}", e))
+}
+
+/// Tauri command for adding an alias to an application.
+///
+/// This function is called when the user adds an alias to the selected search result.
+/// The alias is saved to the settings file and the open windows are notified.
+///
+/// # Arguments
+///
+/// * `app_id` - The unique identifier of the application
+/// * `alias` - The alias to add
+/// * `app_handle` - Tauri app handle for notifying the windows
+/// * `app_state` - Tauri state containing the Kasuri instance
+///
+/// # Returns
+///
+/// `Ok(())` if the alias was added, `KasuriError::InvalidSettings` if the alias is empty,
+/// already used or locked by the policy, or the error that occurred
+#[tauri::command]
+pub fn add_application_alias(
+    app_id: String,
+    alias: String,
+    app_handle: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<(), KasuriError> {
+    log::debug!(
+        "Adding alias '{}' to application with ID: {}",
+        alias,
+        app_id
+    );
+    let changes = app_state
+        .lock()
+        .unwrap()
+        .handle_add_alias(&app_id, &alias)
+        .inspect_err(|e| log::error!("Failed to add alias: {}", e))?;
+    event_handler::apply_settings_changes(&app_handle, changes);
+    Ok(())
+}
+
+/// Tauri command for rescanning the application search paths.
+///
+/// This function is called when the user chooses to rescan the applications,
+/// for example after an application failed to launch.
+///
+/// # Arguments
+///
+/// * `app_state` - Tauri state containing the Kasuri instance
+///
+/// # Returns
+///
+/// `Ok(())` if the applications were reloaded successfully, or the error that occurred
+#[tauri::command]
+pub fn rescan_applications(app_state: tauri::State<'_, Mutex<Kasuri>>) -> Result<(), KasuriError> {
+    log::debug!("Rescanning applications");
+    app_state
+        .lock()
+        .unwrap()
+        .load_applications_to_cache()
+        .inspect_err(|e| log::error!("Failed to rescan applications: {}", e))
+}
+
+/// Tauri command for retrieving the actions available for an application.
+///
+/// This function is called when the user opens the action list of a search result.
+///
+/// # Arguments
+///
+/// * `app_id` - The unique identifier of the application
+/// * `app_state` - Tauri state containing the Kasuri instance
+///
+/// # Returns
+///
+/// A vector of actions for display in the UI, or the error that occurred
+#[tauri::command]
+pub fn get_application_actions(
+    app_id: String,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<Vec<ActionForView>, KasuriError> {
+    log::debug!("Retrieving actions for application with ID: {}", app_id);
+    app_state
+        .lock()
+        .unwrap()
+        .handle_get_application_actions(&app_id)
+        .inspect_err(|e| log::error!("Failed to retrieve application actions: {}", e))
+}
+
+/// Tauri command for executing an action on an application.
+///
+/// This function is called when the user selects an action from the action list.
+/// Actions that result in clipboard content are completed here.
+///
+/// # Arguments
+///
+/// * `app_id` - The unique identifier of the application
+/// * `action_id` - The identifier of the action to execute
+/// * `arguments` - Optional arguments for actions that require them
+/// * `app_handle` - Tauri app handle for accessing the clipboard
+/// * `app_state` - Tauri state containing the Kasuri instance
+///
+/// # Returns
+///
+/// `Ok(())` if the action was executed successfully, or the error that occurred
+#[tauri::command]
+pub fn execute_action(
+    app_id: String,
+    action_id: String,
+    arguments: Option<String>,
+    app_handle: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<(), KasuriError> {
+    log::debug!(
+        "Executing action '{}' on application with ID: {}",
+        action_id,
+        app_id
+    );
+    let outcome =
+        app_state
+            .lock()
+            .unwrap()
+            .handle_execute_action(&app_id, &action_id, arguments.as_deref());
+    match outcome {
+        Ok(ActionOutcome::Done) => Ok(()),
+        Ok(ActionOutcome::CopyToClipboard(text)) => {
+            app_handle.clipboard().write_text(text).map_err(|e| {
+                log::error!("Failed to write to clipboard: {}", e);
+                KasuriError::Platform(format!("Failed to write to clipboard: {}", e))
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to execute action '{}': {}", action_id, e);
+            Err(e)
+        }
+    }
 }
 
 /// Tauri command to retrieve the current settings of the application.
 ///
 /// This function is exposed to the frontend and allows the UI to access
-/// the current settings of the Kasuri application.
+/// the current settings of the Kasuri application, together with the
+/// settings locked by the machine-wide policy.
 ///
 /// # Arguments
 ///
@@ -112,11 +290,15 @@ pub fn launch_application(app_id: String, app_state: tauri::State<'_, Mutex<Kasu
 ///
 /// # Returns
 ///
-/// None
+/// The current settings and the names of the locked settings
 #[tauri::command]
-pub fn get_settings(app_state: tauri::State<'_, Mutex<Kasuri>>) -> Settings {
+pub fn get_settings(app_state: tauri::State<'_, Mutex<Kasuri>>) -> SettingsForView {
     log::debug!("Retrieving settings");
-    app_state.lock().unwrap().settings.clone()
+    let kasuri = app_state.lock().unwrap();
+    SettingsForView {
+        settings: kasuri.settings.clone(),
+        locked_fields: kasuri.policy.get_enforced_fields(),
+    }
 }
 
 /// Tauri command to retrieve the default settings of the application.
@@ -135,19 +317,91 @@ pub fn get_default_settings() -> Settings {
 
 /// Tauri command to set new settings for the application.
 /// This function is called when the user updates settings in the UI.
-/// It saves the new settings to the file system.
+/// It validates the new settings, saves them to the file system and applies them without a restart.
+/// Invalid settings and changes to settings locked by the policy are not saved.
 /// # Arguments
 /// * `settings` - The new settings to be saved
+/// * `app_handle` - Tauri app handle for applying the settings to the windows
+/// * `app_state` - Tauri state containing the Kasuri instance
 /// # Returns
-/// * A string indicating the result of the operation
+/// * `Ok(())` if the settings were saved, `KasuriError::InvalidSettings` with the
+///   field errors if validation failed, or the error that occurred
 #[tauri::command]
-pub fn save_settings(settings: Settings) -> bool {
+pub fn save_settings(
+    settings: Settings,
+    app_handle: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<(), KasuriError> {
     log::debug!("Setting new settings");
-    if let Err(e) = settings.save() {
-        log::error!("Failed to save settings: {}", e);
-        return false;
+    let errors = settings.validate();
+    if !errors.is_empty() {
+        log::warn!("Refusing to save invalid settings: {:?}", errors);
+        return Err(KasuriError::InvalidSettings(errors));
     }
-    true
+    let changes = {
+        let mut kasuri = app_state.lock().unwrap();
+        let errors = kasuri.policy.check(&kasuri.settings, &settings);
+        if !errors.is_empty() {
+            log::warn!(
+                "Refusing to change settings locked by the policy: {:?}",
+                errors
+            );
+            return Err(KasuriError::InvalidSettings(errors));
+        }
+        let settings_file = kasuri.paths.get_settings_file().to_path_buf();
+        settings
+            .clone()
+            .save_with_policy(&settings_file, &kasuri.policy)
+            .inspect_err(|e| log::error!("Failed to save settings: {}", e))?;
+        kasuri.apply_settings(settings)
+    };
+    event_handler::apply_settings_changes(&app_handle, changes);
+    Ok(())
+}
+
+/// Tauri command to list the backups of the settings file.
+/// # Arguments
+/// * `app_state` - Tauri state containing the Kasuri instance
+/// # Returns
+/// * The backups, newest first, or the error that occurred
+#[tauri::command]
+pub fn get_settings_backups(
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<Vec<SettingsBackup>, KasuriError> {
+    log::debug!("Listing settings backups");
+    let settings_file = app_state
+        .lock()
+        .unwrap()
+        .paths
+        .get_settings_file()
+        .to_path_buf();
+    settings_backup::list_backups(&settings_file)
+}
+
+/// Tauri command to restore a backup of the settings file.
+/// The restored settings are applied without a restart.
+/// # Arguments
+/// * `name` - File name of the backup to restore
+/// * `app_handle` - Tauri app handle for applying the settings to the windows
+/// * `app_state` - Tauri state containing the Kasuri instance
+/// # Returns
+/// * `Ok(())` if the backup was restored, or the error that occurred
+#[tauri::command]
+pub fn restore_settings_backup(
+    name: String,
+    app_handle: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<Kasuri>>,
+) -> Result<(), KasuriError> {
+    log::debug!("Restoring settings backup: {}", name);
+    let changes = {
+        let mut kasuri = app_state.lock().unwrap();
+        let settings_file = kasuri.paths.get_settings_file().to_path_buf();
+        let settings = Settings::restore_backup(&settings_file, &name, &kasuri.policy)
+            .inspect_err(|e| log::error!("Failed to restore settings backup: {}", e))?;
+        kasuri.apply_settings(settings)
+    };
+    event_handler::apply_settings_changes(&app_handle, changes);
+    Ok(())
 }
 
 /// Tauri command to restart the application.
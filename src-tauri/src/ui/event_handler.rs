@@ -1,14 +1,22 @@
-use super::{EVENT_WINDOW_SHOW, MenuId, WINDOW_ID_MAIN, WINDOW_ID_SETTINGS};
+use super::automation::{start_automation_api, stop_automation_api};
+use super::instance_args::{InstanceRequest, parse_instance_args};
+use super::{
+    EVENT_LAUNCH_FAILED, EVENT_SET_QUERY, EVENT_SETTINGS_CHANGED, EVENT_WINDOW_SHOW, MenuId,
+    TRAY_ICON_ID, WINDOW_ID_MAIN, WINDOW_ID_SETTINGS,
+};
 use global_hotkey::GlobalHotKeyEvent;
 use global_hotkey::HotKeyState;
-use kasuri::Kasuri;
-use kasuri::core::log::get_log_directory;
+use kasuri::core::automation::default_endpoint;
+use kasuri::core::deep_link::DeepLink;
+use kasuri::core::settings::{Settings, SettingsChanges};
+use kasuri::{Kasuri, KasuriResult};
 use std::sync::Mutex;
-use tauri::menu::MenuEvent;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, Submenu};
 use tauri::tray::TrayIcon;
 use tauri::tray::TrayIconEvent;
-use tauri::{AppHandle, Emitter, Manager, WebviewWindowBuilder};
-use tauri_plugin_global_shortcut::Shortcut;
+use tauri::{AppHandle, Emitter, LogicalSize, Manager, WebviewWindowBuilder, Wry};
+use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 use tauri_plugin_opener::OpenerExt;
 
 /// Handles global shortcut key events.
@@ -29,16 +37,7 @@ pub fn on_global_shortcut(app: &AppHandle, shortcut: &Shortcut, event: GlobalHot
         .expect("Failed to get main window");
     if !window.is_visible().unwrap_or(true) {
         log::debug!("Window not visible, showing window");
-        let _ = window.show();
-        if let Err(e) = window.set_enabled(true) {
-            log::error!("Failed to enable window: {}", e);
-        }
-        if let Err(e) = window.set_focus() {
-            log::error!("Failed to focus window: {}", e);
-        }
-        if let Err(e) = app.emit(EVENT_WINDOW_SHOW, ()) {
-            log::error!("Failed to emit window show event: {}", e);
-        }
+        show_main_window(app);
     } else {
         log::debug!("Window visible, hiding window");
         let _ = window.hide();
@@ -49,7 +48,7 @@ pub fn on_global_shortcut(app: &AppHandle, shortcut: &Shortcut, event: GlobalHot
 ///
 /// This function is called when a menu item is clicked.
 /// It processes the menu event based on the item ID and performs the corresponding action.
-/// Currently, it handles exit, reload, and open log directory actions.
+/// Currently, it handles exit, reload, open log directory and profile switching actions.
 pub fn on_menu_event(app: &AppHandle, event: MenuEvent) {
     match event.id.as_ref().parse::<MenuId>() {
         Err(_) => {
@@ -63,69 +62,120 @@ pub fn on_menu_event(app: &AppHandle, event: MenuEvent) {
                     app.exit(0);
                 }
                 MenuId::Reload => {
-                    app.state::<Mutex<Kasuri>>()
-                        .lock()
-                        .unwrap()
-                        .load_applications_to_cache(app)
-                        .expect("Failed to reload applications");
+                    reload_applications(app);
                 }
                 MenuId::OpenLogDir => {
-                    let log_dir = get_log_directory();
+                    let log_dir = app
+                        .state::<Mutex<Kasuri>>()
+                        .lock()
+                        .unwrap()
+                        .paths
+                        .get_log_dir()
+                        .to_path_buf();
                     log::debug!("Opening log directory: {:?}", log_dir);
                     app.opener()
                         .open_path(log_dir.to_string_lossy(), None::<&str>)
                         .expect("Failed to open log directory");
                 }
                 MenuId::Settings => {
-                    if app
-                        .windows()
-                        .iter()
-                        .any(|(label, _)| label == WINDOW_ID_SETTINGS)
-                    {
-                        log::debug!("Settings window already exists, showing it");
-                        if let Some(window) = app.get_window(WINDOW_ID_SETTINGS) {
-                            if let Err(e) = window.show() {
-                                log::error!("Failed to show settings window: {}", e);
-                            }
-                            if let Err(e) = window.set_focus() {
-                                log::error!("Failed to focus settings window: {}", e);
-                            }
-                            return;
-                        } else {
-                            log::error!("Settings window should exist... but it was not found.");
-                            unreachable!("Settings window should exist...");
-                        }
-                    }
-
-                    let window_config = app
-                        .config()
-                        .app
-                        .windows
-                        .iter()
-                        .filter(|w| w.label == WINDOW_ID_SETTINGS)
-                        .next()
-                        .expect("Settings window not found");
-
-                    if let Ok(window_builder) =
-                        WebviewWindowBuilder::from_config(app, window_config)
-                    {
-                        if let Ok(window) = window_builder.build() {
-                            log::debug!("Settings window created successfully");
-                            if let Err(e) = window.show() {
-                                log::error!("Failed to show settings window: {}", e);
-                            }
-                        } else {
-                            log::error!("Failed to build settings window");
-                        }
-                    } else {
-                        log::error!("Failed to create settings window");
-                    }
+                    open_settings_window(app);
+                }
+                MenuId::Profile(name) => {
+                    switch_profile(app, &name);
                 }
             }
         }
     }
 }
 
+/// Builds the tray menu and sets it on the tray icon.
+///
+/// The profile submenu lists the settings profiles with the active one checked.
+/// It is left out if there are no profiles.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `settings` - The settings, for the profile submenu
+///
+/// # Returns
+///
+/// Returns a `tauri::Result<()>` indicating success or failure of building the menu
+pub fn update_tray_menu(app: &AppHandle, settings: &Settings) -> tauri::Result<()> {
+    let item_exit = MenuItem::with_id(app, MenuId::Exit, "Exit", true, None::<&str>)?;
+    let item_reload = MenuItem::with_id(app, MenuId::Reload, "Reload", true, None::<&str>)?;
+    let item_open_log_dir = MenuItem::with_id(
+        app,
+        MenuId::OpenLogDir,
+        "Open Log Directory",
+        true,
+        None::<&str>,
+    )?;
+    let item_settings = MenuItem::with_id(app, MenuId::Settings, "Settings", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&item_settings, &item_reload])?;
+
+    let profile_names = settings.get_profile_names();
+    if !profile_names.is_empty() {
+        let active_profile = settings.get_active_profile().map(|v| v.name.as_str());
+        let profile_items = std::iter::once((String::new(), "Default".to_string()))
+            .chain(profile_names.into_iter().map(|name| (name.clone(), name)))
+            .map(|(name, label)| {
+                let checked = active_profile.unwrap_or_default() == name;
+                CheckMenuItem::with_id(
+                    app,
+                    MenuId::Profile(name),
+                    label,
+                    true,
+                    checked,
+                    None::<&str>,
+                )
+            })
+            .collect::<tauri::Result<Vec<_>>>()?;
+        let profile_items: Vec<&dyn IsMenuItem<Wry>> = profile_items
+            .iter()
+            .map(|item| item as &dyn IsMenuItem<Wry>)
+            .collect();
+        menu.append(&Submenu::with_items(app, "Profile", true, &profile_items)?)?;
+    }
+    menu.append_items(&[&item_open_log_dir, &item_exit])?;
+
+    let tray_icon_main = app.tray_by_id(TRAY_ICON_ID).expect("Tray icon not found");
+    tray_icon_main.set_menu(Some(menu))?;
+    Ok(())
+}
+
+/// Switches to a settings profile selected in the tray menu.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `name` - Name of the profile, empty for the general settings
+fn switch_profile(app: &AppHandle, name: &str) {
+    log::info!("Switching to profile: '{}'", name);
+    let result = app
+        .state::<Mutex<Kasuri>>()
+        .lock()
+        .unwrap()
+        .switch_profile(name);
+    let changes = result.unwrap_or_else(|e| {
+        log::error!("Failed to switch to profile '{}': {}", name, e);
+        SettingsChanges::default()
+    });
+    if !changes.profiles {
+        // Clicking a check item toggles it, restore the check marks.
+        let settings = app
+            .state::<Mutex<Kasuri>>()
+            .lock()
+            .unwrap()
+            .settings
+            .clone();
+        if let Err(e) = update_tray_menu(app, &settings) {
+            log::error!("Failed to update the tray menu: {}", e);
+        }
+    }
+    apply_settings_changes(app, changes);
+}
+
 /// Handles tray icon events.
 /// This function is called when a tray icon event occurs, such as a double-click.
 /// It currently handles double-click events to show the main window if it is hidden.
@@ -141,15 +191,7 @@ pub fn on_tray_icon_event(tray_icon: &TrayIcon, event: TrayIconEvent) {
             if let Some(window) = tray_icon.app_handle().get_window(WINDOW_ID_MAIN) {
                 if !window.is_visible().unwrap_or(true) {
                     log::debug!("Showing window on tray icon double-click");
-                    if let Err(e) = window.show() {
-                        log::error!("Failed to show window: {}", e);
-                    }
-                    if let Err(e) = window.set_focus() {
-                        log::error!("Failed to focus window: {}", e);
-                    }
-                    if let Err(e) = tray_icon.app_handle().emit(EVENT_WINDOW_SHOW, ()) {
-                        log::error!("Failed to emit window show event: {}", e);
-                    }
+                    show_main_window(tray_icon.app_handle());
                 }
             } else {
                 log::warn!("Main window not found on tray icon double-click");
@@ -158,3 +200,326 @@ pub fn on_tray_icon_event(tray_icon: &TrayIcon, event: TrayIconEvent) {
         _ => {}
     }
 }
+
+/// Handles the start of a second instance of the application.
+///
+/// The second instance exits right away and its arguments are forwarded here.
+/// Starting the application again without arguments shows the main window.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle of the running instance
+/// * `args` - The command line arguments of the second instance, including the program name
+/// * `cwd` - The working directory of the second instance
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    log::info!("Second instance started in {}: {:?}", cwd, args);
+    if args.iter().any(|arg| DeepLink::is_deep_link(arg)) {
+        log::debug!("Second instance was started by a deep link");
+        return;
+    }
+    let requests = parse_instance_args(args.into_iter().skip(1));
+    if requests.is_empty() {
+        show_main_window(app);
+        return;
+    }
+    handle_instance_requests(app, requests);
+}
+
+/// Acts on the requests passed on the command line.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `requests` - The requests to act on, in order
+pub fn handle_instance_requests(app: &AppHandle, requests: Vec<InstanceRequest>) {
+    for request in requests {
+        log::debug!("Handling instance request: {:?}", request);
+        match request {
+            InstanceRequest::Show => show_main_window(app),
+            InstanceRequest::Query(query) => {
+                show_main_window(app);
+                if let Err(e) = app.emit(EVENT_SET_QUERY, query) {
+                    log::error!("Failed to emit set query event: {}", e);
+                }
+            }
+            InstanceRequest::Reload => reload_applications(app),
+            InstanceRequest::Settings => open_settings_window(app),
+        }
+    }
+}
+
+/// Handles `kasuri://` deep links opened by other applications.
+///
+/// Each route is handled by the same code path as the corresponding
+/// command line argument, tray menu item or Tauri command.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `urls` - The opened deep links
+pub fn on_open_url(app: &AppHandle, urls: Vec<String>) {
+    for url in urls {
+        log::info!("Deep link opened: {}", url);
+        let deep_link = match url.parse::<DeepLink>() {
+            Ok(deep_link) => deep_link,
+            Err(e) => {
+                log::warn!("Ignoring deep link: {}", e);
+                continue;
+            }
+        };
+        match deep_link {
+            DeepLink::Show => show_main_window(app),
+            DeepLink::Search(query) => {
+                handle_instance_requests(app, vec![InstanceRequest::Query(query)])
+            }
+            DeepLink::Launch(app_id) => {
+                if let Err(e) = launch_application(app, &app_id) {
+                    if let Err(e) = app.emit(EVENT_LAUNCH_FAILED, e) {
+                        log::error!("Failed to emit launch failed event: {}", e);
+                    }
+                }
+            }
+            DeepLink::Reload => reload_applications(app),
+            DeepLink::Settings => open_settings_window(app),
+        }
+    }
+}
+
+/// Launches an application and shows the main window if the launch fails.
+///
+/// The window is shown so that the user can rescan the applications
+/// or remove the entry that failed to launch.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `app_id` - The unique identifier of the application to launch
+///
+/// # Returns
+///
+/// `Ok(())` if the application was launched, or the error with the details of the failure
+pub fn launch_application(app: &AppHandle, app_id: &str) -> KasuriResult<()> {
+    let result = app
+        .state::<Mutex<Kasuri>>()
+        .lock()
+        .unwrap()
+        .handle_launch_application(app_id);
+    if let Err(e) = &result {
+        log::warn!("{}", e);
+        show_main_window(app);
+    }
+    result
+}
+
+/// Handles an external change of the settings file.
+///
+/// The settings file is loaded again and the changed settings are applied.
+/// If the file cannot be loaded, the current settings are kept.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+pub fn on_settings_file_changed(app: &AppHandle) {
+    let result = app
+        .state::<Mutex<Kasuri>>()
+        .lock()
+        .unwrap()
+        .reload_settings();
+    match result {
+        Ok(changes) => apply_settings_changes(app, changes),
+        Err(e) => log::error!("Failed to reload settings, keeping current settings: {}", e),
+    }
+}
+
+/// Applies the changed settings that are handled by the UI layer.
+///
+/// Settings handled by the core (log level, aliases, search paths, profiles) have already
+/// been applied by `Kasuri::apply_settings`. The frontend is notified so that open
+/// windows show the new settings.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `changes` - The settings that have changed
+pub fn apply_settings_changes(app: &AppHandle, changes: SettingsChanges) {
+    if !changes.is_any() {
+        return;
+    }
+    let settings = app
+        .state::<Mutex<Kasuri>>()
+        .lock()
+        .unwrap()
+        .settings
+        .clone();
+    if changes.shortcut_key {
+        register_shortcut(app, settings.get_shortcut_key());
+    }
+    if changes.width {
+        resize_main_window(app, settings.get_width());
+    }
+    if changes.auto_startup {
+        apply_auto_startup(app, settings.get_auto_startup());
+    }
+    if changes.profiles
+        && let Err(e) = update_tray_menu(app, &settings)
+    {
+        log::error!("Failed to update the tray menu: {}", e);
+    }
+    if changes.automation_api_enabled {
+        if settings.get_automation_api_enabled() {
+            let endpoint = default_endpoint(&app.state::<Mutex<Kasuri>>().lock().unwrap().paths);
+            start_automation_api(app, &endpoint);
+        } else {
+            stop_automation_api();
+        }
+    }
+    if let Err(e) = app.emit(EVENT_SETTINGS_CHANGED, &settings) {
+        log::error!("Failed to emit settings changed event: {}", e);
+    }
+}
+
+/// Enables or disables starting KASURI when the user logs in.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `enabled` - Whether KASURI should start automatically
+pub fn apply_auto_startup(app: &AppHandle, enabled: bool) {
+    if enabled {
+        if let Err(e) = app.autolaunch().enable() {
+            log::error!("Failed to enable autolaunch: {}", e);
+        } else {
+            log::debug!("Autolaunch enabled successfully");
+        }
+    } else if app.autolaunch().is_enabled().unwrap_or(false) {
+        if let Err(e) = app.autolaunch().disable() {
+            log::error!("Failed to disable autolaunch: {}", e);
+        } else {
+            log::debug!("Autolaunch disabled successfully");
+        }
+    }
+}
+
+/// Replaces the registered global shortcut.
+///
+/// If the new shortcut cannot be registered, no shortcut is registered
+/// and the main window can still be opened from the tray icon.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `shortcut_key` - The new shortcut key combination
+fn register_shortcut(app: &AppHandle, shortcut_key: &str) {
+    log::info!("Registering global shortcut: {}", shortcut_key);
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        log::error!("Failed to unregister global shortcut: {}", e);
+    }
+    if let Err(e) = app.global_shortcut().register(shortcut_key) {
+        log::error!(
+            "Failed to register global shortcut '{}': {}",
+            shortcut_key,
+            e
+        );
+    }
+}
+
+/// Changes the width of the main window, keeping its height.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+/// * `width` - The new width in logical pixels
+fn resize_main_window(app: &AppHandle, width: u32) {
+    let Some(window) = app.get_window(WINDOW_ID_MAIN) else {
+        log::warn!("Main window not found");
+        return;
+    };
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    let height = window
+        .inner_size()
+        .map(|size| size.to_logical::<f64>(scale_factor).height)
+        .unwrap_or(100.0);
+    if let Err(e) = window.set_size(LogicalSize::new(width as f64, height)) {
+        log::error!("Failed to set window size: {}", e);
+    }
+}
+
+/// Shows and focuses the main window and notifies the frontend.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+pub fn show_main_window(app: &AppHandle) {
+    let Some(window) = app.get_window(WINDOW_ID_MAIN) else {
+        log::warn!("Main window not found");
+        return;
+    };
+    if let Err(e) = window.show() {
+        log::error!("Failed to show window: {}", e);
+    }
+    if let Err(e) = window.set_enabled(true) {
+        log::error!("Failed to enable window: {}", e);
+    }
+    if let Err(e) = window.set_focus() {
+        log::error!("Failed to focus window: {}", e);
+    }
+    if let Err(e) = app.emit(EVENT_WINDOW_SHOW, ()) {
+        log::error!("Failed to emit window show event: {}", e);
+    }
+}
+
+/// Rescans the applications and reloads the application cache.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+pub fn reload_applications(app: &AppHandle) {
+    if let Err(e) = app
+        .state::<Mutex<Kasuri>>()
+        .lock()
+        .unwrap()
+        .load_applications_to_cache()
+    {
+        log::error!("Failed to reload applications: {}", e);
+    }
+}
+
+/// Opens the settings window, or brings it to the front if it is already open.
+///
+/// # Arguments
+///
+/// * `app` - Tauri app handle
+pub fn open_settings_window(app: &AppHandle) {
+    if let Some(window) = app.get_window(WINDOW_ID_SETTINGS) {
+        log::debug!("Settings window already exists, showing it");
+        if let Err(e) = window.show() {
+            log::error!("Failed to show settings window: {}", e);
+        }
+        if let Err(e) = window.set_focus() {
+            log::error!("Failed to focus settings window: {}", e);
+        }
+        return;
+    }
+
+    let window_config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .filter(|w| w.label == WINDOW_ID_SETTINGS)
+        .next()
+        .expect("Settings window not found");
+
+    if let Ok(window_builder) = WebviewWindowBuilder::from_config(app, window_config) {
+        if let Ok(window) = window_builder.build() {
+            log::debug!("Settings window created successfully");
+            if let Err(e) = window.show() {
+                log::error!("Failed to show settings window: {}", e);
+            }
+        } else {
+            log::error!("Failed to build settings window");
+        }
+    } else {
+        log::error!("Failed to create settings window");
+    }
+}
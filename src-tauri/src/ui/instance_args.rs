@@ -0,0 +1,106 @@
+//! Command line arguments of the KASURI window application.
+//!
+//! Only one instance of KASURI runs at a time. When it is started again,
+//! the arguments of the new invocation are forwarded to the running instance,
+//! which acts on them as if they had been passed to it.
+
+use kasuri::core::deep_link::DeepLink;
+
+/// Requests that can be passed on the command line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstanceRequest {
+    /// Show the main window (`--show`)
+    Show,
+    /// Show the main window and search for the text (`--query <text>`)
+    Query(String),
+    /// Rescan the applications (`--reload`)
+    Reload,
+    /// Open the settings window (`--settings`)
+    Settings,
+}
+
+/// Parses the command line arguments into requests.
+///
+/// Unknown arguments are logged and ignored, so that arguments added by the
+/// system (for example by the auto startup) do not prevent KASURI from starting.
+/// `kasuri://` deep links are skipped, they are handled by the deep link plugin.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, without the program name
+///
+/// # Returns
+///
+/// The requests in the order they were given
+pub fn parse_instance_args<I>(args: I) -> Vec<InstanceRequest>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut requests = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--show" => requests.push(InstanceRequest::Show),
+            "--reload" => requests.push(InstanceRequest::Reload),
+            "--settings" => requests.push(InstanceRequest::Settings),
+            "--query" => match args.next() {
+                Some(query) => requests.push(InstanceRequest::Query(query)),
+                None => log::warn!("Missing text for --query"),
+            },
+            _ => {
+                if let Some(query) = arg.strip_prefix("--query=") {
+                    requests.push(InstanceRequest::Query(query.to_string()));
+                } else if DeepLink::is_deep_link(&arg) {
+                    log::debug!("Skipping deep link argument: {}", arg);
+                } else {
+                    log::warn!("Ignoring unknown argument: {}", arg);
+                }
+            }
+        }
+    }
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Vec<InstanceRequest> {
+        parse_instance_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_instance_args() {
+        assert_eq!(
+            parse(&["--show", "--reload", "--settings"]),
+            vec![
+                InstanceRequest::Show,
+                InstanceRequest::Reload,
+                InstanceRequest::Settings
+            ]
+        );
+        assert_eq!(parse(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse(&["--query", "visual studio"]),
+            vec![InstanceRequest::Query("visual studio".to_string())]
+        );
+        assert_eq!(
+            parse(&["--query=code"]),
+            vec![InstanceRequest::Query("code".to_string())]
+        );
+        assert_eq!(parse(&["--query"]), vec![]);
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_args() {
+        assert_eq!(
+            parse(&["--minimized", "--show"]),
+            vec![InstanceRequest::Show]
+        );
+        assert_eq!(parse(&["kasuri://reload"]), vec![]);
+    }
+}
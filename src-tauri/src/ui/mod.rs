@@ -1,5 +1,10 @@
+pub mod automation;
 pub mod command;
 pub mod event_handler;
+pub mod instance_args;
+
+/// Tray icon ID
+pub const TRAY_ICON_ID: &str = "main";
 
 /// Window ID
 pub const WINDOW_ID_MAIN: &str = "main";
@@ -11,6 +16,24 @@ pub const WINDOW_ID_SETTINGS: &str = "settings";
 /// allowing the frontend to respond appropriately.
 pub const EVENT_WINDOW_SHOW: &str = "window-show";
 
+/// Event name for setting the search query
+///
+/// This event is emitted with the query text when KASURI is started
+/// with `--query <text>`, so that the frontend searches for it.
+pub const EVENT_SET_QUERY: &str = "set-query";
+
+/// Event name for a failed launch that was not requested by the frontend
+///
+/// This event is emitted with the error when launching an application
+/// from a deep link fails, so that the frontend can show the failure.
+pub const EVENT_LAUNCH_FAILED: &str = "launch-failed";
+
+/// Event name for changed settings
+///
+/// This event is emitted with the new settings after they were saved
+/// or the settings file was edited, so that open windows can update.
+pub const EVENT_SETTINGS_CHANGED: &str = "settings-changed";
+
 /// Menu item IDs
 pub enum MenuId {
     /// Exit application
@@ -21,6 +44,8 @@ pub enum MenuId {
     OpenLogDir,
     /// Open settings
     Settings,
+    /// Switch to the settings profile with the name, empty for the general settings
+    Profile(String),
 }
 
 /// Converts MenuId to string for use in menu events
@@ -31,6 +56,7 @@ impl std::fmt::Display for MenuId {
             MenuId::Reload => write!(f, "reload"),
             MenuId::OpenLogDir => write!(f, "open-log-dir"),
             MenuId::Settings => write!(f, "settings"),
+            MenuId::Profile(name) => write!(f, "profile:{}", name),
         }
     }
 }
@@ -45,7 +71,10 @@ impl std::str::FromStr for MenuId {
             "reload" => Ok(MenuId::Reload),
             "open-log-dir" => Ok(MenuId::OpenLogDir),
             "settings" => Ok(MenuId::Settings),
-            _ => Err(()),
+            _ => s
+                .strip_prefix("profile:")
+                .map(|name| MenuId::Profile(name.to_string()))
+                .ok_or(()),
         }
     }
 }
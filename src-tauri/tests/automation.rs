@@ -0,0 +1,84 @@
+//! Integration test that talks to the automation API over a Unix domain socket.
+#![cfg(unix)]
+
+use interprocess::local_socket::prelude::*;
+use interprocess::local_socket::{GenericFilePath, Stream};
+use kasuri::Kasuri;
+use kasuri::core::automation::{default_endpoint, start_server};
+use kasuri::core::paths::KasuriPaths;
+use kasuri::core::settings::Settings;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+
+/// Creates a Kasuri instance that finds `Notepad.exe` in a temporary directory.
+fn start_kasuri(root: &std::path::Path) -> (Kasuri, KasuriPaths) {
+    let apps_dir = root.join("apps");
+    fs::create_dir_all(&apps_dir).unwrap();
+    fs::write(apps_dir.join("Notepad.exe"), b"").unwrap();
+    let paths = KasuriPaths::builder()
+        .data_dir(root)
+        .cache_dir(root.join("cache"))
+        .build();
+    fs::write(
+        paths.get_settings_file(),
+        format!(
+            "application_search_path_list = [{}]\nautomation_api_enabled = true\n",
+            toml::Value::String(apps_dir.to_string_lossy().to_string())
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load(paths.get_settings_file()).unwrap();
+    let mut kasuri = Kasuri::with_settings(settings, paths.clone()).unwrap();
+    kasuri.init().unwrap();
+    (kasuri, paths)
+}
+
+#[test]
+fn test_search_over_socket() {
+    let root = tempfile::tempdir().unwrap();
+    let (kasuri, paths) = start_kasuri(root.path());
+    let endpoint = default_endpoint(&paths);
+    start_server(&endpoint, Arc::new(Mutex::new(kasuri))).unwrap();
+
+    let name = endpoint.as_str().to_fs_name::<GenericFilePath>().unwrap();
+    let mut stream = BufReader::new(Stream::connect(name).unwrap());
+    let request =
+        json!({"jsonrpc": "2.0", "id": 1, "method": "search", "params": {"query": "notepad"}});
+    writeln!(stream.get_mut(), "{}", request).unwrap();
+    let mut line = String::new();
+    stream.read_line(&mut line).unwrap();
+
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"][0]["name"], "Notepad");
+}
+
+#[test]
+fn test_stop_disconnects_clients() {
+    let root = tempfile::tempdir().unwrap();
+    let (kasuri, paths) = start_kasuri(root.path());
+    let endpoint = default_endpoint(&paths);
+    let kasuri = Arc::new(Mutex::new(kasuri));
+    let name = endpoint.as_str().to_fs_name::<GenericFilePath>().unwrap();
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": "list"});
+
+    for _ in 0..2 {
+        // The server can be started again on the same endpoint after it has been stopped.
+        let server = start_server(&endpoint, kasuri.clone()).unwrap();
+        let mut stream = BufReader::new(Stream::connect(name.clone()).unwrap());
+        writeln!(stream.get_mut(), "{}", request).unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["result"][0]["name"], "Notepad");
+
+        server.stop();
+
+        // The connected client reads the end of the stream instead of waiting forever.
+        line.clear();
+        assert_eq!(stream.read_line(&mut line).unwrap(), 0);
+        assert!(Stream::connect(name.clone()).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Integration tests for importing data of other launchers
+//! from the files in `tests/fixtures/launchers`.
+
+use kasuri::Kasuri;
+use kasuri::core::launcher_import::Launcher;
+use kasuri::core::paths::KasuriPaths;
+use kasuri::core::settings::Settings;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Returns the directory of the fixture files of a launcher.
+fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("launchers")
+        .join(name)
+}
+
+#[test]
+fn test_read_powertoys_run() {
+    let data = Launcher::PowerToysRun
+        .read(&fixture_dir("powertoys"))
+        .unwrap();
+    assert!(data.aliases.is_empty());
+    assert_eq!(data.usage.len(), 2);
+    let notepad = data.usage.iter().find(|v| v.name == "Notepad").unwrap();
+    assert_eq!(notepad.usage_count, 5);
+    assert_eq!(notepad.last_used, 1714525200);
+    assert!(
+        notepad
+            .path
+            .as_deref()
+            .unwrap()
+            .ends_with(r"Accessories\Notepad.lnk")
+    );
+    let calculator = data.usage.iter().find(|v| v.name == "Calculator").unwrap();
+    assert_eq!(calculator.path, None);
+}
+
+#[test]
+fn test_read_wox() {
+    let data = Launcher::Wox.read(&fixture_dir("wox")).unwrap();
+    assert!(data.aliases.is_empty());
+    let code = data
+        .usage
+        .iter()
+        .find(|v| v.name == "Visual Studio Code")
+        .unwrap();
+    assert_eq!(code.usage_count, 7);
+    assert_eq!(code.last_used, 0);
+    assert!(
+        code.path
+            .as_deref()
+            .unwrap()
+            .starts_with(r"C:\Users\kasuri")
+    );
+}
+
+#[test]
+fn test_read_flow_launcher() {
+    let data = Launcher::FlowLauncher.read(&fixture_dir("flow")).unwrap();
+    assert_eq!(data.usage.len(), 2);
+    let code = data
+        .usage
+        .iter()
+        .find(|v| v.name == "Visual Studio Code")
+        .unwrap();
+    assert_eq!(code.usage_count, 12);
+    assert!(code.path.as_deref().unwrap().starts_with(r"\\fileserver"));
+    let aliases: Vec<_> = data.aliases.iter().map(|v| v.alias.as_str()).collect();
+    assert_eq!(aliases, vec!["code", "calc"]);
+}
+
+#[test]
+fn test_read_keypirinha() {
+    let data = Launcher::Keypirinha
+        .read(&fixture_dir("keypirinha"))
+        .unwrap();
+    let names: Vec<_> = data.usage.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["Notepad", "Visual Studio Code", "Calculator"]);
+    let notepad = &data.usage[0];
+    assert_eq!(notepad.usage_count, 4);
+    assert_eq!(notepad.last_used, 1714525200);
+    assert_eq!(
+        notepad.path.as_deref(),
+        Some(r"C:\Windows\System32\notepad.exe")
+    );
+    assert_eq!(data.usage[1].last_used, 0);
+    assert_eq!(data.usage[2].path, None);
+    let aliases: Vec<_> = data
+        .aliases
+        .iter()
+        .map(|v| (v.alias.as_str(), v.target.as_str()))
+        .collect();
+    assert_eq!(
+        aliases,
+        vec![("np", "Notepad"), ("code", "Visual Studio Code")]
+    );
+}
+
+#[test]
+fn test_read_missing_and_invalid_files() {
+    let root = tempfile::tempdir().unwrap();
+    assert!(Launcher::Wox.read(root.path()).is_err());
+    fs::write(
+        root.path().join("UserSelectedRecord.json"),
+        "{\"other\": {}}",
+    )
+    .unwrap();
+    assert!(Launcher::Wox.read(root.path()).is_err());
+    // Flow Launcher works without its settings file.
+    fs::write(
+        root.path().join("UserSelectedRecord.json"),
+        "{\"records\": {\"Calculator\": 1}}",
+    )
+    .unwrap();
+    let data = Launcher::FlowLauncher.read(root.path()).unwrap();
+    assert_eq!(data.usage.len(), 1);
+    assert!(data.aliases.is_empty());
+    // Keypirinha works without its configuration file, but not with an invalid history.
+    fs::create_dir_all(root.path().join("Local")).unwrap();
+    fs::write(
+        root.path().join("Local").join("History.ini"),
+        "[Calculator]\ncount = 2\n",
+    )
+    .unwrap();
+    let data = Launcher::Keypirinha.read(root.path()).unwrap();
+    assert_eq!(data.usage.len(), 1);
+    assert!(data.aliases.is_empty());
+    fs::write(
+        root.path().join("Local").join("History.ini"),
+        "not an ini file",
+    )
+    .unwrap();
+    assert!(Launcher::Keypirinha.read(root.path()).is_err());
+}
+
+#[test]
+fn test_import_flow_launcher() {
+    let root = tempfile::tempdir().unwrap();
+    let apps_dir = root.path().join("apps");
+    fs::create_dir_all(&apps_dir).unwrap();
+    fs::write(apps_dir.join("Visual Studio Code.lnk"), b"").unwrap();
+    fs::write(apps_dir.join("Notepad.exe"), b"").unwrap();
+    let paths = KasuriPaths::builder()
+        .data_dir(root.path())
+        .cache_dir(root.path().join("cache"))
+        .build();
+    fs::write(
+        paths.get_settings_file(),
+        format!(
+            "application_search_path_list = [{}]\n",
+            toml::Value::String(apps_dir.to_string_lossy().to_string())
+        ),
+    )
+    .unwrap();
+    let settings = Settings::load(paths.get_settings_file()).unwrap();
+    let mut kasuri = Kasuri::with_settings(settings, paths.clone()).unwrap();
+    kasuri.init().unwrap();
+    let data = Launcher::FlowLauncher.read(&fixture_dir("flow")).unwrap();
+
+    // The preview changes nothing.
+    let report = kasuri.handle_import_launcher(&data, true).unwrap();
+    assert_eq!(report.settings_added.len(), 1);
+    assert_eq!(report.applications.len(), 1);
+    assert!(report.applications[0].remapped);
+    assert_eq!(report.applications_not_found, vec!["Calculator"]);
+    assert_eq!(report.aliases_not_found, vec!["calc: Calculator"]);
+    assert_eq!(kasuri.handle_get_stats().unwrap().used_application_count, 0);
+    assert!(
+        !fs::read_to_string(paths.get_settings_file())
+            .unwrap()
+            .contains("code")
+    );
+
+    kasuri.handle_import_launcher(&data, false).unwrap();
+    let stats = kasuri.handle_get_stats().unwrap();
+    assert_eq!(stats.most_used_applications[0].name, "Visual Studio Code");
+    let saved = Settings::load(paths.get_settings_file()).unwrap();
+    assert_eq!(
+        saved.get_application_name_aliases()[0].aliases,
+        vec!["code"]
+    );
+
+    // Importing again does not add the alias twice.
+    let report = kasuri.handle_import_launcher(&data, true).unwrap();
+    assert!(report.settings_added.is_empty());
+}
@@ -0,0 +1,587 @@
+//! Integration tests that drive the full scan, sync, search and launch pipeline
+//! against a temporary directory.
+
+use kasuri::core::archive::{KasuriArchive, PathMapping};
+use kasuri::core::paths::KasuriPaths;
+use kasuri::core::policy::Policy;
+use kasuri::core::settings::Settings;
+use kasuri::model::action::ActionOutcome;
+use kasuri::model::event::KasuriEvent;
+use kasuri::{Kasuri, KasuriError};
+use std::fs;
+use std::path::Path;
+
+/// Creates the settings file and an application directory inside `root`.
+///
+/// The application directory contains two applications and a file
+/// that must not be picked up by the scan.
+fn prepare(root: &Path) -> KasuriPaths {
+    let apps_dir = root.join("apps");
+    fs::create_dir_all(apps_dir.join("tools")).unwrap();
+    fs::write(apps_dir.join("Notepad.exe"), b"").unwrap();
+    fs::write(apps_dir.join("tools").join("Calculator.lnk"), b"").unwrap();
+    fs::write(apps_dir.join("readme.txt"), b"").unwrap();
+
+    let paths = KasuriPaths::builder()
+        .data_dir(root)
+        .cache_dir(root.join("cache"))
+        .build();
+    let settings = format!(
+        "application_search_path_list = [{}]\napplication_search_interval_on_startup_minute = 60\n",
+        toml::Value::String(apps_dir.to_string_lossy().to_string())
+    );
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    paths
+}
+
+/// Creates and initializes a Kasuri instance for the given paths.
+fn start(paths: &KasuriPaths) -> Kasuri {
+    let settings = Settings::load(paths.get_settings_file()).unwrap();
+    let mut kasuri = Kasuri::with_settings(settings, paths.clone()).unwrap();
+    kasuri.init().unwrap();
+    kasuri
+}
+
+#[test]
+fn test_scan_and_search() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let kasuri = start(&paths);
+
+    let results = kasuri.handle_search_application("notepad");
+    assert_eq!(results[0].name, "Notepad");
+    assert!(Path::new(&results[0].icon_path).starts_with(paths.get_cache_dir()));
+
+    let results = kasuri.handle_search_application("calculator");
+    assert_eq!(results[0].name, "Calculator");
+
+    let results = kasuri.handle_search_application("readme");
+    assert!(results.iter().all(|app| app.name != "readme"));
+
+    assert!(paths.get_database_file().exists());
+}
+
+#[test]
+fn test_applications_are_synced_to_the_database() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    drop(start(&paths));
+
+    // The search interval has not passed, so the applications come from the database.
+    fs::remove_file(root.path().join("apps").join("Notepad.exe")).unwrap();
+    let kasuri = start(&paths);
+    let results = kasuri.handle_search_application("notepad");
+    assert_eq!(results[0].name, "Notepad");
+
+    // A rescan removes the deleted application.
+    let mut kasuri = kasuri;
+    kasuri.load_applications_to_cache().unwrap();
+    let results = kasuri.handle_search_application("notepad");
+    assert!(results.iter().all(|app| app.name != "Notepad"));
+}
+
+#[test]
+fn test_launch_failure_is_recorded() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    let app_id = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+    fs::remove_file(root.path().join("apps").join("Notepad.exe")).unwrap();
+
+    for expected_count in 1..=2 {
+        match kasuri.handle_launch_application(&app_id) {
+            Err(KasuriError::Launch(failure)) => {
+                assert_eq!(failure.app_id, app_id);
+                assert_eq!(failure.failure_count, expected_count);
+            }
+            result => panic!("Expected a launch error, got {:?}", result),
+        }
+    }
+
+    match kasuri.handle_launch_application("unknown") {
+        Err(KasuriError::ApplicationNotFound(app_id)) => assert_eq!(app_id, "unknown"),
+        result => panic!("Expected an application not found error, got {:?}", result),
+    }
+}
+
+#[test]
+fn test_forget_usage_updates_the_catalog() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    let app_id = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+    let events = kasuri.subscribe();
+
+    let outcome = kasuri
+        .handle_execute_action(&app_id, "forget-usage", None)
+        .unwrap();
+    assert_eq!(outcome, ActionOutcome::Done);
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![KasuriEvent::CatalogUpdated {
+            application_count: kasuri.get_applications().len()
+        }]
+    );
+}
+
+#[test]
+fn test_changed_settings_are_applied() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    // The app ID of a file based application is its path.
+    let notepad_path = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+    let events = kasuri.subscribe();
+
+    // Adding an alias makes the application searchable by the alias.
+    let other_apps_dir = root.path().join("other_apps");
+    fs::create_dir_all(&other_apps_dir).unwrap();
+    fs::write(other_apps_dir.join("Paint.exe"), b"").unwrap();
+    let settings = format!(
+        "application_search_path_list = [{}]\n[[application_name_aliases]]\npath = {}\nalias = \"editor\"\n",
+        toml::Value::String(root.path().join("apps").to_string_lossy().to_string()),
+        toml::Value::String(notepad_path),
+    );
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let changes = kasuri.reload_settings().unwrap();
+    assert!(changes.application_name_aliases);
+    assert!(!changes.application_search_path_list);
+    assert_eq!(
+        kasuri.handle_search_application("editor")[0].name,
+        "Notepad"
+    );
+
+    // Changing the search paths rescans the applications.
+    let settings = format!(
+        "application_search_path_list = [{}]\n",
+        toml::Value::String(other_apps_dir.to_string_lossy().to_string())
+    );
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let changes = kasuri.reload_settings().unwrap();
+    assert!(changes.application_search_path_list);
+    assert_eq!(kasuri.handle_search_application("paint")[0].name, "Paint");
+    assert!(kasuri.handle_search_application("notepad").is_empty());
+    assert!(kasuri.handle_search_application("editor").is_empty());
+
+    // Loading the same settings again changes nothing.
+    assert!(!kasuri.reload_settings().unwrap().is_any());
+    assert_eq!(events.try_iter().count(), 2);
+}
+
+#[test]
+fn test_edit_of_inactive_profile_is_kept() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    fs::write(
+        paths.get_settings_file(),
+        format!(
+            "{}\n[[profiles]]\nname = \"work\"\nwidth = 1000\n",
+            settings
+        ),
+    )
+    .unwrap();
+    let mut kasuri = start(&paths);
+
+    // Editing a profile that is not active changes no setting in effect.
+    let edited = fs::read_to_string(paths.get_settings_file())
+        .unwrap()
+        .replace("width = 1000", "width = 1200");
+    fs::write(paths.get_settings_file(), &edited).unwrap();
+    assert!(!kasuri.reload_settings().unwrap().is_any());
+
+    // Saving the settings keeps the edit.
+    let changes = kasuri.switch_profile("work").unwrap();
+    assert!(changes.width && changes.profiles);
+    assert_eq!(kasuri.settings.get_width(), 1200);
+    let saved = fs::read_to_string(paths.get_settings_file()).unwrap();
+    assert!(saved.contains("width = 1200"), "{}", saved);
+}
+
+#[test]
+fn test_policy_hides_applications() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let notepad = root.path().join("apps").join("Notepad.exe");
+    let policy = Policy::parse(&format!(
+        "[enforced]\napplication_hide_list = [{}]\n",
+        toml::Value::String(notepad.to_string_lossy().to_string())
+    ))
+    .unwrap();
+    let settings = Settings::load_with_policy(paths.get_settings_file(), &policy).unwrap();
+    let mut kasuri = Kasuri::with_settings(settings, paths.clone())
+        .unwrap()
+        .with_policy(policy);
+    kasuri.init().unwrap();
+
+    let results = kasuri.handle_search_application("notepad");
+    assert!(results.iter().all(|app| app.name != "Notepad"));
+    assert_eq!(kasuri.get_applications().len(), 1);
+
+    // Without the policy, the application is shown again.
+    kasuri.policy = Policy::default();
+    assert!(kasuri.reload_settings().unwrap().application_hide_list);
+    let results = kasuri.handle_search_application("notepad");
+    assert_eq!(results[0].name, "Notepad");
+}
+
+#[test]
+fn test_switch_profile() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let calculator = root
+        .path()
+        .join("apps")
+        .join("tools")
+        .join("Calculator.lnk");
+    let mut settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    settings.push_str(&format!(
+        "\n[[profiles]]\nname = \"work\"\nwidth = 1000\n\n[[profiles]]\nname = \"demo\"\nseparate_usage = true\napplication_hide_list = [{}]\n",
+        toml::Value::String(calculator.to_string_lossy().to_string())
+    ));
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let mut kasuri = start(&paths);
+    let notepad_id = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+    kasuri.handle_set_pinned(&notepad_id, true).unwrap();
+    let is_pinned = |kasuri: &Kasuri| {
+        kasuri
+            .get_applications()
+            .iter()
+            .any(|app| app.app_id == notepad_id && app.pinned)
+    };
+
+    // A profile without separate usage shares the database.
+    let changes = kasuri.switch_profile("work").unwrap();
+    assert!(changes.profiles && changes.width && !changes.usage_profile);
+    assert_eq!(kasuri.settings.get_width(), 1000);
+    assert!(is_pinned(&kasuri));
+
+    let changes = kasuri.switch_profile("demo").unwrap();
+    assert!(changes.usage_profile && changes.application_hide_list);
+    assert!(paths.get_profile_database_file(Some("demo")).exists());
+    assert!(kasuri.handle_search_application("calculator").is_empty());
+    assert!(!is_pinned(&kasuri));
+    // The active profile is saved.
+    let saved = Settings::load(paths.get_settings_file()).unwrap();
+    assert_eq!(saved.get_active_profile().unwrap().name, "demo");
+
+    kasuri.switch_profile("").unwrap();
+    assert!(is_pinned(&kasuri));
+    assert_eq!(
+        kasuri.handle_search_application("calculator")[0].name,
+        "Calculator"
+    );
+    // Switching the database rescans the applications, although it was searched recently.
+    fs::write(root.path().join("apps").join("Paint.exe"), b"").unwrap();
+    kasuri.switch_profile("demo").unwrap();
+    assert_eq!(kasuri.handle_search_application("paint")[0].name, "Paint");
+    assert!(kasuri.switch_profile("missing").is_err());
+}
+
+#[test]
+fn test_add_alias() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    let notepad_id = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+
+    let changes = kasuri.handle_add_alias(&notepad_id, "memo").unwrap();
+    assert!(changes.application_name_aliases);
+    kasuri.handle_add_alias(&notepad_id, "editor").unwrap();
+    for alias in ["memo", "editor"] {
+        assert_eq!(kasuri.handle_search_application(alias)[0].name, "Notepad");
+    }
+    assert!(matches!(
+        kasuri.handle_add_alias(&notepad_id, "Memo"),
+        Err(KasuriError::InvalidSettings(_))
+    ));
+    assert!(matches!(
+        kasuri.handle_add_alias("missing", "other"),
+        Err(KasuriError::ApplicationNotFound(_))
+    ));
+
+    // The aliases are saved for the application ID.
+    let saved = Settings::load(paths.get_settings_file()).unwrap();
+    let aliases = saved.get_application_name_aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].app_id, notepad_id);
+    assert_eq!(aliases[0].aliases, vec!["memo", "editor"]);
+
+    // Aliases locked by the policy cannot be added.
+    let policy = Policy::parse("[enforced]\napplication_name_aliases = []\n").unwrap();
+    let mut kasuri = start(&paths).with_policy(policy);
+    assert!(matches!(
+        kasuri.handle_add_alias(&notepad_id, "np"),
+        Err(KasuriError::InvalidSettings(_))
+    ));
+}
+
+#[test]
+fn test_application_overrides() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    let notepad_id = kasuri.handle_search_application("notepad")[0]
+        .app_id
+        .clone();
+    let icon = root.path().join("editor.png");
+    fs::write(&icon, b"png").unwrap();
+    let mut settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    settings.push_str(&format!(
+        "[[application_overrides]]\napp_id = {}\ndisplay_name = \"Text Editor\"\nicon = {}\n",
+        toml::Value::String(notepad_id.clone()),
+        toml::Value::String(icon.to_string_lossy().to_string())
+    ));
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let changes = kasuri.reload_settings().unwrap();
+    assert!(changes.application_overrides);
+
+    // The overrides are kept when the applications are scanned again.
+    for rescan in [false, true] {
+        if rescan {
+            kasuri.load_applications_to_cache().unwrap();
+        }
+        let results = kasuri.handle_search_application("text editor");
+        assert_eq!(results[0].name, "Text Editor");
+        assert!(Path::new(&results[0].icon_path).starts_with(paths.get_cache_dir()));
+        assert_eq!(fs::read(&results[0].icon_path).unwrap(), b"png");
+        // The original name still finds the application.
+        assert_eq!(
+            kasuri.handle_search_application("notepad")[0].app_id,
+            notepad_id
+        );
+    }
+
+    // An icon that cannot be copied falls back to the extracted icon.
+    fs::remove_file(&icon).unwrap();
+    kasuri.load_applications_to_cache().unwrap();
+    let results = kasuri.handle_search_application("text editor");
+    assert!(!results[0].icon_path.contains("override_"));
+}
+
+#[test]
+fn test_custom_entries() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+    let mut settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    settings.push_str(&format!(
+        "[[custom_entries]]\nname = \"Deploy staging\"\ncommand = {}\narguments = [\"--list\"]\nworking_dir = {}\n\n[[custom_entries]]\nname = \"Broken tool\"\ncommand = \"kasuri-missing-tool\"\n",
+        toml::Value::String(std::env::current_exe().unwrap().to_string_lossy().to_string()),
+        toml::Value::String(root.path().to_string_lossy().to_string())
+    ));
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let changes = kasuri.reload_settings().unwrap();
+    assert!(changes.custom_entries);
+
+    // Custom entries are ranked with the applications and launched like them.
+    let results = kasuri.handle_search_application("deploy");
+    assert_eq!(results[0].name, "Deploy staging");
+    assert_eq!(results[0].app_id, "custom:Deploy staging");
+    assert!(results[0].icon_path.is_empty());
+    kasuri
+        .handle_launch_application("custom:Deploy staging")
+        .unwrap();
+    match kasuri.handle_launch_application("custom:Broken tool") {
+        Err(KasuriError::Launch(failure)) => assert_eq!(failure.failure_count, 1),
+        result => panic!("Expected a launch error, got {:?}", result),
+    }
+
+    // The entries are stored with the applications, so their usage is kept after a restart.
+    let mut kasuri = start(&paths);
+    let stats = kasuri.handle_get_stats().unwrap();
+    assert_eq!(stats.most_used_applications[0].name, "Deploy staging");
+    kasuri
+        .handle_launch_application("custom:Deploy staging")
+        .unwrap();
+
+    // Removed entries disappear from the search results.
+    let settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    let settings = &settings[..settings.find("[[custom_entries]]").unwrap()];
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    kasuri.reload_settings().unwrap();
+    assert!(kasuri.handle_search_application("deploy").is_empty());
+}
+
+#[test]
+fn test_web_search_rows() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut settings = fs::read_to_string(paths.get_settings_file()).unwrap();
+    settings.push_str(concat!(
+        "web_search_fallback = \"ddg\"\n",
+        "[[web_search_keywords]]\nkeyword = \"jira\"\ntitle = \"Jira\"\n",
+        "url_template = \"https://example.atlassian.net/browse/{query}\"\nencoding = \"none\"\n",
+        "[[web_search_keywords]]\nkeyword = \"ddg\"\ntitle = \"DuckDuckGo\"\n",
+        "url_template = \"https://duckduckgo.com/?q={query}\"\n",
+    ));
+    fs::write(paths.get_settings_file(), settings).unwrap();
+    let mut kasuri = start(&paths);
+
+    // A keyword puts its web search above the applications.
+    let results = kasuri.handle_search_application("jira ABC-123");
+    assert_eq!(results[0].name, "Jira: ABC-123");
+    assert_eq!(results[0].app_id, "web:jira ABC-123");
+
+    // Other queries get the fallback web search below the applications.
+    let results = kasuri.handle_search_application("notepad");
+    assert_eq!(results[0].name, "Notepad");
+    let fallback = results.last().unwrap();
+    assert_eq!(fallback.name, "DuckDuckGo: notepad");
+    assert!(kasuri.handle_search_application("  ").is_empty());
+
+    let actions: Vec<String> = kasuri
+        .handle_get_application_actions(&fallback.app_id)
+        .unwrap()
+        .into_iter()
+        .map(|action| action.action_id)
+        .collect();
+    assert_eq!(actions, vec!["launch", "copy-url"]);
+    assert_eq!(
+        kasuri
+            .handle_execute_action("web:ddg c++ tips", "copy-url", None)
+            .unwrap(),
+        ActionOutcome::CopyToClipboard("https://duckduckgo.com/?q=c%2B%2B+tips".to_string())
+    );
+    assert!(matches!(
+        kasuri.handle_execute_action("web:ddg rust", "pin", None),
+        Err(KasuriError::Action(_))
+    ));
+    assert!(matches!(
+        kasuri.handle_launch_application("web:bing rust"),
+        Err(KasuriError::ApplicationNotFound(_))
+    ));
+}
+
+#[test]
+fn test_calculator_row() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+
+    let results = kasuri.handle_search_application("0x1F + 12");
+    assert_eq!(results[0].name, "0x1F + 12 = 43");
+    assert_eq!(results[0].app_id, "calc:0x1F + 12");
+    assert!(
+        kasuri
+            .handle_search_application("notepad")
+            .iter()
+            .all(|result| !result.app_id.starts_with("calc:"))
+    );
+
+    let actions: Vec<String> = kasuri
+        .handle_get_application_actions("calc:1920*1080/4")
+        .unwrap()
+        .into_iter()
+        .map(|action| action.action_id)
+        .collect();
+    assert_eq!(actions, vec!["copy-value"]);
+    assert_eq!(
+        kasuri
+            .handle_execute_action("calc:1920*1080/4", "copy-value", None)
+            .unwrap(),
+        ActionOutcome::CopyToClipboard("518400".to_string())
+    );
+    assert!(matches!(
+        kasuri.handle_launch_application("calc:1920*1080/4"),
+        Err(KasuriError::Action(_))
+    ));
+    assert!(matches!(
+        kasuri.handle_execute_action("calc:1 / 0", "copy-value", None),
+        Err(KasuriError::ApplicationNotFound(_))
+    ));
+}
+
+#[test]
+fn test_unit_conversion_row() {
+    let root = tempfile::tempdir().unwrap();
+    let paths = prepare(root.path());
+    let mut kasuri = start(&paths);
+
+    let results = kasuri.handle_search_application("3.5 GiB in MB");
+    assert_eq!(results[0].name, "3.5 GiB in MB = 3758.096384 MB");
+    assert_eq!(results[0].app_id, "calc:3.5 GiB in MB");
+
+    // Only the number is copied, without the unit
+    assert_eq!(
+        kasuri
+            .handle_execute_action("calc:2h30m in seconds", "copy-value", None)
+            .unwrap(),
+        ActionOutcome::CopyToClipboard("9000".to_string())
+    );
+    assert!(matches!(
+        kasuri.handle_get_application_actions("calc:5 mi to kg"),
+        Err(KasuriError::ApplicationNotFound(_))
+    ));
+}
+
+#[test]
+fn test_export_and_import_archive() {
+    let old_root = tempfile::tempdir().unwrap();
+    let old_paths = prepare(old_root.path());
+    let old_apps_dir = old_root.path().join("apps");
+    let mut settings = fs::read_to_string(old_paths.get_settings_file()).unwrap();
+    settings.push_str(&format!(
+        "application_name_aliases = [{{ path = {}, alias = \"np\" }}]\n",
+        toml::Value::String(
+            old_apps_dir
+                .join("Notepad.exe")
+                .to_string_lossy()
+                .to_string()
+        )
+    ));
+    fs::write(old_paths.get_settings_file(), settings).unwrap();
+    let mut old_kasuri = start(&old_paths);
+    let notepad_id = old_kasuri.handle_search_application("np")[0].app_id.clone();
+    old_kasuri.handle_set_pinned(&notepad_id, true).unwrap();
+    let archive_file = old_root.path().join("kasuri.json");
+    let archive = old_kasuri.handle_export_archive().unwrap();
+    assert_eq!(archive.applications.len(), 1);
+    archive.write(&archive_file).unwrap();
+
+    let new_root = tempfile::tempdir().unwrap();
+    let new_paths = prepare(new_root.path());
+    let new_apps_dir = new_root.path().join("apps");
+    let mut kasuri = start(&new_paths);
+    let archive = KasuriArchive::read(&archive_file).unwrap();
+    let is_pinned = |kasuri: &Kasuri| {
+        kasuri
+            .get_applications()
+            .iter()
+            .any(|app| app.name == "Notepad" && app.pinned)
+    };
+
+    // Without a mapping, the application is found by its file name.
+    let report = kasuri.handle_import_archive(&archive, &[], true).unwrap();
+    assert!(report.applications[0].remapped);
+    assert!(!is_pinned(&kasuri));
+
+    let mapping: PathMapping = format!(
+        "{}={}",
+        old_apps_dir.to_string_lossy(),
+        new_apps_dir.to_string_lossy()
+    )
+    .parse()
+    .unwrap();
+    let report = kasuri
+        .handle_import_archive(&archive, &[mapping], false)
+        .unwrap();
+    assert_eq!(report.settings_added.len(), 1);
+    assert!(!report.applications[0].remapped);
+    assert!(report.applications_not_found.is_empty());
+    assert!(is_pinned(&kasuri));
+    assert_eq!(kasuri.handle_search_application("np")[0].name, "Notepad");
+    let saved = Settings::load(new_paths.get_settings_file()).unwrap();
+    assert_eq!(saved.get_application_name_aliases()[0].aliases, vec!["np"]);
+    assert_eq!(saved.get_application_search_path_list().len(), 1);
+}
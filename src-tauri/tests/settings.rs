@@ -0,0 +1,804 @@
+//! Integration tests for loading and validating the settings file.
+
+use kasuri::core::policy::Policy;
+use kasuri::core::settings::{ApplicationNameAlias, Settings, SettingsFieldError};
+use kasuri::core::settings_backup;
+use kasuri::core::settings_migration::SETTINGS_VERSION;
+use std::fs;
+use std::path::Path;
+
+/// Writes `content` as the settings file in `root` and returns its path.
+fn write_settings(root: &Path, content: &str) -> std::path::PathBuf {
+    let path = root.join("settings.toml");
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// Returns the fields and indices of the errors, for comparing them in tests.
+fn fields(errors: &[SettingsFieldError]) -> Vec<(&str, Option<usize>)> {
+    errors.iter().map(|e| (e.field.as_str(), e.index)).collect()
+}
+
+#[test]
+fn test_valid_settings_have_no_errors() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(
+        root.path(),
+        &format!(
+            "application_search_path_list = [{}, \"WindowsStoreApp\"]\nlog_level = \"debug\"\nwidth = 800\nshortcut_key = \"Ctrl+Alt+Space\"\n",
+            toml::Value::String(root.path().to_string_lossy().to_string())
+        ),
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(settings.get_log_level(), "debug");
+    assert_eq!(settings.get_width(), 800);
+}
+
+#[test]
+fn test_invalid_fields_fall_back_to_defaults() {
+    let root = tempfile::tempdir().unwrap();
+    let missing_dir = root.path().join("missing");
+    let path = write_settings(
+        root.path(),
+        &format!(
+            r#"application_search_path_list = [{}]
+log_level = "verbose"
+width = 50
+shortcut_key = "Ctrl+Nothing"
+auto_startup = "yes"
+
+[[application_name_aliases]]
+path = "C:\\apps\\a.exe"
+alias = "editor"
+
+[[application_name_aliases]]
+path = "C:\\apps\\b.exe"
+alias = ""
+
+[[application_name_aliases]]
+path = "C:\\apps\\c.exe"
+alias = "Editor"
+"#,
+            toml::Value::String(missing_dir.to_string_lossy().to_string())
+        ),
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        vec![
+            ("auto_startup", None),
+            ("application_search_path_list", Some(0)),
+            ("log_level", None),
+            ("width", None),
+            ("shortcut_key", None),
+            ("application_name_aliases", Some(1)),
+            ("application_name_aliases", Some(2)),
+        ]
+    );
+
+    let defaults = Settings::default();
+    assert_eq!(settings.get_log_level(), defaults.get_log_level());
+    assert_eq!(settings.get_width(), defaults.get_width());
+    assert_eq!(settings.get_shortcut_key(), defaults.get_shortcut_key());
+    assert_eq!(settings.get_auto_startup(), defaults.get_auto_startup());
+    // A missing search path is only reported, the drive may be disconnected.
+    assert_eq!(settings.get_application_search_path_list().len(), 1);
+    let aliases = settings.get_application_name_aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].aliases, vec!["editor"]);
+    assert!(
+        settings
+            .validate()
+            .iter()
+            .all(|e| e.field == "application_search_path_list")
+    );
+}
+
+#[test]
+fn test_unparseable_file_is_an_error() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), "width = [800\n");
+    assert!(Settings::load_and_validate(&path).is_err());
+}
+
+#[test]
+fn test_corrupt_file_is_recovered_from_backup() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), "width = 900\n");
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(changed_dir.path(), "width = 1000\n");
+    Settings::load(&changed_path).unwrap().save(&path).unwrap();
+    // Simulates a crash in the middle of writing the file.
+    fs::write(&path, "").unwrap();
+    assert!(Settings::load(&path).is_err());
+
+    let settings = Settings::load_or_recover(&path, &Policy::default()).unwrap();
+    assert_eq!(settings.get_width(), 900);
+    assert!(root.path().join("settings.toml.corrupt").exists());
+    assert_eq!(Settings::load(&path).unwrap().get_width(), 900);
+}
+
+#[test]
+fn test_restore_backup() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), "settings_version = 2\nwidth = 1000\n");
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(changed_dir.path(), "settings_version = 2\nwidth = 900\n");
+    Settings::load(&changed_path).unwrap().save(&path).unwrap();
+    let backups = settings_backup::list_backups(&path).unwrap();
+    assert_eq!(backups.len(), 1);
+
+    let settings = Settings::restore_backup(&path, &backups[0].name, &Policy::default()).unwrap();
+    assert_eq!(settings.get_width(), 1000);
+    // The replaced settings are backed up as well.
+    assert_eq!(settings_backup::list_backups(&path).unwrap().len(), 2);
+    assert!(Settings::restore_backup(&path, "settings.toml", &Policy::default()).is_err());
+}
+
+/// A settings file as an administrator might write it, with comments,
+/// a custom key order and keys unknown to this version.
+const ANNOTATED_SETTINGS: &str = r#"# Managed by IT, do not edit by hand.
+settings_version = 2
+width = 900 # wide enough for long names
+log_level = "info"
+
+# Added by a newer version
+future_option = "keep me"
+
+[[application_name_aliases]]
+# Main editor
+path = "C:\\apps\\editor.exe"
+aliases = ["edit"]
+icon = "editor.ico"
+
+[future_table]
+enabled = true
+"#;
+
+#[test]
+fn test_save_without_changes_keeps_the_file() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), ANNOTATED_SETTINGS);
+
+    Settings::load(&path).unwrap().save(&path).unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), ANNOTATED_SETTINGS);
+    assert!(settings_backup::list_backups(&path).unwrap().is_empty());
+}
+
+#[test]
+fn test_save_keeps_comments_and_unknown_keys() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), ANNOTATED_SETTINGS);
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(
+        changed_dir.path(),
+        &ANNOTATED_SETTINGS
+            .replace("width = 900", "width = 1200")
+            .replace("aliases = [\"edit\"]", "aliases = [\"edit\", \"editor\"]")
+            .replace(
+                "log_level = \"info\"",
+                "log_level = \"info\"\nauto_startup = true",
+            ),
+    );
+
+    Settings::load(&changed_path).unwrap().save(&path).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        ANNOTATED_SETTINGS
+            .replace("width = 900 # wide", "width = 1200 # wide")
+            .replace(
+                "aliases = [\"edit\"]",
+                "aliases = [\n    \"edit\",\n    \"editor\",\n]"
+            )
+            .replace(
+                "future_option = \"keep me\"\n",
+                "future_option = \"keep me\"\nauto_startup = true\n"
+            )
+    );
+    assert_eq!(Settings::load(&path).unwrap().get_width(), 1200);
+}
+
+#[test]
+fn test_save_replaces_changed_alias_list() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), ANNOTATED_SETTINGS);
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(
+        changed_dir.path(),
+        &format!(
+            "{}\n[[application_name_aliases]]\npath = 'C:\\apps\\browser.exe'\naliases = ['web']\n",
+            ANNOTATED_SETTINGS
+        ),
+    );
+    let changed = Settings::load(&changed_path).unwrap();
+
+    changed.clone().save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(
+        saved.starts_with(
+            "# Managed by IT, do not edit by hand.\nsettings_version = 2\nwidth = 900"
+        )
+    );
+    assert!(saved.contains("future_option = \"keep me\""));
+    assert!(saved.contains("[future_table]\nenabled = true"));
+    assert_eq!(Settings::load(&path).unwrap(), changed);
+}
+
+/// Copies a settings file of a historical format from `tests/fixtures/settings` into `root`.
+fn copy_fixture(root: &Path, name: &str) -> std::path::PathBuf {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("settings")
+        .join(name);
+    write_settings(root, &fs::read_to_string(fixture).unwrap())
+}
+
+#[test]
+fn test_migrate_v0_fixture() {
+    let root = tempfile::tempdir().unwrap();
+    let path = copy_fixture(root.path(), "v0.toml");
+    let original = fs::read_to_string(&path).unwrap();
+
+    let settings = Settings::load(&path).unwrap();
+
+    assert_eq!(settings.get_settings_version(), SETTINGS_VERSION);
+    assert_eq!(settings.get_log_level(), "debug");
+    assert_eq!(settings.get_width(), 1000);
+    assert!(settings.get_auto_startup());
+    assert_eq!(settings.get_shortcut_key(), "ctrl+Space");
+    assert_eq!(
+        settings.get_application_name_aliases()[0].aliases,
+        vec!["memo"]
+    );
+    let migrated = fs::read_to_string(&path).unwrap();
+    assert!(migrated.contains(&format!("settings_version = {}", SETTINGS_VERSION)));
+    let backups = settings_backup::list_backups(&path).unwrap();
+    assert_eq!(backups.len(), 1);
+    let backup = settings_backup::get_backup_path(&path, &backups[0].name).unwrap();
+    assert_eq!(fs::read_to_string(backup).unwrap(), original);
+}
+
+#[test]
+fn test_migrate_v1_fixture() {
+    let root = tempfile::tempdir().unwrap();
+    let path = copy_fixture(root.path(), "v1.toml");
+
+    let settings = Settings::load(&path).unwrap();
+
+    assert_eq!(settings.get_settings_version(), SETTINGS_VERSION);
+    let aliases = settings.get_application_name_aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].aliases, vec!["memo"]);
+    assert!(aliases[0].path.ends_with("Notepad.lnk"));
+    let migrated = fs::read_to_string(&path).unwrap();
+    assert!(migrated.contains("aliases = [\"memo\"]"));
+    assert!(!migrated.contains("alias = "));
+}
+
+#[test]
+fn test_current_fixture_is_not_migrated() {
+    let root = tempfile::tempdir().unwrap();
+    let path = copy_fixture(root.path(), &format!("v{}.toml", SETTINGS_VERSION));
+    let original = fs::read_to_string(&path).unwrap();
+
+    let settings = Settings::load(&path).unwrap();
+
+    assert_eq!(settings.get_settings_version(), SETTINGS_VERSION);
+    assert!(settings.get_automation_api_enabled());
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    assert!(settings_backup::list_backups(&path).unwrap().is_empty());
+}
+
+#[test]
+fn test_paths_are_expanded_and_saved_unexpanded() {
+    let root = tempfile::tempdir().unwrap();
+    // Cargo sets CARGO_MANIFEST_DIR for the test process as well.
+    let content = "settings_version = 2\napplication_search_path_list = [\"$CARGO_MANIFEST_DIR/tests\"]\n\n[[application_name_aliases]]\npath = \"${CARGO_MANIFEST_DIR}/app.exe\"\naliases = [\"app\"]\n";
+    let path = write_settings(root.path(), content);
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    assert_eq!(
+        settings.get_application_search_path_list(),
+        vec![format!("{}/tests", manifest_dir)]
+    );
+    assert_eq!(
+        settings.get_application_name_aliases()[0].path,
+        format!("{}/app.exe", manifest_dir)
+    );
+
+    // The settings window and the settings file get the paths as written.
+    let serialized = serde_json::to_value(&settings).unwrap();
+    assert_eq!(
+        serialized["application_search_path_list"][0],
+        "$CARGO_MANIFEST_DIR/tests"
+    );
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(
+        changed_dir.path(),
+        &content.replace(
+            "settings_version = 2\n",
+            "settings_version = 2\nwidth = 1000\n",
+        ),
+    );
+    Settings::load(&changed_path).unwrap().save(&path).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("width = 1000"));
+    assert!(saved.contains("\"$CARGO_MANIFEST_DIR/tests\""));
+    assert!(saved.contains("\"${CARGO_MANIFEST_DIR}/app.exe\""));
+}
+
+#[test]
+fn test_alias_entries_match_by_id_name_and_path() {
+    let by_name = ApplicationNameAlias {
+        name: "visual studio code*".to_string(),
+        aliases: vec!["vsc".to_string(), "code".to_string()],
+        ..Default::default()
+    };
+    assert!(by_name.matches(
+        "C:\\Code.lnk",
+        "Visual Studio Code - Insiders",
+        "C:\\Code.lnk"
+    ));
+    assert!(!by_name.matches("C:\\Studio.lnk", "Android Studio", "C:\\Studio.lnk"));
+
+    let by_path = ApplicationNameAlias {
+        path: "*\\Microsoft VS Code\\Code.exe".to_string(),
+        aliases: vec!["vsc".to_string()],
+        ..Default::default()
+    };
+    assert!(by_path.matches(
+        "app",
+        "Code",
+        "C:\\Program Files\\Microsoft VS Code\\Code.exe"
+    ));
+    assert!(!by_path.matches(
+        "app",
+        "Code",
+        "C:\\Program Files\\Microsoft VS Code\\bin\\code.cmd"
+    ));
+
+    let by_id = ApplicationNameAlias::for_app_id(
+        "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App",
+        vec!["calc".to_string()],
+    );
+    assert!(by_id.matches(
+        "microsoft.windowscalculator_8wekyb3d8bbwe!app",
+        "Calculator",
+        "Microsoft.WindowsCalculator_11.2401.0.0_x64__8wekyb3d8bbwe"
+    ));
+    assert!(!ApplicationNameAlias::default().matches("", "", ""));
+}
+
+#[test]
+fn test_add_alias() {
+    let mut settings = Settings::default();
+    settings.add_alias("C:\\apps\\code.exe", "vsc").unwrap();
+    settings.add_alias("C:\\apps\\code.exe", " code ").unwrap();
+    let aliases = settings.get_application_name_aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].app_id, "C:\\apps\\code.exe");
+    assert_eq!(aliases[0].aliases, vec!["vsc", "code"]);
+
+    assert!(settings.add_alias("C:\\apps\\other.exe", "VSC").is_err());
+    assert!(settings.add_alias("C:\\apps\\other.exe", " ").is_err());
+    assert!(
+        settings
+            .validate()
+            .iter()
+            .all(|e| e.field != "application_name_aliases")
+    );
+}
+
+/// A policy that sets a default width and locks auto startup.
+const POLICY: &str = r#"[defaults]
+width = 1000
+
+[enforced]
+auto_startup = true
+"#;
+
+#[test]
+fn test_invalid_application_overrides_are_removed() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(
+        root.path(),
+        r#"settings_version = 2
+application_search_path_list = []
+
+[[application_overrides]]
+app_id = "$CARGO_MANIFEST_DIR/apps/edge.lnk"
+display_name = "Edge Beta"
+icon = "$CARGO_MANIFEST_DIR/icons/edge.png"
+
+[[application_overrides]]
+app_id = ""
+display_name = "Nothing"
+
+[[application_overrides]]
+app_id = "$CARGO_MANIFEST_DIR/APPS/EDGE.LNK"
+display_name = "Edge"
+
+[[application_overrides]]
+app_id = 'C:\apps\prog64.exe'
+
+[[application_overrides]]
+app_id = 'C:\apps\prog.exe'
+icon = 'C:\icons\prog.ico'
+"#,
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        (1..=4)
+            .map(|i| ("application_overrides", Some(i)))
+            .collect::<Vec<_>>()
+    );
+    let overrides = settings.get_application_overrides();
+    assert_eq!(overrides.len(), 1);
+    assert_eq!(overrides[0].display_name, "Edge Beta");
+    // The paths are expanded like the other paths of the settings.
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    assert_eq!(
+        overrides[0].app_id,
+        format!("{}/apps/edge.lnk", manifest_dir)
+    );
+    assert_eq!(
+        overrides[0].icon,
+        format!("{}/icons/edge.png", manifest_dir)
+    );
+}
+
+#[test]
+fn test_invalid_custom_entries_are_removed() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(
+        root.path(),
+        r#"settings_version = 2
+application_search_path_list = []
+
+[[custom_entries]]
+name = "Deploy staging"
+command = "$CARGO_MANIFEST_DIR/deploy.cmd"
+arguments = ["--env", "staging", "$CARGO_MANIFEST_DIR/deploy.json"]
+working_dir = "$CARGO_MANIFEST_DIR"
+environment = ["DEPLOY_TARGET=staging", "EMPTY="]
+show_console = true
+
+[[custom_entries]]
+name = ""
+command = "https://example.com"
+
+[[custom_entries]]
+name = "deploy STAGING"
+command = "https://example.com"
+
+[[custom_entries]]
+name = "Jira"
+command = " "
+
+[[custom_entries]]
+name = "Team share"
+command = '\\fileserver\team'
+environment = ["=value"]
+
+[[custom_entries]]
+name = "Wiki"
+command = "https://example.com/wiki"
+icon = "wiki.ico"
+"#,
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        (1..=5)
+            .map(|i| ("custom_entries", Some(i)))
+            .collect::<Vec<_>>()
+    );
+    let entries = settings.get_custom_entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].get_app_id(), "custom:Deploy staging");
+    assert!(entries[0].show_console);
+    assert_eq!(
+        entries[0].get_environment(),
+        vec![("DEPLOY_TARGET", "staging"), ("EMPTY", "")]
+    );
+    // The paths are expanded like the other paths of the settings, the arguments are not.
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    assert_eq!(entries[0].command, format!("{}/deploy.cmd", manifest_dir));
+    assert_eq!(entries[0].working_dir, manifest_dir);
+    assert_eq!(entries[0].arguments[2], "$CARGO_MANIFEST_DIR/deploy.json");
+}
+
+#[test]
+fn test_invalid_web_search_keywords_are_removed() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(
+        root.path(),
+        r#"settings_version = 2
+application_search_path_list = []
+web_search_fallback = "bing"
+
+[[web_search_keywords]]
+keyword = "g"
+url_template = "https://www.google.com/search?q={query}"
+
+[[web_search_keywords]]
+keyword = "G"
+url_template = "https://www.google.co.jp/search?q={query}"
+
+[[web_search_keywords]]
+keyword = "my search"
+url_template = "https://example.com/?q={query}"
+
+[[web_search_keywords]]
+keyword = "wiki"
+url_template = "https://example.com/wiki"
+encoding = "path"
+"#,
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        vec![
+            ("web_search_keywords", Some(1)),
+            ("web_search_keywords", Some(2)),
+            ("web_search_keywords", Some(3)),
+            ("web_search_fallback", None),
+        ]
+    );
+    let keywords = settings.get_web_search_keywords();
+    assert_eq!(keywords.len(), 1);
+    assert_eq!(keywords[0].keyword, "g");
+    assert!(settings.get_web_search_fallback().is_none());
+}
+
+#[test]
+fn test_policy_is_layered_over_the_settings_file() {
+    let root = tempfile::tempdir().unwrap();
+    let policy = Policy::parse(POLICY).unwrap();
+    let path = write_settings(
+        root.path(),
+        "settings_version = 2\nauto_startup = false\nlog_level = \"debug\"\n",
+    );
+
+    let settings = Settings::load_with_policy(&path, &policy).unwrap();
+    assert_eq!(settings.get_width(), 1000);
+    assert!(settings.get_auto_startup());
+    assert_eq!(settings.get_log_level(), "debug");
+
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(
+        changed_dir.path(),
+        "settings_version = 2\nlog_level = \"info\"\n",
+    );
+    let changed = Settings::load_with_policy(&changed_path, &policy).unwrap();
+    assert!(policy.check(&settings, &changed).is_empty());
+    changed.save_with_policy(&path, &policy).unwrap();
+    // The enforced value is not written, the value of the user is kept.
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "settings_version = 2\nauto_startup = false\nlog_level = \"info\"\n"
+    );
+
+    let unlocked = Settings::load(&path).unwrap();
+    let errors = policy.check(&settings, &unlocked);
+    assert_eq!(fields(&errors), vec![("auto_startup", None)]);
+}
+
+#[test]
+fn test_new_settings_file_leaves_out_policy_values() {
+    let root = tempfile::tempdir().unwrap();
+    let policy = Policy::parse(POLICY).unwrap();
+    let path = root.path().join("settings.toml");
+
+    let settings = Settings::load_with_policy(&path, &policy).unwrap();
+
+    assert_eq!(settings.get_width(), 1000);
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("log_level"));
+    assert!(!saved.contains("width"));
+    assert!(!saved.contains("auto_startup"));
+    // Changing the policy default changes the setting of the user.
+    let policy = Policy::parse(&POLICY.replace("width = 1000", "width = 1100")).unwrap();
+    assert_eq!(
+        Settings::load_with_policy(&path, &policy)
+            .unwrap()
+            .get_width(),
+        1100
+    );
+}
+
+#[test]
+fn test_profile_cannot_replace_enforced_settings() {
+    let root = tempfile::tempdir().unwrap();
+    let policy = Policy::parse(
+        r#"[enforced]
+application_hide_list = ['C:\apps\uninstall.exe']
+shortcut_key = "Alt+Space"
+"#,
+    )
+    .unwrap();
+    let source = r#"settings_version = 2
+application_search_path_list = []
+active_profile = "bypass"
+
+[[profiles]]
+name = "bypass"
+application_hide_list = []
+shortcut_key = "Ctrl+Space"
+width = 900
+separate_usage = false
+"#;
+    let path = write_settings(root.path(), source);
+
+    let settings = Settings::load_with_policy(&path, &policy).unwrap();
+    assert_eq!(
+        settings.get_application_hide_list(),
+        vec![r"C:\apps\uninstall.exe"]
+    );
+    assert_eq!(settings.get_shortcut_key(), "Alt+Space");
+    // Settings that are not enforced are still replaced by the profile.
+    assert_eq!(settings.get_width(), 900);
+
+    // Saving keeps the values of the profile in the file.
+    let mut changed = settings.clone();
+    changed.set_active_profile("").unwrap();
+    changed.clone().save_with_policy(&path, &policy).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        source.replace("active_profile = \"bypass\"", "active_profile = \"\"")
+    );
+
+    // Profiles that set enforced values cannot be saved.
+    let unlocked = Settings::load(&path).unwrap();
+    let errors = policy.check(&settings, &unlocked);
+    assert_eq!(
+        fields(&errors),
+        vec![
+            ("application_hide_list", None),
+            ("shortcut_key", None),
+            ("profiles", Some(0)),
+            ("profiles", Some(0)),
+        ]
+    );
+}
+
+/// Settings with a profile that replaces the width and the search paths.
+const PROFILE_SETTINGS: &str = r#"settings_version = 2
+application_search_path_list = []
+width = 800
+active_profile = "work"
+
+[[profiles]]
+name = "work"
+# Wide enough for long names
+width = 1000
+application_search_path_list = ["WindowsStoreApp"]
+
+[[profiles]]
+name = "demo"
+separate_usage = true
+"#;
+
+#[test]
+fn test_active_profile_replaces_settings() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), PROFILE_SETTINGS);
+
+    let (mut settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(settings.get_profile_names(), vec!["work", "demo"]);
+    assert_eq!(settings.get_width(), 1000);
+    assert_eq!(
+        settings.get_application_search_path_list(),
+        vec!["WindowsStoreApp"]
+    );
+    assert_eq!(settings.get_usage_profile(), None);
+
+    let general = {
+        let mut general = settings.clone();
+        general.set_active_profile("").unwrap();
+        general
+    };
+    assert_eq!(general.get_width(), 800);
+    let changes = settings.diff(&general);
+    assert!(changes.width && changes.application_search_path_list && changes.profiles);
+    assert!(!changes.usage_profile);
+
+    settings.set_active_profile("demo").unwrap();
+    assert_eq!(settings.get_width(), 800);
+    assert_eq!(settings.get_usage_profile(), Some("demo"));
+    assert!(settings.set_active_profile("missing").is_err());
+}
+
+#[test]
+fn test_invalid_profiles_are_removed() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(
+        root.path(),
+        &PROFILE_SETTINGS
+            .replace("active_profile = \"work\"", "active_profile = \"home\"")
+            .replace("name = \"demo\"", "name = \"Work\"")
+            .replace("width = 1000", "width = 10"),
+    );
+
+    let (settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        vec![
+            ("profiles", Some(0)),
+            ("profiles", Some(1)),
+            ("active_profile", None)
+        ]
+    );
+    assert!(settings.get_profile_names().is_empty());
+    assert!(settings.get_active_profile().is_none());
+}
+
+#[test]
+fn test_save_keeps_invalid_values_in_the_file() {
+    let root = tempfile::tempdir().unwrap();
+    let content = r#"settings_version = 2
+application_search_path_list = []
+width = 50
+
+[[application_name_aliases]]
+path = "C:\\apps\\a.exe"
+aliases = ["editor"]
+
+[[application_name_aliases]]
+path = "C:\\apps\\b.exe"
+aliases = [""]
+
+[[profiles]]
+name = "work"
+separate_usage = false
+"#;
+    let path = write_settings(root.path(), content);
+    let (mut settings, errors) = Settings::load_and_validate(&path).unwrap();
+    assert_eq!(
+        fields(&errors),
+        vec![("width", None), ("application_name_aliases", Some(1))]
+    );
+
+    // The invalid values are only replaced in memory, the user can still correct them.
+    settings.clone().save(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+    settings.set_active_profile("work").unwrap();
+    settings.save(&path).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("active_profile = \"work\""));
+    assert!(saved.contains("width = 50"));
+    assert!(saved.contains("path = \"C:\\\\apps\\\\b.exe\"\naliases = [\"\"]"));
+}
+
+#[test]
+fn test_save_removes_unset_profile_settings() {
+    let root = tempfile::tempdir().unwrap();
+    let path = write_settings(root.path(), PROFILE_SETTINGS);
+    let changed_dir = tempfile::tempdir().unwrap();
+    let changed_path = write_settings(
+        changed_dir.path(),
+        &PROFILE_SETTINGS.replace("# Wide enough for long names\nwidth = 1000\n", ""),
+    );
+
+    Settings::load(&changed_path).unwrap().save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("width = 1000"));
+    assert!(saved.contains("application_search_path_list = [\"WindowsStoreApp\"]"));
+    assert_eq!(Settings::load(&path).unwrap().get_width(), 800);
+}
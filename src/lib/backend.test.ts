@@ -1,6 +1,6 @@
 import { describe, it, expect, beforeEach, beforeAll, vi } from 'vitest';
-import { Backend } from './backend';
-import type { Application } from './backend';
+import { Backend, getSettingsFieldErrors, isKasuriError } from './backend';
+import type { Application, ApplicationAction, KasuriError } from './backend';
 import { mockIPC, clearMocks } from '@tauri-apps/api/mocks';
 
 beforeAll(() => {
@@ -78,7 +78,162 @@ describe('Backend', () => {
             }
         });
         const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
-        await backend.launch(app);
+        const result = await backend.launch(app);
+        expect(called).toBe(true);
+        expect(result).toBeNull();
+    });
+
+    it('launch returns the error if the backend rejects', async () => {
+        const error: KasuriError = {
+            code: 'launch',
+            message: "Failed to launch application 'App': File not found",
+            details: {
+                app_id: 'id',
+                name: 'App',
+                message: 'File not found',
+                failure_count: 2,
+            },
+        };
+        mockIPC((cmd) => {
+            if (cmd === 'launch_application') {
+                throw error;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        const result = await backend.launch(app);
+        expect(result).toEqual(error);
+    });
+
+    it('launch rethrows errors that are not from the backend', async () => {
+        mockIPC((cmd) => {
+            if (cmd === 'launch_application') {
+                throw new Error('IPC failure');
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        await expect(backend.launch(app)).rejects.toThrow('IPC failure');
+    });
+
+    it('isKasuriError detects backend errors', () => {
+        expect(isKasuriError({ code: 'scan', message: 'Scan error', details: null })).toBe(true);
+        expect(isKasuriError(new Error('IPC failure'))).toBe(false);
+        expect(isKasuriError(null)).toBe(false);
+        expect(isKasuriError('error')).toBe(false);
+    });
+
+    it('getSettingsFieldErrors returns the details of invalid_settings errors', () => {
+        const error: KasuriError = {
+            code: 'invalid_settings',
+            message: 'Invalid settings: width: Width must be between 200 and 7680',
+            details: [{ field: 'width', index: null, message: 'Width must be between 200 and 7680' }],
+        };
+        expect(getSettingsFieldErrors(error)).toEqual(error.details);
+        expect(getSettingsFieldErrors({ code: 'io', message: 'I/O error', details: null })).toEqual([]);
+        expect(getSettingsFieldErrors('error')).toEqual([]);
+    });
+
+    it('getSettings keeps the names of the locked fields', async () => {
+        mockIPC((cmd) => {
+            if (cmd === 'get_settings') {
+                return { auto_startup: true, locked_fields: ['auto_startup'] };
+            }
+        });
+        const settings = await backend.getSettings();
+        expect(settings.autoStartup).toBe(true);
+        expect(settings.lockedFields).toEqual(['auto_startup']);
+    });
+
+    it('saveSettings does not send the locked fields', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'get_settings') {
+                return { auto_startup: true, locked_fields: ['auto_startup'] };
+            }
+            if (cmd === 'save_settings') {
+                expect(args).toEqual({ settings: { auto_startup: true } });
+            }
+        });
+        await backend.saveSettings(await backend.getSettings());
+    });
+
+    it('restoreSettingsBackup calls invoke with correct args', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'restore_settings_backup') {
+                expect(args).toEqual({ name: 'settings-20250101-120000-000-000.toml' });
+                return null;
+            }
+        });
+        await expect(
+            backend.restoreSettingsBackup('settings-20250101-120000-000-000.toml'),
+        ).resolves.toBeUndefined();
+    });
+
+    it('restoreSettingsBackup throws if name is empty', async () => {
+        await expect(backend.restoreSettingsBackup('')).rejects.toThrow('Invalid backup name');
+    });
+
+    it('removeApplication calls invoke with correct args', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'remove_application') {
+                expect(args).toEqual({ appId: 'id' });
+                return null;
+            }
+        });
+        await expect(backend.removeApplication('id')).resolves.toBeUndefined();
+    });
+
+    it('removeApplication rejects with the backend error', async () => {
+        const error: KasuriError = {
+            code: 'application_not_found',
+            message: 'Application with ID id not found',
+            details: { app_id: 'id' },
+        };
+        mockIPC((cmd) => {
+            if (cmd === 'remove_application') {
+                throw error;
+            }
+        });
+        await expect(backend.removeApplication('id')).rejects.toEqual(error);
+    });
+
+    it('removeApplication throws if appId is empty', async () => {
+        await expect(backend.removeApplication('')).rejects.toThrow('Invalid application id');
+    });
+
+    it('addApplicationAlias calls invoke with correct args', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'add_application_alias') {
+                expect(args).toEqual({ appId: 'id', alias: 'vsc' });
+                return null;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        await expect(backend.addApplicationAlias(app, 'vsc')).resolves.toBeUndefined();
+    });
+
+    it('addApplicationAlias rejects with the backend error', async () => {
+        const error: KasuriError = {
+            code: 'invalid_settings',
+            message: 'Invalid settings: application_name_aliases: Duplicate alias: vsc',
+            details: [{ field: 'application_name_aliases', index: null, message: 'Duplicate alias: vsc' }],
+        };
+        mockIPC((cmd) => {
+            if (cmd === 'add_application_alias') {
+                throw error;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        await expect(backend.addApplicationAlias(app, 'vsc')).rejects.toEqual(error);
+    });
+
+    it('rescanApplications calls invoke', async () => {
+        let called = false;
+        mockIPC((cmd) => {
+            if (cmd === 'rescan_applications') {
+                called = true;
+                return null;
+            }
+        });
+        await backend.rescanApplications();
         expect(called).toBe(true);
     });
 
@@ -109,4 +264,48 @@ describe('Backend', () => {
         const app: Application = { name: 'App', app_id: null as any, icon_path: 'icon.png' };
         await expect(backend.launch(app)).rejects.toThrow('Invalid application object');
     });
+
+    it('getApplicationActions calls invoke with correct args', async () => {
+        const actions: ApplicationAction[] = [
+            { action_id: 'launch', name: 'Open', requires_arguments: false },
+        ];
+        mockIPC((cmd, args) => {
+            if (cmd === 'get_application_actions') {
+                expect(args).toEqual({ appId: 'id' });
+                return actions;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        const result = await backend.getApplicationActions(app);
+        expect(result).toEqual(actions);
+    });
+
+    it('getApplicationActions throws if app_id is empty', async () => {
+        const app: Application = { name: 'App', app_id: '', icon_path: 'icon.png' };
+        await expect(backend.getApplicationActions(app)).rejects.toThrow('Invalid application object');
+    });
+
+    it('executeAction calls invoke with correct args', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'execute_action') {
+                expect(args).toEqual({ appId: 'id', actionId: 'open-with-arguments', arguments: '--help' });
+                return null;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        const action: ApplicationAction = { action_id: 'open-with-arguments', name: 'Open with Arguments...', requires_arguments: true };
+        await expect(backend.executeAction(app, action, '--help')).resolves.toBeUndefined();
+    });
+
+    it('executeAction passes null arguments by default', async () => {
+        mockIPC((cmd, args) => {
+            if (cmd === 'execute_action') {
+                expect(args).toEqual({ appId: 'id', actionId: 'copy-path', arguments: null });
+                return null;
+            }
+        });
+        const app: Application = { name: 'App', app_id: 'id', icon_path: 'icon.png' };
+        const action: ApplicationAction = { action_id: 'copy-path', name: 'Copy Path', requires_arguments: false };
+        await expect(backend.executeAction(app, action)).resolves.toBeUndefined();
+    });
 });
@@ -5,9 +5,16 @@ const INVOKE_SEARCH_APPLICATION = "search_application";
 const INVOKE_CHANGED_CONTENT_SIZE = "changed_content_size";
 const INVOKE_CLOSE_WINDOW = "close_window";
 const INVOKE_LAUNCH_APPLICATION = "launch_application";
+const INVOKE_GET_APPLICATION_ACTIONS = "get_application_actions";
+const INVOKE_EXECUTE_ACTION = "execute_action";
+const INVOKE_REMOVE_APPLICATION = "remove_application";
+const INVOKE_ADD_APPLICATION_ALIAS = "add_application_alias";
+const INVOKE_RESCAN_APPLICATIONS = "rescan_applications";
 const INVOKE_GET_SETTINGS = "get_settings";
 const INVOKE_GET_DEFAULT_SETTINGS = "get_default_settings";
 const INVOKE_SAVE_SETTINGS = "save_settings";
+const INVOKE_GET_SETTINGS_BACKUPS = "get_settings_backups";
+const INVOKE_RESTORE_SETTINGS_BACKUP = "restore_settings_backup";
 const INVOKE_RESTART_APP = "restart_app";
 
 
@@ -23,6 +30,114 @@ export interface Application {
     icon_path: string;
 }
 
+/**
+ * Represents an action that can be executed on an application.
+ */
+export interface ApplicationAction {
+    /** The identifier of the action */
+    action_id: string;
+    /** The display name of the action */
+    name: string;
+    /** Whether the action needs arguments entered by the user */
+    requires_arguments: boolean;
+}
+
+/**
+ * Identifier of the action that the backend offers for applications that can get an alias.
+ * It is handled by the frontend with `Backend.addApplicationAlias`
+ * instead of being executed as an application action.
+ */
+export const ADD_ALIAS_ACTION_ID = "add-alias";
+
+/**
+ * Describes why an application could not be launched.
+ * Sent as the details of a "launch" error.
+ */
+export interface LaunchFailure {
+    /** The unique identifier of the application */
+    app_id: string;
+    /** The display name of the application, if known */
+    name: string | null;
+    /** Human readable description of the failure */
+    message: string;
+    /** Number of consecutive failed launches of the application */
+    failure_count: number;
+}
+
+/**
+ * Describes a problem with a single settings field.
+ * Sent as the details of an "invalid_settings" error.
+ */
+export interface SettingsFieldError {
+    /** Name of the field as in the settings file, e.g. "shortcut_key" */
+    field: string;
+    /** Index of the invalid entry for list fields */
+    index: number | null;
+    /** Human readable description of the problem */
+    message: string;
+}
+
+/**
+ * Describes a backup of the settings file.
+ */
+export interface SettingsBackup {
+    /** File name of the backup, used to restore it */
+    name: string;
+    /** Time the backup was created, in RFC 3339 format */
+    created_at: string;
+    /** Size of the backup in bytes */
+    size: number;
+}
+
+/**
+ * Stable error codes sent by the backend.
+ */
+export type KasuriErrorCode =
+    | "settings"
+    | "invalid_settings"
+    | "database"
+    | "scan"
+    | "icon"
+    | "application_not_found"
+    | "launch"
+    | "action"
+    | "platform"
+    | "archive"
+    | "io";
+
+/**
+ * Error returned by the backend when a command fails.
+ */
+export interface KasuriError {
+    /** The stable error code */
+    code: KasuriErrorCode;
+    /** Human readable description of the error */
+    message: string;
+    /** Additional details, the launch failure for "launch" errors */
+    details: LaunchFailure | { app_id: string } | SettingsFieldError[] | null;
+}
+
+/**
+ * Checks whether the given value is an error returned by the backend.
+ * @param e The value to be checked.
+ * @returns True if the value is a KasuriError.
+ */
+export function isKasuriError(e: unknown): e is KasuriError {
+    return typeof e === "object" && e !== null && "code" in e && "message" in e;
+}
+
+/**
+ * Returns the field errors of an "invalid_settings" error.
+ * @param e The value thrown by a backend call.
+ * @returns The field errors, or an empty array for any other error.
+ */
+export function getSettingsFieldErrors(e: unknown): SettingsFieldError[] {
+    if (!isKasuriError(e) || e.code !== "invalid_settings" || !Array.isArray(e.details)) {
+        return [];
+    }
+    return e.details;
+}
+
 /**
  * Backend class for interacting with Tauri backend services.
  * Provides methods for searching applications, managing window state,
@@ -85,14 +200,102 @@ export class Backend {
     /**
      * Launches an application based on the provided application object.
      * @param application The application object to be launched.
-     * @returns A promise that resolves when the application is launched.
+     * @returns A promise that resolves to null when the application is launched,
+     * or to the backend error if the application could not be launched.
+     */
+    public async launch(application: Application): Promise<KasuriError | null> {
+        if (!application || !application.app_id) {
+            throw new Error("Invalid application object");
+        }
+        try {
+            await invoke(INVOKE_LAUNCH_APPLICATION, {
+                appId: application.app_id,
+            });
+        } catch (e) {
+            if (isKasuriError(e)) {
+                return e;
+            }
+            throw e;
+        }
+        return null;
+    }
+
+    /**
+     * Removes an application from the application list.
+     * @param appId The unique identifier of the application to be removed.
+     * @returns A promise that resolves when the application is removed,
+     * or rejects with a KasuriError.
      */
-    public async launch(application: Application): Promise<void> {
+    public async removeApplication(appId: string): Promise<void> {
+        if (!appId) {
+            throw new Error("Invalid application id");
+        }
+        await invoke(INVOKE_REMOVE_APPLICATION, {
+            appId,
+        });
+    }
+
+    /**
+     * Adds an alias to an application and saves it to the settings.
+     * @param application The application object to add the alias to.
+     * @param alias The alias to be added.
+     * @returns A promise that resolves when the alias is saved,
+     * or rejects with a KasuriError ("invalid_settings" if the alias is empty,
+     * already used or locked by the policy).
+     */
+    public async addApplicationAlias(application: Application, alias: string): Promise<void> {
         if (!application || !application.app_id) {
             throw new Error("Invalid application object");
         }
-        await invoke(INVOKE_LAUNCH_APPLICATION, {
+        await invoke(INVOKE_ADD_APPLICATION_ALIAS, {
             appId: application.app_id,
+            alias,
+        });
+    }
+
+    /**
+     * Rescans the application search paths.
+     * @returns A promise that resolves when the applications are reloaded,
+     * or rejects with a KasuriError.
+     */
+    public async rescanApplications(): Promise<void> {
+        await invoke(INVOKE_RESCAN_APPLICATIONS);
+    }
+
+    /**
+     * Retrieves the actions available for the provided application.
+     * @param application The application object to get the actions for.
+     * @returns A promise that resolves to an array of ApplicationAction objects.
+     */
+    public async getApplicationActions(application: Application): Promise<ApplicationAction[]> {
+        if (!application || !application.app_id) {
+            throw new Error("Invalid application object");
+        }
+        return invoke(INVOKE_GET_APPLICATION_ACTIONS, {
+            appId: application.app_id,
+        });
+    }
+
+    /**
+     * Executes an action on the provided application.
+     * @param application The application object to execute the action on.
+     * @param action The action to be executed.
+     * @param args The arguments for actions that require them.
+     * @returns A promise that resolves when the action is executed,
+     * or rejects with a KasuriError.
+     */
+    public async executeAction(
+        application: Application,
+        action: ApplicationAction,
+        args: string | null = null,
+    ): Promise<void> {
+        if (!application || !application.app_id) {
+            throw new Error("Invalid application object");
+        }
+        await invoke(INVOKE_EXECUTE_ACTION, {
+            appId: application.app_id,
+            actionId: action.action_id,
+            arguments: args,
         });
     }
 
@@ -122,12 +325,39 @@ export class Backend {
 
     /**
      * Saves the settings to the backend.
+     * The locked fields are reported by the backend and are not sent back.
      * @param settings The settings object to be saved.
-     * @returns A promise that resolves when the settings are saved.
+     * @returns A promise that resolves when the settings are saved,
+     * or rejects with a KasuriError ("invalid_settings" if validation failed
+     * or a setting locked by the policy was changed).
      */
-    public async saveSettings(settings: Settings): Promise<boolean> {
-        return await invoke(INVOKE_SAVE_SETTINGS, {
-            settings: this.transformForBackend(settings)
+    public async saveSettings(settings: Settings): Promise<void> {
+        const { lockedFields: _lockedFields, ...values } = settings;
+        await invoke(INVOKE_SAVE_SETTINGS, {
+            settings: this.transformForBackend(values)
+        });
+    }
+
+    /**
+     * Retrieves the backups of the settings file.
+     * @returns A promise that resolves to the backups, newest first.
+     */
+    public async getSettingsBackups(): Promise<SettingsBackup[]> {
+        return invoke(INVOKE_GET_SETTINGS_BACKUPS);
+    }
+
+    /**
+     * Restores a backup of the settings file and applies it.
+     * @param name The file name of the backup to restore.
+     * @returns A promise that resolves when the backup is restored,
+     * or rejects with a KasuriError.
+     */
+    public async restoreSettingsBackup(name: string): Promise<void> {
+        if (!name) {
+            throw new Error("Invalid backup name");
+        }
+        await invoke(INVOKE_RESTORE_SETTINGS_BACKUP, {
+            name,
         });
     }
 
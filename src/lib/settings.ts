@@ -2,13 +2,25 @@
  * Settings interface.
  */
 export type Settings = {
+    settingsVersion: number;
     applicationSearchPathList: string[];
     applicationSearchIntervalOnStartupMinute: number;
     logLevel: LogLevel;
     width: number;
     autoStartup: boolean;
     shortcutKey: string;
+    automationApiEnabled: boolean;
+    applicationHideList: string[];
+    activeProfile: string;
     applicationNameAliases: ApplicationNameAlias[];
+    applicationOverrides: ApplicationOverride[];
+    customEntries: CustomEntry[];
+    webSearchKeywords: WebSearchKeyword[];
+    /** Keyword searched with any query that matches no keyword, empty for none */
+    webSearchFallback: string;
+    profiles: SettingsProfile[];
+    /** Names of the settings locked by the machine-wide policy, as in the settings file */
+    lockedFields?: string[];
 }
 
 /**
@@ -22,9 +34,74 @@ export enum LogLevel {
 }
 
 /**
- * Application name alias.
+ * Aliases for the applications that match all criteria that are set.
+ * `name` and `path` are patterns where `*` matches any characters.
  */
-type ApplicationNameAlias = {
-    path: string;
-    alias: string;
+export type ApplicationNameAlias = {
+    appId?: string;
+    name?: string;
+    path?: string;
+    aliases: string[];
+};
+
+/**
+ * Display name and icon that replace those of the application with the ID.
+ * Empty values keep the name or icon of the application.
+ */
+export type ApplicationOverride = {
+    appId: string;
+    displayName?: string;
+    icon?: string;
+};
+
+/**
+ * Entry of the search results defined in the settings, e.g. a script, a folder or a URL.
+ * URLs, folders and files other than programs are opened with their default handler.
+ */
+export type CustomEntry = {
+    name: string;
+    command: string;
+    arguments?: string[];
+    workingDir?: string;
+    /** Environment variables written as `NAME=value` */
+    environment?: string[];
+    icon?: string;
+    showConsole: boolean;
+};
+
+/**
+ * Keyword that opens a web search, e.g. `g rust` for `https://www.google.com/search?q=rust`.
+ * `{query}` in the URL template is replaced with the encoded query.
+ */
+export type WebSearchKeyword = {
+    keyword: string;
+    title?: string;
+    urlTemplate: string;
+    encoding?: UrlEncoding;
+};
+
+/**
+ * How the query of a web search is encoded
+ */
+export enum UrlEncoding {
+    /** Spaces become `+` */
+    Query = 'query',
+    /** Spaces become `%20` */
+    Path = 'path',
+    /** The query is inserted as typed */
+    None = 'none'
+}
+
+/**
+ * Settings profile.
+ * The settings that are set replace the general settings while the profile is active.
+ */
+export type SettingsProfile = {
+    name: string;
+    separateUsage: boolean;
+    applicationSearchPathList?: string[];
+    applicationHideList?: string[];
+    applicationNameAliases?: ApplicationNameAlias[];
+    width?: number;
+    shortcutKey?: string;
 };
\ No newline at end of file